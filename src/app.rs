@@ -1,3 +1,4 @@
+use std::path::PathBuf;
 use std::process::Command;
 
 use nucleo_matcher::pattern::{CaseMatching, Normalization, Pattern};
@@ -7,15 +8,33 @@ use ratatui_interact::components::TreeNode;
 use ratatui_interact::state::FocusManager;
 use ratatui_interact::traits::ClickRegionRegistry;
 use ratatui_themes::{ThemeName, ThemePalette};
+use serde::{Deserialize, Serialize};
+use usage::spec::arg::SpecDoubleDashChoices;
 use usage::{Spec, SpecCommand, SpecFlag};
 
 use crate::components::arg_panel::{ArgPanelAction, ArgPanelComponent, ArgPanelEnterRequest};
 use crate::components::command_panel::{CommandPanelAction, CommandPanelComponent};
-use crate::components::execution::{ExecutionAction, ExecutionComponent};
+use crate::components::dir_picker::{DirPickerAction, DirPickerComponent};
+use crate::components::env_editor::{EnvEditorAction, EnvEditorComponent, EnvSuggestion};
+use crate::components::execution::{stop_executions, ExecutionAction, ExecutionComponent};
 use crate::components::filterable::{FilterAction, FilterableComponent};
 use crate::components::flag_panel::{FlagPanelAction, FlagPanelComponent, FlagPanelEnterRequest};
+use crate::components::picker::{PickerAction, PickerComponent, PickerItem};
+use crate::components::prompt::{PromptAction, PromptComponent};
 use crate::components::theme_picker::{ThemePickerAction, ThemePickerComponent};
 use crate::components::{Component, EventResult};
+use crate::config::{OutputLayout, PreviewEnter, RerunOutput};
+use crate::env::EnvVar;
+use crate::favorites::{Favorite, Favorites};
+use crate::history::{History, HistoryEntry};
+use crate::jobs::Job;
+use crate::spec_cache::{SpecCache, SpecRefresh};
+use crate::spec_source::SpecSource;
+use crate::validation::Diagnostic;
+use crate::watch::Watcher;
+
+/// Most directories listed in the Recent tab of the working directory overlay.
+const MAX_RECENT_DIRS: usize = 10;
 
 /// Per-field match scores for an item (command or flag).
 /// Keeps name and help scores separate so highlighting can be applied
@@ -36,11 +55,15 @@ impl MatchScores {
 }
 
 /// Actions that the event loop should take after handling a key.
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Action {
     None,
     Quit,
     Execute,
+    /// Exit and print the command instead of running it.
+    Print,
+    /// Stop the latest run and start the current command in its place.
+    Rerun,
 }
 
 /// Whether the app is in command-builder mode or execution mode.
@@ -59,10 +82,21 @@ pub enum Focus {
     Flags,
     Args,
     Preview,
+    /// The output pane of the split layouts.
+    Output,
+}
+
+/// What the text prompt's input is used for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PromptKind {
+    /// Name for saving the current command as a favorite.
+    SaveFavorite,
+    /// A command line to load into the builder.
+    CommandLine,
 }
 
 /// Tracks the value set for a flag.
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum FlagValue {
     /// Boolean flag toggled on/off.
     Bool(bool),
@@ -72,6 +106,27 @@ pub enum FlagValue {
     String(String),
     /// Count flag (e.g., -vvv).
     Count(u32),
+    /// Repeatable flag (`var=#true`) with one value per occurrence.
+    Multi(Vec<String>),
+}
+
+impl FlagValue {
+    /// Whether the value differs from the "not given" state and would
+    /// affect the built command.
+    pub fn is_set(&self) -> bool {
+        match self {
+            FlagValue::Bool(b) => *b,
+            FlagValue::NegBool(state) => state.is_some(),
+            FlagValue::String(s) => !s.is_empty(),
+            FlagValue::Count(c) => *c > 0,
+            FlagValue::Multi(values) => !values.is_empty(),
+        }
+    }
+
+    /// Whether two values are of the same variant (i.e. belong to the same kind of flag).
+    fn same_kind(&self, other: &FlagValue) -> bool {
+        std::mem::discriminant(self) == std::mem::discriminant(other)
+    }
 }
 
 /// State for one positional argument's user-entered value.
@@ -82,6 +137,57 @@ pub struct ArgValue {
     pub required: bool,
     pub choices: Vec<String>,
     pub help: Option<String>,
+    /// Variadic argument (`var=#true`): its values are kept in `values`
+    /// and `value` stays empty.
+    pub var: bool,
+    /// Values of a variadic argument, each passed as a separate argument.
+    pub values: Vec<String>,
+    /// Minimum and maximum number of values for a variadic argument.
+    pub var_min: Option<usize>,
+    pub var_max: Option<usize>,
+    /// How the spec wants `--` handled before this argument.
+    pub double_dash: SpecDoubleDashChoices,
+}
+
+impl ArgValue {
+    /// Whether a value has been given.
+    pub fn is_set(&self) -> bool {
+        if self.var {
+            !self.values.is_empty()
+        } else {
+            !self.value.is_empty()
+        }
+    }
+}
+
+/// A saved argument value, keyed by argument name.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SavedArg {
+    pub name: String,
+    pub value: String,
+    /// Values of a variadic argument.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub values: Vec<String>,
+}
+
+/// Serializable copy of the builder state: the selected command and all
+/// flag and argument values. Used to restore a previously built command.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct BuilderSnapshot {
+    pub command_path: Vec<String>,
+    /// Flag values keyed by command path (joined by space), as in `App::flag_values`.
+    pub flag_values: std::collections::HashMap<String, Vec<(String, FlagValue)>>,
+    /// Argument values keyed by command path (joined by space).
+    pub arg_values: std::collections::HashMap<String, Vec<SavedArg>>,
+    /// Extra arguments (the text typed after `--`) keyed by command path.
+    #[serde(default, skip_serializing_if = "std::collections::HashMap::is_empty")]
+    pub extra_args: std::collections::HashMap<String, String>,
+    /// Environment variable overrides keyed by command path.
+    #[serde(default, skip_serializing_if = "std::collections::HashMap::is_empty")]
+    pub env: std::collections::HashMap<String, Vec<EnvVar>>,
+    /// Directory the command runs in, when it isn't TuiSage's own.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cwd: Option<PathBuf>,
 }
 
 /// Data stored in each tree node for a command.
@@ -123,6 +229,12 @@ pub struct UiLayout {
     pub flag_overlay_rect: Option<Rect>,
     pub arg_overlay_rect: Option<Rect>,
     pub theme_overlay_rect: Option<Rect>,
+    pub history_overlay_rect: Option<Rect>,
+    pub favorites_overlay_rect: Option<Rect>,
+    pub jobs_overlay_rect: Option<Rect>,
+    pub prompt_overlay_rect: Option<Rect>,
+    pub env_overlay_rect: Option<Rect>,
+    pub dir_overlay_rect: Option<Rect>,
     pub theme_indicator_rect: Option<Rect>,
 }
 
@@ -133,6 +245,12 @@ impl UiLayout {
             flag_overlay_rect: None,
             arg_overlay_rect: None,
             theme_overlay_rect: None,
+            history_overlay_rect: None,
+            favorites_overlay_rect: None,
+            jobs_overlay_rect: None,
+            prompt_overlay_rect: None,
+            env_overlay_rect: None,
+            dir_overlay_rect: None,
             theme_indicator_rect: None,
         }
     }
@@ -162,8 +280,22 @@ pub struct App {
     /// Current app mode (builder vs executing).
     pub mode: AppMode,
 
-    /// Execution component when a command is running.
-    pub execution: Option<ExecutionComponent>,
+    /// Commands started from the builder, running or finished, in the
+    /// order they were started.
+    pub jobs: Vec<Job>,
+
+    /// Runs replaced by a rerun while still running, recorded in the
+    /// history once they have stopped.
+    stopping: Vec<Job>,
+
+    /// Id of the job shown in the execution view.
+    foreground_job: Option<usize>,
+
+    /// Id for the next job.
+    next_job_id: usize,
+
+    /// Jobs list overlay component.
+    pub jobs_picker: PickerComponent,
 
     /// Current color theme.
     pub theme_name: ThemeName,
@@ -189,6 +321,14 @@ pub struct App {
     /// Arg values for the current command.
     pub arg_values: Vec<ArgValue>,
 
+    /// Free-form extra arguments passed after `--`, as typed, keyed by
+    /// command path.
+    extra_args_by_path: std::collections::HashMap<String, String>,
+
+    /// Environment variable overrides for the next run, keyed by command
+    /// path.
+    env_by_path: std::collections::HashMap<String, Vec<EnvVar>>,
+
     /// Focus manager for Tab navigation between panels.
     pub focus_manager: FocusManager<Focus>,
 
@@ -203,9 +343,68 @@ pub struct App {
 
     /// Current mouse cursor position (column, row) for hover highlighting.
     pub mouse_position: Option<(u16, u16)>,
+
+    /// Command history (in-memory unless loaded from a file).
+    pub history: History,
+
+    /// History picker overlay component.
+    pub history_picker: PickerComponent,
+
+    /// Saved favorites for this spec (in-memory unless loaded from a file).
+    pub favorites: Favorites,
+
+    /// Favorites picker overlay component.
+    pub favorites_picker: PickerComponent,
+
+    /// Text prompt overlay and what its input is for.
+    pub prompt: PromptComponent,
+    prompt_kind: Option<PromptKind>,
+
+    /// Environment variables overlay component.
+    pub env_editor: EnvEditorComponent,
+
+    /// Directory the next run happens in, or `None` for TuiSage's own
+    /// working directory.
+    pub cwd: Option<PathBuf>,
+
+    /// Directories chosen in this session, most recent first.
+    chosen_dirs: Vec<PathBuf>,
+
+    /// Working directory overlay component.
+    pub dir_picker: DirPickerComponent,
+
+    /// Transient message shown in the help bar until the next key press.
+    pub status_message: Option<String>,
+
+    /// Where the spec was read from, so it can be reloaded.
+    spec_source: Option<SpecSource>,
+
+    /// Replaces the `bin` of a reloaded spec (`--cmd`, or configured).
+    spec_bin: Option<String>,
+
+    /// Watches the spec file to reload it when it changes.
+    spec_watcher: Option<Watcher>,
+
+    /// Cache for the spec command's output.
+    spec_cache: Option<SpecCache>,
+
+    /// The spec command running in the background to check a cached spec.
+    spec_refresh: Option<SpecRefresh>,
+
+    /// Why reloading the spec failed, shown in a banner until a reload
+    /// succeeds.
+    pub spec_error: Option<String>,
+
+    /// Set when running an invalid command (or quitting with jobs running)
+    /// was refused, so that repeating the request right away goes ahead.
+    unconfirmed_action: Option<Action>,
+
+    /// User configuration (defaults unless loaded from a file).
+    pub config: crate::config::Config,
 }
 
 impl App {
+    #[allow(dead_code)] // used in tests; main builds the app with the configured theme
     pub fn new(spec: Spec) -> Self {
         Self::with_theme(spec, ThemeName::default())
     }
@@ -215,36 +414,572 @@ impl App {
         self.mode == AppMode::Executing
     }
 
-    /// Close the execution view and return to the builder.
+    /// The execution component shown in the execution view.
+    #[cfg(test)]
+    pub fn execution(&self) -> Option<&ExecutionComponent> {
+        let id = self.foreground_job?;
+        self.jobs.iter().find(|j| j.id == id).map(|j| &j.execution)
+    }
+
+    pub fn execution_mut(&mut self) -> Option<&mut ExecutionComponent> {
+        let id = self.foreground_job?;
+        self.jobs.iter_mut().find(|j| j.id == id).map(|j| &mut j.execution)
+    }
+
+    /// Whether the foreground job is shown in a pane next to the builder.
+    pub fn has_output_pane(&self) -> bool {
+        self.config.behavior.layout.is_split()
+            && !self.is_executing()
+            && self.foreground_job.is_some()
+    }
+
+    /// Close the execution view or output pane and return to the builder.
     pub fn close_execution(&mut self) {
+        if let Some(id) = self.foreground_job.take() {
+            if let Some(index) = self.jobs.iter().position(|j| j.id == id) {
+                let mut job = self.jobs.remove(index);
+                self.record_run(&mut job);
+            }
+        }
         self.mode = AppMode::Builder;
-        self.execution = None;
+        self.remove_output_pane();
+    }
+
+    /// Move the focus off the output pane once it is no longer shown.
+    fn remove_output_pane(&mut self) {
+        if self.focus() == Focus::Output {
+            self.set_focus(Focus::Preview);
+        }
+        self.rebuild_focus_manager();
+    }
+
+    /// Record the latest run of a job in the history, and save its
+    /// transcript if a transcript directory is configured.
+    fn record_run(&mut self, job: &mut Job) {
+        // With a transcript directory configured, every run is kept
+        if let Some(ref dir) = self.config.behavior.transcript_dir {
+            if job.execution.transcript_path().is_none() {
+                let _ = job.execution.save_transcript(dir);
+            }
+        }
+        if let Some(mut entry) = job.history.clone() {
+            entry.exit_status = job.execution.exit_status();
+            let _ = self.history.record(entry);
+        }
+    }
+
+    /// Run a job's command again in place, with the same argv, environment
+    /// and working directory, recording the finished run. A run still going
+    /// is sent SIGTERM instead, and `poll_watches()` starts the new one once
+    /// it has exited.
+    fn run_job_again(&mut self, id: usize) {
+        let Some(index) = self.jobs.iter().position(|j| j.id == id) else {
+            return;
+        };
+        if self.jobs[index].is_running() {
+            let job = &mut self.jobs[index];
+            job.execution.stop(self.config.behavior.kill_after.0);
+            job.restarting = true;
+            return;
+        }
+        let mut job = self.jobs.remove(index);
+        job.restarting = false;
+        self.record_run(&mut job);
+        let append = self.config.behavior.rerun_output == RerunOutput::Append;
+        match job.execution.run_again(append) {
+            Ok(()) => {
+                job.finish_reported = false;
+                if let Some(ref mut entry) = job.history {
+                    entry.timestamp = crate::history::now();
+                }
+            }
+            Err(e) => job.execution.report(format!("failed to run again: {e}")),
+        }
+        self.jobs.insert(index, job);
+    }
+
+    /// Start or stop running the foreground job's command again whenever
+    /// the watched files change, or at the configured interval.
+    fn toggle_watch(&mut self) {
+        let config = self.config.watch.clone();
+        let Some(exec) = self.execution_mut() else {
+            return;
+        };
+        if exec.is_watching() {
+            exec.set_watch(None);
+            return;
+        }
+        let Some(run_dir) = exec.run_dir() else {
+            return;
+        };
+        let dir = match config.dir {
+            Some(ref dir) => run_dir.join(crate::paths::expand_home(&dir.to_string_lossy())),
+            None => run_dir,
+        };
+        match Watcher::from_config(&config, &dir) {
+            Ok(watcher) => exec.set_watch(Some(watcher)),
+            Err(e) => exec.report(e.to_string()),
+        }
+    }
+
+    /// Number of jobs in watch mode.
+    pub fn watching_jobs(&self) -> usize {
+        self.jobs.iter().filter(|j| j.execution.is_watching()).count()
+    }
+
+    /// Run the commands of jobs in watch mode again when they are due, and
+    /// of jobs stopped to run again once they have exited.
+    pub fn poll_watches(&mut self) {
+        let now = std::time::Instant::now();
+        let due: Vec<usize> = self
+            .jobs
+            .iter()
+            .filter(|j| {
+                if j.restarting {
+                    !j.is_running()
+                } else {
+                    j.execution.watch_due(now)
+                }
+            })
+            .map(|j| j.id)
+            .collect();
+        for id in due {
+            self.run_job_again(id);
+        }
+    }
+
+    /// Remember where the spec was read from so it can be reloaded, and
+    /// watch the spec file for changes. `bin` replaces the `bin` of each
+    /// reloaded spec, like `--cmd` did for the first one.
+    pub fn set_spec_source(&mut self, source: SpecSource, bin: Option<String>) {
+        self.spec_watcher = None;
+        if let Some(path) = source.file().filter(|_| source.can_reload()) {
+            match Watcher::file(path, self.config.watch.debounce.0) {
+                Ok(watcher) => self.spec_watcher = Some(watcher),
+                Err(e) => self.status_message = Some(e.to_string()),
+            }
+        }
+        self.spec_source = Some(source);
+        self.spec_bin = bin;
+    }
+
+    /// Save the spec command's output in `cache` when the spec is reloaded.
+    /// When the spec was loaded from the cache, `cached` is that output, and
+    /// the command runs in the background to check it.
+    pub fn set_spec_cache(&mut self, cache: SpecCache, cached: Option<String>) {
+        self.spec_refresh = cached.map(|output| cache.refresh(output));
+        self.spec_cache = Some(cache);
+    }
+
+    /// Whether the spec file is watched for changes.
+    pub fn is_watching_spec(&self) -> bool {
+        self.spec_watcher.is_some()
+    }
+
+    /// Whether the spec command runs in the background to check a cached
+    /// spec.
+    pub fn is_refreshing_spec(&self) -> bool {
+        self.spec_refresh.is_some()
+    }
+
+    /// Switch to the spec from a finished background refresh if it changed,
+    /// and reload the spec once its file changed. Waits while a value is
+    /// being typed or chosen, or an overlay is open, so the reload doesn't
+    /// lose it.
+    pub fn poll_spec_reload(&mut self) {
+        if self.is_executing() || self.is_overlay_open() || self.focused_panel_is_handling_input() {
+            return;
+        }
+        if let Some(result) = self.spec_refresh.as_ref().and_then(SpecRefresh::poll) {
+            self.spec_refresh = None;
+            match result {
+                Ok(Some(spec)) => self.use_reloaded_spec(spec),
+                Ok(None) => {}
+                Err(e) => self.spec_error = Some(e.to_string()),
+            }
+        }
+        let now = std::time::Instant::now();
+        if let Some(ref mut watcher) = self.spec_watcher {
+            if !watcher.due(now) {
+                return;
+            }
+            watcher.ran(now);
+            self.reload_spec();
+        }
+    }
+
+    /// Read the spec again from its source. A spec that fails to read or
+    /// parse leaves the current one in place and shows the error.
+    pub fn reload_spec(&mut self) {
+        let Some(ref source) = self.spec_source else {
+            self.status_message = Some("The spec can't be reloaded".to_string());
+            return;
+        };
+        if !source.can_reload() {
+            self.status_message = Some("A spec read from stdin can't be reloaded".to_string());
+            return;
+        }
+        // Supersedes a background refresh still running
+        self.spec_refresh = None;
+        let result = match self.spec_cache {
+            Some(ref cache) => cache.fetch().map(|(_, spec)| spec),
+            None => source.read(),
+        };
+        match result {
+            Ok(spec) => self.use_reloaded_spec(spec),
+            Err(e) => self.spec_error = Some(e.to_string()),
+        }
+    }
+
+    /// Switch to a spec read again from its source, with the `bin`
+    /// override applied.
+    fn use_reloaded_spec(&mut self, mut spec: Spec) {
+        if let Some(ref bin) = self.spec_bin {
+            spec.bin = bin.clone();
+        }
+        if spec.bin.is_empty() && spec.name.is_empty() {
+            self.spec_error =
+                Some("The usage spec has no `bin` to run. Use --cmd to set the command.".to_string());
+            return;
+        }
+        self.replace_spec(spec);
+    }
+
+    /// Switch to a changed spec, rebuilding the command tree and keeping the
+    /// command path and values whose names still exist.
+    pub fn replace_spec(&mut self, spec: Spec) {
+        let state = self.snapshot();
+        self.spec = spec;
+        self.command_panel =
+            FilterableComponent::new(CommandPanelComponent::new(build_command_tree(&self.spec)));
+        self.spec_error = None;
+        let warnings = self.restore_snapshot(&state);
+        self.status_message = Some("Reloaded the spec".to_string());
+        self.report_restore_warnings(&warnings);
+        self.notify_focus_gained(self.focus());
+    }
+
+    /// Go back to the builder to change the foreground job's command, with
+    /// the builder state it was run with and the preview focused. The
+    /// output pane of a split layout stays until the next run.
+    fn edit_job(&mut self) {
+        let Some(id) = self.foreground_job else {
+            return;
+        };
+        let state = self
+            .jobs
+            .iter()
+            .find(|j| j.id == id)
+            .and_then(|j| j.history.as_ref())
+            .map(|entry| entry.state.clone());
+        if self.is_executing() {
+            self.close_execution();
+        }
+        if let Some(state) = state {
+            let warnings = self.restore_snapshot(&state);
+            self.report_restore_warnings(&warnings);
+        }
+        self.set_focus(Focus::Preview);
+    }
+
+    /// Remove all jobs, as when quitting. Running jobs are stopped first so
+    /// that no processes are left behind.
+    pub fn finish_jobs(&mut self) {
+        self.stop_jobs();
+        let stopping = std::mem::take(&mut self.stopping);
+        for mut job in stopping.into_iter().chain(std::mem::take(&mut self.jobs)) {
+            self.record_run(&mut job);
+        }
+        self.foreground_job = None;
+    }
+
+    /// Send SIGTERM to every running job, then SIGKILL to those still running
+    /// after the `kill_after` grace period.
+    fn stop_jobs(&self) {
+        let running: Vec<&ExecutionComponent> = self
+            .jobs
+            .iter()
+            .chain(&self.stopping)
+            .filter(|j| j.is_running())
+            .map(|j| &j.execution)
+            .collect();
+        stop_executions(&running, self.config.behavior.kill_after.0);
+    }
+
+    /// Save the running or finished command's transcript to the configured
+    /// directory, or the default one in the data directory.
+    fn save_transcript(&mut self) {
+        let dir = self
+            .config
+            .behavior
+            .transcript_dir
+            .clone()
+            .or_else(crate::transcript::default_dir);
+        if let (Some(exec), Some(dir)) = (self.execution_mut(), dir) {
+            let _ = exec.save_transcript(&dir);
+        }
     }
 
     /// Start command execution with the given component.
+    #[cfg(test)]
     pub fn start_execution(&mut self, component: ExecutionComponent) {
-        self.mode = AppMode::Executing;
-        self.execution = Some(component);
+        self.start_job(component, None);
+    }
+
+    /// Add a job for the component and show it in the execution view, or
+    /// in the output pane with a split layout.
+    fn start_job(&mut self, component: ExecutionComponent, history: Option<HistoryEntry>) {
+        let id = self.next_job_id;
+        self.next_job_id += 1;
+        self.jobs.push(Job::new(id, component, history));
+        if self.config.behavior.layout.is_split() {
+            self.clear_output_pane();
+            self.foreground_job = Some(id);
+            self.rebuild_focus_manager();
+        } else {
+            self.foreground_job = Some(id);
+            self.mode = AppMode::Executing;
+        }
+    }
+
+    /// Make room in the output pane for another job: a finished job is
+    /// closed, and a running one is left in the background.
+    fn clear_output_pane(&mut self) {
+        let Some(id) = self.foreground_job else {
+            return;
+        };
+        let running = self.jobs.iter().any(|j| j.id == id && j.is_running());
+        if running {
+            self.background_execution();
+        } else {
+            self.close_execution();
+        }
     }
 
     pub fn spawn_execution(&mut self, terminal_size: ratatui::layout::Size) -> color_eyre::Result<()> {
         let parts = self.build_command_parts();
         let command_display = self.build_command();
-        let component = ExecutionComponent::spawn(command_display, &parts, terminal_size)?;
-        self.start_execution(component);
+        let component = ExecutionComponent::spawn(
+            command_display.clone(),
+            &parts,
+            self.env_vars(),
+            self.cwd.as_deref(),
+            self.output_size(terminal_size),
+            self.config.behavior.scrollback,
+        )?
+        .with_keys(&self.config.keys)
+        .with_cwd(self.cwd_label())
+        .with_timeout(
+            self.config.behavior.timeout.map(|t| t.0),
+            self.config.behavior.kill_after.0,
+        );
+        let history = HistoryEntry {
+            spec: self.spec_id(),
+            timestamp: crate::history::now(),
+            cwd: self.run_dir(),
+            command: command_display,
+            argv: parts,
+            exit_status: None,
+            state: self.snapshot(),
+        };
+        self.start_job(component, Some(history));
         Ok(())
     }
 
+    /// Size of the output of a command started now: the inside of the
+    /// output pane with a split layout, or the execution view's output area.
+    fn output_size(&self, terminal_size: ratatui::layout::Size) -> ratatui::layout::Size {
+        let area = Rect::new(0, 0, terminal_size.width, terminal_size.height);
+        match crate::ui::output_pane_area(area, self.config.behavior.layout) {
+            Some(pane) => ratatui::layout::Size {
+                width: pane.width.saturating_sub(2).max(1),
+                height: pane.height.saturating_sub(2).max(1),
+            },
+            None => ExecutionComponent::output_size(terminal_size),
+        }
+    }
+
+    /// Start the current command in place of the foreground job. A job still
+    /// running is sent SIGTERM and left to stop in the background.
+    pub fn rerun(&mut self, terminal_size: ratatui::layout::Size) -> color_eyre::Result<()> {
+        if let Some(id) = self.foreground_job.take() {
+            if let Some(index) = self.jobs.iter().position(|j| j.id == id) {
+                let mut job = self.jobs.remove(index);
+                if job.is_running() {
+                    job.execution.stop(self.config.behavior.kill_after.0);
+                    self.stopping.push(job);
+                } else {
+                    self.record_run(&mut job);
+                }
+            }
+        }
+        self.spawn_execution(terminal_size)
+    }
+
+    /// Return to the builder, leaving the foreground job running.
+    pub fn background_execution(&mut self) {
+        if let Some(id) = self.foreground_job.take() {
+            self.status_message = Some(format!(
+                "[{id}] moved to the background — {} lists jobs",
+                self.config.keys.jobs.label()
+            ));
+        }
+        self.mode = AppMode::Builder;
+        self.remove_output_pane();
+    }
+
+    /// Show a job in the execution view, or in the output pane with a split
+    /// layout.
+    pub fn foreground(&mut self, id: usize) {
+        if !self.jobs.iter().any(|j| j.id == id) {
+            return;
+        }
+        if self.config.behavior.layout.is_split() && self.foreground_job != Some(id) {
+            self.clear_output_pane();
+        }
+        if let Some(job) = self.jobs.iter_mut().find(|j| j.id == id) {
+            job.finish_reported = true;
+        }
+        self.foreground_job = Some(id);
+        if self.config.behavior.layout.is_split() {
+            self.rebuild_focus_manager();
+            self.set_focus(Focus::Output);
+        } else {
+            self.mode = AppMode::Executing;
+        }
+    }
+
+    /// Switch to the next output layout. The output pane's job is shown
+    /// full screen when switching away from the split layouts.
+    pub fn cycle_layout(&mut self) {
+        let layout = self.config.behavior.layout.next();
+        self.config.behavior.layout = layout;
+        if !layout.is_split() && self.foreground_job.is_some() {
+            self.remove_output_pane();
+            self.mode = AppMode::Executing;
+        }
+        self.rebuild_focus_manager();
+        let name = match layout {
+            OutputLayout::Full => "full screen",
+            OutputLayout::Right => "right of the builder",
+            OutputLayout::Below => "below the builder",
+        };
+        self.status_message = Some(format!("Output shown {name}"));
+    }
+
+    /// Number of jobs whose command is still running.
+    pub fn running_jobs(&self) -> usize {
+        self.jobs.iter().filter(|j| j.is_running()).count()
+    }
+
+    /// Report background jobs that finished since the last check in the
+    /// status bar. Returns whether any did.
+    pub fn poll_jobs(&mut self) -> bool {
+        let (stopped, stopping) = std::mem::take(&mut self.stopping)
+            .into_iter()
+            .partition(|j| !j.is_running());
+        self.stopping = stopping;
+        for mut job in stopped {
+            self.record_run(&mut job);
+        }
+        let foreground = self.foreground_job;
+        let mut finished = Vec::new();
+        for job in &mut self.jobs {
+            if !job.finish_reported && !job.restarting && !job.is_running() {
+                job.finish_reported = true;
+                if Some(job.id) != foreground {
+                    finished.push(format!(
+                        "[{}] {} finished — {}",
+                        job.id,
+                        job.command(),
+                        job.status()
+                    ));
+                }
+            }
+        }
+        let Some(last) = finished.pop() else {
+            return false;
+        };
+        self.status_message = Some(if finished.is_empty() {
+            last
+        } else {
+            format!("{last} (+{} more)", finished.len())
+        });
+        true
+    }
+
+    /// Whether the jobs list is open.
+    pub fn is_jobs_picking(&self) -> bool {
+        self.jobs_picker.is_open()
+    }
+
+    /// Open the jobs list.
+    pub fn open_jobs_picker(&mut self) {
+        if self.jobs.is_empty() {
+            self.status_message = Some(format!(
+                "No jobs — press {} in a running command to send it to the background",
+                self.config.keys.prefixed(self.config.keys.background)
+            ));
+            return;
+        }
+        self.jobs_picker.open(self.jobs_picker_items());
+    }
+
+    fn jobs_picker_items(&self) -> Vec<PickerItem> {
+        self.jobs
+            .iter()
+            .map(|job| PickerItem {
+                label: format!("[{}] {}", job.id, job.command()),
+                description: job.status(),
+            })
+            .collect()
+    }
+
+    /// Process a PickerAction emitted by the jobs picker component.
+    fn process_jobs_picker_action(&mut self, action: PickerAction) {
+        match action {
+            PickerAction::Selected(idx) => {
+                if let Some(id) = self.jobs.get(idx).map(|j| j.id) {
+                    self.foreground(id);
+                }
+            }
+            PickerAction::Deleted(idx) => {
+                if self.jobs.get(idx).is_some_and(|j| j.is_running()) {
+                    // Only finished jobs can be dismissed
+                    self.status_message = Some(format!("[{}] is still running", self.jobs[idx].id));
+                    let selected = self.jobs_picker_items();
+                    self.jobs_picker.open(selected);
+                } else if idx < self.jobs.len() {
+                    let mut job = self.jobs.remove(idx);
+                    self.record_run(&mut job);
+                    if self.jobs.is_empty() {
+                        self.jobs_picker.close();
+                    }
+                }
+            }
+            PickerAction::Cancelled => {}
+        }
+    }
+
+    /// Handle key events when the jobs picker is open.
+    fn handle_jobs_picker_key(&mut self, key: crossterm::event::KeyEvent) -> Action {
+        if let EventResult::Action(action) = self.jobs_picker.handle_key(key) {
+            self.process_jobs_picker_action(action);
+        }
+        Action::None
+    }
+
+    /// Resize every job's PTY, so background jobs fit when brought back.
     pub fn resize_execution_to_terminal(&self, terminal_size: ratatui::layout::Size) {
-        if let Some(ref exec) = self.execution {
-            exec.resize_to_terminal(terminal_size);
+        for job in &self.jobs {
+            job.execution.resize_to_terminal(terminal_size);
         }
     }
 
     /// Resize the PTY to fit the new terminal dimensions.
     #[cfg(test)]
     pub fn resize_pty(&self, rows: u16, cols: u16) {
-        if let Some(ref exec) = self.execution {
+        if let Some(exec) = self.execution() {
             exec.resize_pty(rows, cols);
         }
     }
@@ -256,19 +991,44 @@ impl App {
         let mut app = Self {
             spec,
             mode: AppMode::Builder,
-            execution: None,
+            jobs: Vec::new(),
+            stopping: Vec::new(),
+            foreground_job: None,
+            next_job_id: 1,
+            jobs_picker: PickerComponent::new("Jobs").with_delete(),
             theme_name,
             command_path: Vec::new(),
             command_panel,
             flag_panel: FilterableComponent::new(FlagPanelComponent::new()),
             flag_values: std::collections::HashMap::new(),
             arg_values_by_path: std::collections::HashMap::new(),
+            extra_args_by_path: std::collections::HashMap::new(),
+            env_by_path: std::collections::HashMap::new(),
             arg_values: Vec::new(),
             focus_manager: FocusManager::new(),
             arg_panel: FilterableComponent::new(ArgPanelComponent::new()),
             layout: UiLayout::new(),
             theme_picker: ThemePickerComponent::new(),
             mouse_position: None,
+            history: History::in_memory(),
+            history_picker: PickerComponent::new("History"),
+            favorites: Favorites::in_memory(),
+            favorites_picker: PickerComponent::new("Favorites").with_delete(),
+            prompt: PromptComponent::new(),
+            prompt_kind: None,
+            env_editor: EnvEditorComponent::new(),
+            cwd: None,
+            chosen_dirs: Vec::new(),
+            dir_picker: DirPickerComponent::new(),
+            status_message: None,
+            spec_source: None,
+            spec_bin: None,
+            spec_watcher: None,
+            spec_cache: None,
+            spec_refresh: None,
+            spec_error: None,
+            unconfirmed_action: None,
+            config: crate::config::Config::default(),
         };
         app.sync_state();
         // Synchronize command_path with the tree's initial selection so the
@@ -293,6 +1053,9 @@ impl App {
             self.focus_manager.register(Focus::Args);
         }
         self.focus_manager.register(Focus::Preview);
+        if self.has_output_pane() {
+            self.focus_manager.register(Focus::Output);
+        }
         // Only restore previous focus if we had one before;
         // otherwise let FocusManager default to the first registered element
         if had_focus {
@@ -354,6 +1117,17 @@ impl App {
         }
     }
 
+    /// Whether a picker, prompt or editor is open over the builder.
+    fn is_overlay_open(&self) -> bool {
+        self.is_theme_picking()
+            || self.is_history_picking()
+            || self.is_favorites_picking()
+            || self.is_jobs_picking()
+            || self.is_prompting()
+            || self.is_env_editing()
+            || self.is_dir_picking()
+    }
+
     /// Whether the theme picker is open.
     pub fn is_theme_picking(&self) -> bool {
         self.theme_picker.is_open()
@@ -371,7 +1145,10 @@ impl App {
                 self.theme_name = name;
             }
             ThemePickerAction::Confirmed => {
-                // Theme already set by preview — nothing to do.
+                // Theme already set by preview — just remember it.
+                if let Err(e) = self.config.save_theme(self.theme_name) {
+                    self.status_message = Some(format!("Failed to save theme: {e}"));
+                }
             }
             ThemePickerAction::Cancelled(original) => {
                 self.theme_name = original;
@@ -387,43 +1164,445 @@ impl App {
         Action::None
     }
 
-    /// Whether any choice select box is open (flag panel or arg panel).
-    pub fn is_choosing(&self) -> bool {
-        self.flag_panel.is_choosing() || self.arg_panel.is_choosing()
+    /// Identity of the loaded spec, used to scope history entries.
+    pub fn spec_id(&self) -> String {
+        if self.spec.bin.is_empty() {
+            self.spec.name.clone()
+        } else {
+            self.spec.bin.clone()
+        }
     }
 
-    /// Dispatch a FilterAction result, handling FocusNext/FocusPrev/Consumed/NotHandled
-    /// uniformly and delegating inner actions to the provided closure.
-    fn dispatch_filter_result<A>(
-        &mut self,
-        result: EventResult<FilterAction<A>>,
-        process: impl FnOnce(&mut Self, A) -> Action,
-    ) -> Action {
-        self.dispatch_filter_result_with(result, process, |_| {})
+    /// Whether the history picker is open.
+    pub fn is_history_picking(&self) -> bool {
+        self.history_picker.is_open()
+    }
+
+    /// Open the history picker with the commands recorded for this spec.
+    pub fn open_history_picker(&mut self) {
+        let now = crate::history::now();
+        let items: Vec<PickerItem> = self
+            .history
+            .entries_for(&self.spec_id())
+            .into_iter()
+            .map(|entry| {
+                let status = match entry.succeeded() {
+                    Some(true) => "✓",
+                    Some(false) => "✗",
+                    None => "?",
+                };
+                PickerItem {
+                    label: entry.command.clone(),
+                    description: format!(
+                        "{} {}",
+                        status,
+                        crate::history::format_age(entry.timestamp, now)
+                    ),
+                }
+            })
+            .collect();
+        if items.is_empty() {
+            self.status_message = Some("No history for this command yet".to_string());
+            return;
+        }
+        self.history_picker.open(items);
     }
 
-    /// Like `dispatch_filter_result` but with an additional callback for NotHandled.
-    fn dispatch_filter_result_with<A>(
-        &mut self,
-        result: EventResult<FilterAction<A>>,
-        process: impl FnOnce(&mut Self, A) -> Action,
-        on_not_handled: impl FnOnce(&mut Self),
-    ) -> Action {
-        match result {
-            EventResult::Action(FilterAction::Inner(action)) => process(self, action),
-            EventResult::Action(FilterAction::FocusNext) => {
-                self.focus_next();
-                Action::None
-            }
-            EventResult::Action(FilterAction::FocusPrev) => {
-                self.focus_prev();
-                Action::None
-            }
-            EventResult::NotHandled => {
-                on_not_handled(self);
-                Action::None
+    /// Process a PickerAction emitted by the history picker component.
+    fn process_history_picker_action(&mut self, action: PickerAction) {
+        match action {
+            PickerAction::Selected(idx) => {
+                let snapshot = self
+                    .history
+                    .entries_for(&self.spec_id())
+                    .get(idx)
+                    .map(|entry| entry.state.clone());
+                if let Some(snapshot) = snapshot {
+                    let warnings = self.restore_snapshot(&snapshot);
+                    self.report_restore_warnings(&warnings);
+                    self.set_focus(Focus::Preview);
+                }
             }
-            EventResult::Consumed => Action::None,
+            PickerAction::Deleted(_) | PickerAction::Cancelled => {}
+        }
+    }
+
+    /// Handle key events when the history picker is open.
+    fn handle_history_picker_key(&mut self, key: crossterm::event::KeyEvent) -> Action {
+        if let EventResult::Action(action) = self.history_picker.handle_key(key) {
+            self.process_history_picker_action(action);
+        }
+        Action::None
+    }
+
+    /// Whether the favorites picker is open.
+    pub fn is_favorites_picking(&self) -> bool {
+        self.favorites_picker.is_open()
+    }
+
+    /// Open the favorites picker.
+    pub fn open_favorites_picker(&mut self) {
+        let items: Vec<PickerItem> = self
+            .favorites
+            .list()
+            .iter()
+            .map(|favorite| PickerItem {
+                label: favorite.name.clone(),
+                description: favorite.command.clone(),
+            })
+            .collect();
+        if items.is_empty() {
+            self.status_message =
+                Some("No favorites yet — press S to save the current command".to_string());
+            return;
+        }
+        self.favorites_picker.open(items);
+    }
+
+    /// Process a PickerAction emitted by the favorites picker component.
+    fn process_favorites_picker_action(&mut self, action: PickerAction) {
+        match action {
+            PickerAction::Selected(idx) => {
+                if let Some(state) = self.favorites.list().get(idx).map(|f| f.state.clone()) {
+                    let warnings = self.restore_snapshot(&state);
+                    self.report_restore_warnings(&warnings);
+                    self.set_focus(Focus::Preview);
+                }
+            }
+            PickerAction::Deleted(idx) => {
+                if let Err(e) = self.favorites.remove(idx) {
+                    self.status_message = Some(format!("Failed to save favorites: {e}"));
+                }
+            }
+            PickerAction::Cancelled => {}
+        }
+    }
+
+    /// Handle key events when the favorites picker is open.
+    fn handle_favorites_picker_key(&mut self, key: crossterm::event::KeyEvent) -> Action {
+        if let EventResult::Action(action) = self.favorites_picker.handle_key(key) {
+            self.process_favorites_picker_action(action);
+        }
+        Action::None
+    }
+
+    /// Load a favorite by name, as for `--preset`. Values the spec no longer
+    /// defines are skipped and reported in the help bar.
+    pub fn apply_preset(&mut self, name: &str) -> color_eyre::Result<()> {
+        let Some(favorite) = self.favorites.find(name) else {
+            let names: Vec<&str> = self.favorites.list().iter().map(|f| f.name.as_str()).collect();
+            return Err(color_eyre::eyre::eyre!(
+                "No preset named '{}' for '{}'{}",
+                name,
+                self.spec_id(),
+                if names.is_empty() {
+                    String::new()
+                } else {
+                    format!(" (available: {})", names.join(", "))
+                }
+            ));
+        };
+        let state = favorite.state.clone();
+        let warnings = self.restore_snapshot(&state);
+        self.report_restore_warnings(&warnings);
+        Ok(())
+    }
+
+    /// Prompt for a name and save the current command as a favorite.
+    pub fn start_save_favorite(&mut self) {
+        let default_name = self.command_path.join("-");
+        self.prompt.open("Save favorite as", &default_name);
+        self.prompt_kind = Some(PromptKind::SaveFavorite);
+    }
+
+    /// Prompt for a command line to load into the builder.
+    pub fn start_paste_command(&mut self) {
+        self.prompt.open("Paste a command", "");
+        self.prompt_kind = Some(PromptKind::CommandLine);
+    }
+
+    /// Load a command line into the builder, as for `--initial`, replacing
+    /// its flags and args. Words that could not be placed are reported in
+    /// the help bar.
+    pub fn load_command_line(&mut self, line: &str) -> color_eyre::Result<()> {
+        let parsed = crate::command_parser::parse_command_line(&self.spec, line)
+            .map_err(|e| color_eyre::eyre::eyre!("Can't parse the command: {}", e))?;
+        self.load_parsed_command(parsed);
+        Ok(())
+    }
+
+    /// Load the words of a command into the builder, as given after `--`.
+    pub fn load_command_parts(&mut self, parts: &[String]) {
+        let parsed = crate::command_parser::parse_command_parts(&self.spec, parts);
+        self.load_parsed_command(parsed);
+    }
+
+    /// `NAME=value` assignments before the command replace its environment
+    /// overrides; the working directory is kept.
+    fn load_parsed_command(&mut self, parsed: crate::command_parser::ParsedCommand) {
+        let mut state = parsed.state;
+        let mut env = self.env_by_path.clone();
+        env.extend(state.env);
+        state.env = env;
+        state.cwd = self.cwd.clone();
+        let warnings = self.restore_snapshot(&state);
+        self.report_restore_warnings(&warnings);
+        if !parsed.unplaced.is_empty() {
+            self.status_message = Some(format!(
+                "Not placed: {}",
+                self.config.behavior.shell.join(&parsed.unplaced)
+            ));
+        }
+    }
+
+    /// Whether the text prompt is open.
+    pub fn is_prompting(&self) -> bool {
+        self.prompt.is_open()
+    }
+
+    /// Whether the text prompt asks for a command line.
+    pub fn is_pasting_command(&self) -> bool {
+        self.is_prompting() && self.prompt_kind == Some(PromptKind::CommandLine)
+    }
+
+    /// Process a PromptAction emitted by the text prompt.
+    fn process_prompt_action(&mut self, action: PromptAction) {
+        let kind = self.prompt_kind.take();
+        let PromptAction::Submitted(text) = action else {
+            return;
+        };
+        match kind {
+            Some(PromptKind::SaveFavorite) => {
+                let name = text.trim().to_string();
+                if name.is_empty() {
+                    self.status_message = Some("Favorite name cannot be empty".to_string());
+                    return;
+                }
+                let favorite = Favorite {
+                    name: name.clone(),
+                    command: self.build_command(),
+                    state: self.snapshot(),
+                };
+                self.status_message = Some(match self.favorites.save(favorite) {
+                    Ok(()) => format!("Saved favorite '{name}'"),
+                    Err(e) => format!("Failed to save favorites: {e}"),
+                });
+            }
+            Some(PromptKind::CommandLine) => {
+                if text.trim().is_empty() {
+                    return;
+                }
+                if let Err(e) = self.load_command_line(&text) {
+                    self.status_message = Some(e.to_string());
+                }
+            }
+            None => {}
+        }
+    }
+
+    /// Handle key events when the text prompt is open.
+    fn handle_prompt_key(&mut self, key: crossterm::event::KeyEvent) -> Action {
+        if let EventResult::Action(action) = self.prompt.handle_key(key) {
+            self.process_prompt_action(action);
+        }
+        Action::None
+    }
+
+    /// Whether the environment overlay is open.
+    pub fn is_env_editing(&self) -> bool {
+        self.env_editor.is_open()
+    }
+
+    /// Whether a variable in the environment overlay is being typed.
+    pub fn is_env_entry_editing(&self) -> bool {
+        self.env_editor.is_editing_entry()
+    }
+
+    /// Open the environment overlay for the current command, listing the
+    /// variables its flags and args read with `env=`.
+    pub fn open_env_editor(&mut self) {
+        let flags = self.visible_flags().into_iter().filter_map(|f| {
+            let source = match (f.long.first(), f.short.first()) {
+                (Some(long), _) => format!("--{long}"),
+                (None, Some(short)) => format!("-{short}"),
+                (None, None) => f.name.clone(),
+            };
+            Some((f.env.clone()?, source))
+        });
+        let args = self
+            .current_command()
+            .args
+            .iter()
+            .filter_map(|a| Some((a.env.clone()?, format!("<{}>", a.name))));
+        let mut suggestions: Vec<EnvSuggestion> = Vec::new();
+        for (name, source) in flags.chain(args) {
+            if suggestions.iter().all(|s| s.name != name) {
+                suggestions.push(EnvSuggestion {
+                    inherited: std::env::var(&name).ok(),
+                    name,
+                    source,
+                });
+            }
+        }
+        self.env_editor.open(self.env_vars().to_vec(), suggestions);
+    }
+
+    /// Process an EnvEditorAction emitted by the environment overlay.
+    fn process_env_editor_action(&mut self, action: EnvEditorAction) {
+        if let EnvEditorAction::Changed(vars) = action {
+            self.set_env_vars(vars);
+        }
+    }
+
+    /// Handle key events when the environment overlay is open.
+    fn handle_env_editor_key(&mut self, key: crossterm::event::KeyEvent) -> Action {
+        if let EventResult::Action(action) = self.env_editor.handle_key(key) {
+            self.process_env_editor_action(action);
+        }
+        Action::None
+    }
+
+    /// Directory the next run happens in.
+    pub fn run_dir(&self) -> Option<PathBuf> {
+        self.cwd.clone().or_else(|| std::env::current_dir().ok())
+    }
+
+    /// The chosen working directory for display, e.g. `packages/api`.
+    pub fn cwd_label(&self) -> Option<String> {
+        self.cwd.as_deref().map(crate::paths::display_dir)
+    }
+
+    /// Run commands in `dir`, or in TuiSage's own working directory.
+    pub fn set_cwd(&mut self, dir: Option<PathBuf>) {
+        let launch = std::env::current_dir().ok();
+        self.cwd = dir.filter(|dir| Some(dir) != launch.as_ref());
+    }
+
+    /// Whether the working directory overlay is open.
+    pub fn is_dir_picking(&self) -> bool {
+        self.dir_picker.is_open()
+    }
+
+    /// Whether a path is being typed in the working directory overlay.
+    pub fn is_dir_path_typing(&self) -> bool {
+        self.dir_picker.is_typing()
+    }
+
+    /// Directories to offer in the Recent tab: those chosen in this session,
+    /// then those this spec's commands ran in, most recent first.
+    fn recent_dirs(&self) -> Vec<PathBuf> {
+        let spec = self.spec_id();
+        let history = self.history.entries_for(&spec);
+        let mut dirs: Vec<PathBuf> = Vec::new();
+        for dir in self
+            .chosen_dirs
+            .iter()
+            .chain(history.iter().filter_map(|e| e.cwd.as_ref()))
+        {
+            if dirs.len() == MAX_RECENT_DIRS {
+                break;
+            }
+            if !dirs.contains(dir) && dir.is_dir() {
+                dirs.push(dir.clone());
+            }
+        }
+        dirs
+    }
+
+    /// Open the working directory overlay, browsing the current choice.
+    pub fn open_dir_picker(&mut self) {
+        let Some(dir) = self.run_dir() else {
+            self.status_message = Some("The working directory is not available".to_string());
+            return;
+        };
+        let recent = self.recent_dirs();
+        self.dir_picker
+            .open(dir, recent, self.config.behavior.resolve_paths);
+    }
+
+    /// Process a DirPickerAction emitted by the working directory overlay.
+    fn process_dir_picker_action(&mut self, action: DirPickerAction) {
+        match action {
+            DirPickerAction::Chosen(dir) => {
+                self.chosen_dirs.retain(|d| *d != dir);
+                self.chosen_dirs.insert(0, dir.clone());
+                self.set_cwd(Some(dir));
+            }
+            DirPickerAction::Reset => self.set_cwd(None),
+            DirPickerAction::ResolvePaths(on) => {
+                self.config.behavior.resolve_paths = on;
+                return;
+            }
+            DirPickerAction::Cancelled => return,
+        }
+        self.status_message = Some(match self.cwd_label() {
+            Some(label) => format!("Commands run in {label}"),
+            None => "Commands run in the launch directory".to_string(),
+        });
+    }
+
+    /// Handle key events when the working directory overlay is open.
+    fn handle_dir_picker_key(&mut self, key: crossterm::event::KeyEvent) -> Action {
+        if let EventResult::Action(action) = self.dir_picker.handle_key(key) {
+            self.process_dir_picker_action(action);
+        }
+        Action::None
+    }
+
+    /// Show values that could not be restored in the help bar.
+    fn report_restore_warnings(&mut self, warnings: &[String]) {
+        if !warnings.is_empty() {
+            self.status_message = Some(format!("Not restored: {}", warnings.join(", ")));
+        }
+    }
+
+    /// Whether any choice select box is open (flag panel or arg panel).
+    pub fn is_choosing(&self) -> bool {
+        self.flag_panel.is_choosing() || self.arg_panel.is_choosing()
+    }
+
+    /// Whether the list editor for a repeatable flag or variadic arg is open.
+    pub fn is_list_editing(&self) -> bool {
+        self.flag_panel.is_list_editing() || self.arg_panel.is_list_editing()
+    }
+
+    /// Whether an entry in the open list editor is being typed.
+    pub fn is_list_entry_editing(&self) -> bool {
+        self.flag_panel.is_list_entry_editing() || self.arg_panel.is_list_entry_editing()
+    }
+
+    /// Dispatch a FilterAction result, handling FocusNext/FocusPrev/Consumed/NotHandled
+    /// uniformly and delegating inner actions to the provided closure.
+    fn dispatch_filter_result<A>(
+        &mut self,
+        result: EventResult<FilterAction<A>>,
+        process: impl FnOnce(&mut Self, A) -> Action,
+    ) -> Action {
+        self.dispatch_filter_result_with(result, process, |_| {})
+    }
+
+    /// Like `dispatch_filter_result` but with an additional callback for NotHandled.
+    fn dispatch_filter_result_with<A>(
+        &mut self,
+        result: EventResult<FilterAction<A>>,
+        process: impl FnOnce(&mut Self, A) -> Action,
+        on_not_handled: impl FnOnce(&mut Self),
+    ) -> Action {
+        match result {
+            EventResult::Action(FilterAction::Inner(action)) => process(self, action),
+            EventResult::Action(FilterAction::FocusNext) => {
+                self.focus_next();
+                Action::None
+            }
+            EventResult::Action(FilterAction::FocusPrev) => {
+                self.focus_prev();
+                Action::None
+            }
+            EventResult::NotHandled => {
+                on_not_handled(self);
+                Action::None
+            }
+            EventResult::Consumed => Action::None,
         }
     }
 
@@ -464,7 +1643,7 @@ impl App {
                 let result = self.arg_panel.handle_focus_gained();
                 self.dispatch_filter_result(result, |s, action| s.process_arg_action(action));
             }
-            Focus::Preview => {}
+            Focus::Preview | Focus::Output => {}
         }
     }
 
@@ -485,7 +1664,7 @@ impl App {
                 let result = self.arg_panel.handle_focus_lost();
                 self.dispatch_filter_result(result, |s, action| s.process_arg_action(action));
             }
-            Focus::Preview => {}
+            Focus::Preview | Focus::Output => {}
         }
     }
 
@@ -512,9 +1691,19 @@ impl App {
     fn focused_panel_is_handling_input(&self) -> bool {
         match self.focus() {
             Focus::Commands => self.command_panel.is_filtering(),
-            Focus::Flags => self.flag_panel.is_filtering() || self.flag_panel.is_editing() || self.flag_panel.is_choosing(),
-            Focus::Args => self.arg_panel.is_filtering() || self.arg_panel.is_editing() || self.arg_panel.is_choosing(),
-            Focus::Preview => false,
+            Focus::Flags => {
+                self.flag_panel.is_filtering()
+                    || self.flag_panel.is_editing()
+                    || self.flag_panel.is_choosing()
+                    || self.flag_panel.is_list_editing()
+            }
+            Focus::Args => {
+                self.arg_panel.is_filtering()
+                    || self.arg_panel.is_editing()
+                    || self.arg_panel.is_choosing()
+                    || self.arg_panel.is_list_editing()
+            }
+            Focus::Preview | Focus::Output => false,
         }
     }
 
@@ -535,7 +1724,7 @@ impl App {
                 let result = self.arg_panel.handle_key(key);
                 self.dispatch_filter_result_option(result, |s, action| s.process_arg_action(action))
             }
-            Focus::Preview => None,
+            Focus::Preview | Focus::Output => None,
         }
     }
 
@@ -557,6 +1746,24 @@ impl App {
         }
     }
 
+    /// Apply the list of values to the repeatable flag at the given visible index.
+    fn apply_flag_list_value(&mut self, flag_idx: usize, new_values: Vec<String>) {
+        let mut changed = false;
+        {
+            let values = self.current_flag_values_mut();
+            if let Some((name, FlagValue::Multi(ref mut list))) = values.get_mut(flag_idx) {
+                let flag_name = name.clone();
+                *list = new_values;
+                let new_val = FlagValue::Multi(list.clone());
+                self.sync_global_flag(&flag_name, &new_val);
+                changed = true;
+            }
+        }
+        if changed {
+            self.refresh_flag_panel_inputs();
+        }
+    }
+
     /// Process a non-choice ArgPanelAction (enter, clear).
     fn process_arg_action(&mut self, action: ArgPanelAction) -> Action {
         match action {
@@ -565,7 +1772,20 @@ impl App {
                 Action::None
             }
             ArgPanelAction::ClearArg(idx) => {
-                self.set_arg_value(idx, String::new());
+                // Variadic args drop their last value, like repeatable flags
+                let last_removed = self.arg_values.get(idx).filter(|a| a.var).map(|a| {
+                    let mut values = a.values.clone();
+                    values.pop();
+                    values
+                });
+                match last_removed {
+                    Some(values) => self.set_arg_values(idx, values),
+                    None => self.set_arg_value(idx, String::new()),
+                }
+                Action::None
+            }
+            ArgPanelAction::ListChanged { index, values } => {
+                self.set_arg_values(index, values);
                 Action::None
             }
             ArgPanelAction::ValueChanged { index, value } => {
@@ -619,6 +1839,11 @@ impl App {
                                 let new_val = FlagValue::NegBool(None);
                                 self.sync_global_flag(&flag_name, &new_val);
                             }
+                            FlagValue::Multi(values) => {
+                                values.pop();
+                                let new_val = FlagValue::Multi(values.clone());
+                                self.sync_global_flag(&flag_name, &new_val);
+                            }
                         }
                         changed = true;
                     }
@@ -645,6 +1870,10 @@ impl App {
                 self.apply_flag_string_value(index, &value);
                 Action::None
             }
+            FlagPanelAction::ListChanged { index, values } => {
+                self.apply_flag_list_value(index, values);
+                Action::None
+            }
             FlagPanelAction::NegBoolClick(idx, target) => {
                 let mut changed = false;
                 {
@@ -854,7 +2083,7 @@ impl App {
         cmd.args.iter().filter(|a| !a.hide).collect()
     }
 
-    fn default_arg_values_for_command(cmd: &SpecCommand) -> Vec<ArgValue> {
+    pub(crate) fn default_arg_values_for_command(cmd: &SpecCommand) -> Vec<ArgValue> {
         cmd.args
             .iter()
             .filter(|a| !a.hide)
@@ -864,13 +2093,22 @@ impl App {
                     .as_ref()
                     .map(|c| c.choices.clone())
                     .unwrap_or_default();
-                let default = a.default.first().cloned().unwrap_or_default();
+                let (value, values) = if a.var {
+                    (String::new(), a.default.clone())
+                } else {
+                    (a.default.first().cloned().unwrap_or_default(), Vec::new())
+                };
                 ArgValue {
                     name: a.name.clone(),
-                    value: default,
+                    value,
                     required: a.required,
                     choices,
                     help: a.help.clone(),
+                    var: a.var,
+                    values,
+                    var_min: a.var_min,
+                    var_max: a.var_max,
+                    double_dash: a.double_dash.clone(),
                 }
             })
             .collect()
@@ -890,10 +2128,56 @@ impl App {
         self.sync_state();
     }
 
+    /// Set the value of the arg at `index`; the index after the last arg is
+    /// the extra args row.
     fn set_arg_value(&mut self, index: usize, value: String) {
         if let Some(arg) = self.arg_values.get_mut(index) {
             arg.value = value;
             self.persist_current_arg_values();
+        } else if index == self.arg_values.len() {
+            self.set_extra_args(value);
+        }
+        self.refresh_arg_panel_inputs();
+    }
+
+    /// Extra arguments for the current command, as typed.
+    pub fn extra_args(&self) -> &str {
+        self.extra_args_by_path
+            .get(&self.command_path_key())
+            .map(String::as_str)
+            .unwrap_or_default()
+    }
+
+    fn set_extra_args(&mut self, text: String) {
+        let key = self.command_path_key();
+        if text.is_empty() {
+            self.extra_args_by_path.remove(&key);
+        } else {
+            self.extra_args_by_path.insert(key, text);
+        }
+    }
+
+    /// Environment variable overrides for the current command.
+    pub fn env_vars(&self) -> &[EnvVar] {
+        self.env_by_path
+            .get(&self.command_path_key())
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    fn set_env_vars(&mut self, vars: Vec<EnvVar>) {
+        let key = self.command_path_key();
+        if vars.is_empty() {
+            self.env_by_path.remove(&key);
+        } else {
+            self.env_by_path.insert(key, vars);
+        }
+    }
+
+    fn set_arg_values(&mut self, index: usize, values: Vec<String>) {
+        if let Some(arg) = self.arg_values.get_mut(index) {
+            arg.values = values;
+            self.persist_current_arg_values();
         }
         self.refresh_arg_panel_inputs();
     }
@@ -959,18 +2243,7 @@ impl App {
                     if let Some(global_val) = root_global_values.get(&f.name) {
                         return (f.name.clone(), global_val.clone());
                     }
-                    let val = if f.count {
-                        FlagValue::Count(0)
-                    } else if f.arg.is_some() {
-                        let default = f.default.first().cloned().unwrap_or_default();
-                        FlagValue::String(default)
-                    } else if f.negate.is_some() {
-                        // Negatable flag: tristate (omitted / explicit on / explicit off)
-                        FlagValue::NegBool(None)
-                    } else {
-                        FlagValue::Bool(false)
-                    };
-                    (f.name.clone(), val)
+                    (f.name.clone(), Self::default_flag_value(f))
                 })
                 .collect();
             self.flag_values.insert(path_key, values);
@@ -978,7 +2251,8 @@ impl App {
 
         // Update list picker states with correct totals
         let flag_count = self.current_flag_values().len();
-        let arg_count = self.arg_values.len();
+        // The extra args row follows the args
+        let arg_count = self.arg_values.len() + 1;
         self.flag_panel.set_total(flag_count);
         self.arg_panel.set_total(arg_count);
 
@@ -989,50 +2263,213 @@ impl App {
         self.rebuild_focus_manager();
     }
 
-    /// Snapshot of visible flags (owned) for initialization purposes.
-    fn visible_flags_snapshot(&self) -> Vec<SpecFlag> {
-        self.visible_flags().into_iter().cloned().collect()
+    /// Initial value for a flag that hasn't been touched yet.
+    pub(crate) fn default_flag_value(f: &SpecFlag) -> FlagValue {
+        if f.count || (f.var && f.arg.is_none()) {
+            // Repeatable boolean flags are given once per count
+            FlagValue::Count(0)
+        } else if f.var {
+            FlagValue::Multi(f.default.clone())
+        } else if f.arg.is_some() {
+            let default = f.default.first().cloned().unwrap_or_default();
+            FlagValue::String(default)
+        } else if f.negate.is_some() {
+            // Negatable flag: tristate (omitted / explicit on / explicit off)
+            FlagValue::NegBool(None)
+        } else {
+            FlagValue::Bool(false)
+        }
     }
 
-    /// Current command path as a key string.
-    fn command_path_key(&self) -> String {
-        self.command_path.join(" ")
+    /// Look up a command by its path key (names joined by space, root = "").
+    fn command_at(&self, path_key: &str) -> Option<&SpecCommand> {
+        let mut cmd = &self.spec.cmd;
+        for name in path_key.split(' ').filter(|n| !n.is_empty()) {
+            cmd = cmd.find_subcommand(name)?;
+        }
+        Some(cmd)
     }
 
-    /// Get the flag values for the current command.
-    pub fn current_flag_values(&self) -> &[(String, FlagValue)] {
-        let key = self.command_path_key();
-        self.flag_values
-            .get(&key)
-            .map(|v| v.as_slice())
-            .unwrap_or(&[])
+    /// Capture the current builder state so it can be restored later.
+    pub fn snapshot(&self) -> BuilderSnapshot {
+        let mut arg_values_by_path = self.arg_values_by_path.clone();
+        arg_values_by_path.insert(self.command_path_key(), self.arg_values.clone());
+        BuilderSnapshot {
+            command_path: self.command_path.clone(),
+            flag_values: self.flag_values.clone(),
+            arg_values: arg_values_by_path
+                .into_iter()
+                .map(|(key, args)| {
+                    let saved = args
+                        .into_iter()
+                        .map(|a| SavedArg {
+                            name: a.name,
+                            value: a.value,
+                            values: a.values,
+                        })
+                        .collect();
+                    (key, saved)
+                })
+                .collect(),
+            extra_args: self.extra_args_by_path.clone(),
+            env: self.env_by_path.clone(),
+            cwd: self.cwd.clone(),
+        }
     }
 
-    /// Get a mutable reference to the flag values for the current command.
-    pub fn current_flag_values_mut(&mut self) -> &mut Vec<(String, FlagValue)> {
-        let key = self.command_path_key();
-        self.flag_values.entry(key).or_default()
-    }
+    /// Replace the builder state with a snapshot.
+    ///
+    /// The spec may have changed since the snapshot was taken, so values are
+    /// matched by name: commands, flags, and args that no longer exist are
+    /// skipped. Returns a description of each non-empty value that could not
+    /// be restored.
+    pub fn restore_snapshot(&mut self, snapshot: &BuilderSnapshot) -> Vec<String> {
+        let mut warnings: Vec<String> = Vec::new();
+        let mut warn = |msg: String| {
+            if !warnings.contains(&msg) {
+                warnings.push(msg);
+            }
+        };
 
-    /// If the named flag is a global flag, propagate its value to all stored
-    /// command-path levels so that build_command (which reads from root) and
-    /// the UI (which reads from the current path) stay consistent.
-    fn sync_global_flag(&mut self, flag_name: &str, new_value: &FlagValue) {
-        let is_global = self
-            .spec
-            .cmd
-            .flags
-            .iter()
-            .any(|f| f.global && f.name == flag_name);
-        if !is_global {
-            return;
-        }
-        // Collect all keys first to avoid borrow issues
-        let keys: Vec<String> = self.flag_values.keys().cloned().collect();
-        for key in keys {
-            if let Some(flags) = self.flag_values.get_mut(&key) {
-                if let Some((_, val)) = flags.iter_mut().find(|(n, _)| n == flag_name) {
-                    *val = new_value.clone();
+        // Keep the longest prefix of the command path that still exists.
+        let mut path = Vec::new();
+        let mut cmd = &self.spec.cmd;
+        for name in &snapshot.command_path {
+            match cmd.find_subcommand(name) {
+                Some(sub) => {
+                    path.push(sub.name.clone());
+                    cmd = sub;
+                }
+                None => {
+                    warn(format!("command '{}'", name));
+                    break;
+                }
+            }
+        }
+
+        let mut flag_values = std::collections::HashMap::new();
+        for (key, saved) in &snapshot.flag_values {
+            let Some(cmd) = self.command_at(key) else {
+                continue;
+            };
+            let mut values: Vec<(String, FlagValue)> = collect_visible_flags(cmd, &self.spec)
+                .into_iter()
+                .map(|f| (f.name.clone(), Self::default_flag_value(f)))
+                .collect();
+            for (name, value) in saved {
+                match values.iter_mut().find(|(n, _)| n == name) {
+                    Some((_, slot)) if slot.same_kind(value) => *slot = value.clone(),
+                    _ if value.is_set() => warn(format!("flag '{}'", name)),
+                    _ => {}
+                }
+            }
+            flag_values.insert(key.clone(), values);
+        }
+
+        let mut arg_values_by_path = std::collections::HashMap::new();
+        for (key, saved) in &snapshot.arg_values {
+            let Some(cmd) = self.command_at(key) else {
+                continue;
+            };
+            let mut values = Self::default_arg_values_for_command(cmd);
+            for saved_arg in saved {
+                match values.iter_mut().find(|a| a.name == saved_arg.name) {
+                    Some(arg) if arg.var => {
+                        // A single value was saved while the arg wasn't variadic
+                        arg.values = if saved_arg.values.is_empty() && !saved_arg.value.is_empty() {
+                            vec![saved_arg.value.clone()]
+                        } else {
+                            saved_arg.values.clone()
+                        };
+                    }
+                    Some(arg) => arg.value = saved_arg.value.clone(),
+                    None if !saved_arg.value.is_empty() || !saved_arg.values.is_empty() => {
+                        warn(format!("arg '{}'", saved_arg.name))
+                    }
+                    None => {}
+                }
+            }
+            arg_values_by_path.insert(key.clone(), values);
+        }
+
+        let cwd = match snapshot.cwd {
+            Some(ref dir) if !dir.is_dir() => {
+                warn(format!("directory '{}'", dir.display()));
+                None
+            }
+            ref cwd => cwd.clone(),
+        };
+
+        self.flag_values = flag_values;
+        self.arg_values_by_path = arg_values_by_path;
+        self.extra_args_by_path = snapshot
+            .extra_args
+            .iter()
+            .filter(|(key, _)| self.command_at(key).is_some())
+            .map(|(key, text)| (key.clone(), text.clone()))
+            .collect();
+        self.env_by_path = snapshot
+            .env
+            .iter()
+            .filter(|(key, _)| self.command_at(key).is_some())
+            .map(|(key, vars)| (key.clone(), vars.clone()))
+            .collect();
+        self.set_cwd(cwd);
+        let names: Vec<&str> = path.iter().map(String::as_str).collect();
+        self.command_panel.navigate_to(&names);
+        self.command_path = if self.has_any_commands() {
+            self.command_panel.path().to_vec()
+        } else {
+            path
+        };
+        self.sync_state();
+        warnings
+    }
+
+    /// Snapshot of visible flags (owned) for initialization purposes.
+    fn visible_flags_snapshot(&self) -> Vec<SpecFlag> {
+        self.visible_flags().into_iter().cloned().collect()
+    }
+
+    /// Current command path as a key string.
+    fn command_path_key(&self) -> String {
+        self.command_path.join(" ")
+    }
+
+    /// Get the flag values for the current command.
+    pub fn current_flag_values(&self) -> &[(String, FlagValue)] {
+        let key = self.command_path_key();
+        self.flag_values
+            .get(&key)
+            .map(|v| v.as_slice())
+            .unwrap_or(&[])
+    }
+
+    /// Get a mutable reference to the flag values for the current command.
+    pub fn current_flag_values_mut(&mut self) -> &mut Vec<(String, FlagValue)> {
+        let key = self.command_path_key();
+        self.flag_values.entry(key).or_default()
+    }
+
+    /// If the named flag is a global flag, propagate its value to all stored
+    /// command-path levels so that build_command (which reads from root) and
+    /// the UI (which reads from the current path) stay consistent.
+    fn sync_global_flag(&mut self, flag_name: &str, new_value: &FlagValue) {
+        let is_global = self
+            .spec
+            .cmd
+            .flags
+            .iter()
+            .any(|f| f.global && f.name == flag_name);
+        if !is_global {
+            return;
+        }
+        // Collect all keys first to avoid borrow issues
+        let keys: Vec<String> = self.flag_values.keys().cloned().collect();
+        for key in keys {
+            if let Some(flags) = self.flag_values.get_mut(&key) {
+                if let Some((_, val)) = flags.iter_mut().find(|(n, _)| n == flag_name) {
+                    *val = new_value.clone();
                 }
             }
         }
@@ -1183,8 +2620,54 @@ impl App {
 
     /// Handle a mouse event and return the resulting Action.
     pub fn handle_mouse(&mut self, event: crossterm::event::MouseEvent) -> Action {
+        if !matches!(event.kind, crossterm::event::MouseEventKind::Down(_)) {
+            return self.handle_mouse_event(event);
+        }
+        let unconfirmed = self.unconfirmed_action.take();
+        let action = self.handle_mouse_event(event);
+        self.confirm(action, unconfirmed)
+    }
+
+    /// Handle pasted text: it goes to the running command, or is typed into
+    /// the field being edited. Elsewhere it is ignored, so that pasted text
+    /// doesn't trigger shortcuts.
+    pub fn handle_paste(&mut self, text: &str) {
+        use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+        if self.is_executing() || self.focus() == Focus::Output {
+            if let Some(exec) = self.execution_mut() {
+                exec.paste(text);
+            }
+            return;
+        }
+        let typing = self.is_editing()
+            || self.is_prompting()
+            || self.is_filtering()
+            || self.is_choosing()
+            || self.is_list_entry_editing()
+            || self.is_env_entry_editing()
+            || self.is_dir_path_typing();
+        if !typing {
+            return;
+        }
+        // Fields hold a single line, so line breaks become spaces
+        for c in text.trim_end_matches(['\r', '\n']).chars() {
+            let c = if c.is_control() { ' ' } else { c };
+            self.handle_key(KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE));
+        }
+    }
+
+    fn handle_mouse_event(&mut self, event: crossterm::event::MouseEvent) -> Action {
         use crossterm::event::{MouseButton, MouseEventKind};
 
+        // The execution view fills the screen and handles the mouse itself
+        if self.is_executing() {
+            if let Some(exec) = self.execution_mut() {
+                exec.handle_mouse(event, Rect::default());
+            }
+            return Action::None;
+        }
+
         let col = event.column;
         let row = event.row;
 
@@ -1207,6 +2690,65 @@ impl App {
                         return Action::None;
                     }
 
+                    if self.is_history_picking() {
+                        if let Some(action) = self.history_picker.click_at(
+                            col,
+                            row,
+                            self.layout.history_overlay_rect,
+                        ) {
+                            self.process_history_picker_action(action);
+                        }
+                        return Action::None;
+                    }
+
+                    if self.is_favorites_picking() {
+                        if let Some(action) = self.favorites_picker.click_at(
+                            col,
+                            row,
+                            self.layout.favorites_overlay_rect,
+                        ) {
+                            self.process_favorites_picker_action(action);
+                        }
+                        return Action::None;
+                    }
+
+                    if self.is_jobs_picking() {
+                        if let Some(action) =
+                            self.jobs_picker
+                                .click_at(col, row, self.layout.jobs_overlay_rect)
+                        {
+                            self.process_jobs_picker_action(action);
+                        }
+                        return Action::None;
+                    }
+
+                    if self.is_prompting() {
+                        if let Some(action) =
+                            self.prompt.click_at(col, row, self.layout.prompt_overlay_rect)
+                        {
+                            self.process_prompt_action(action);
+                        }
+                        return Action::None;
+                    }
+
+                    if self.is_env_editing() {
+                        if let Some(action) =
+                            self.env_editor.click_at(col, row, self.layout.env_overlay_rect)
+                        {
+                            self.process_env_editor_action(action);
+                        }
+                        return Action::None;
+                    }
+
+                    if self.is_dir_picking() {
+                        if let Some(action) =
+                            self.dir_picker.click_at(col, row, self.layout.dir_overlay_rect)
+                        {
+                            self.process_dir_picker_action(action);
+                        }
+                        return Action::None;
+                    }
+
                     if let Some(rect) = self.layout.theme_indicator_rect {
                         if col >= rect.x
                             && col < rect.x + rect.width
@@ -1218,6 +2760,26 @@ impl App {
                         }
                     }
 
+                    if self.flag_panel.is_list_editing() {
+                        let result = self
+                            .flag_panel
+                            .handle_list_editor_click(col, row, self.layout.flag_overlay_rect)
+                            .map(FilterAction::Inner);
+                        return self.dispatch_filter_result(result, |s, action| {
+                            s.process_flag_action(action)
+                        });
+                    }
+
+                    if self.arg_panel.is_list_editing() {
+                        let result = self
+                            .arg_panel
+                            .handle_list_editor_click(col, row, self.layout.arg_overlay_rect)
+                            .map(FilterAction::Inner);
+                        return self.dispatch_filter_result(result, |s, action| {
+                            s.process_arg_action(action)
+                        });
+                    }
+
                     if self.is_choosing() {
                         return self.delegate_mouse_to_choosing_panel(event);
                     }
@@ -1257,6 +2819,7 @@ impl App {
                             }
                             Action::None
                         }
+                        Focus::Output => self.handle_output_pane_mouse(event),
                     }
                 } else {
                     Action::None
@@ -1287,8 +2850,22 @@ impl App {
                         })
                     }
                     Focus::Preview => Action::None,
+                    Focus::Output => self.handle_output_pane_mouse(event),
                 }
             }
+            // Releases and drags reach programs that enabled mouse reporting
+            _ if self.focus() == Focus::Output => self.handle_output_pane_mouse(event),
+            _ => Action::None,
+        }
+    }
+
+    /// Pass a mouse event to the command in the output pane.
+    fn handle_output_pane_mouse(&mut self, event: crossterm::event::MouseEvent) -> Action {
+        let Some(exec) = self.execution_mut() else {
+            return Action::None;
+        };
+        match exec.handle_mouse(event, Rect::default()) {
+            EventResult::Action(action) => self.process_execution_action(action),
             _ => Action::None,
         }
     }
@@ -1337,37 +2914,130 @@ impl App {
             Focus::Args => {
                 self.arg_panel.ensure_visible(viewport_height);
             }
-            Focus::Preview => {}
+            Focus::Preview | Focus::Output => {}
         }
     }
 
     pub fn handle_key(&mut self, key: crossterm::event::KeyEvent) -> Action {
+        self.status_message = None;
+        let unconfirmed = self.unconfirmed_action.take();
+        let action = self.handle_key_event(key);
+        self.confirm(action, unconfirmed)
+    }
+
+    /// Quit, as for Ctrl+C, asking for confirmation while jobs are running.
+    pub fn quit(&mut self) -> Action {
+        self.status_message = None;
+        let unconfirmed = self.unconfirmed_action.take();
+        self.confirm(Action::Quit, unconfirmed)
+    }
+
+    /// Problems with the current command's flag and argument values.
+    pub fn diagnostics(&self) -> Vec<Diagnostic> {
+        crate::validation::validate(
+            &self.visible_flags(),
+            self.current_flag_values(),
+            &self.arg_values,
+            self.extra_args(),
+        )
+    }
+
+    /// Hold back a request to run or print an invalid command, or to quit
+    /// while jobs are running, until it is repeated, explaining why in the
+    /// status message. `unconfirmed` is the request held back last.
+    fn confirm(&mut self, action: Action, unconfirmed: Option<Action>) -> Action {
+        // The execution view has no status bar, and the command was already run
+        if unconfirmed == Some(action) || (action == Action::Rerun && self.is_executing()) {
+            return action;
+        }
+        if action == Action::Quit {
+            let running = self.running_jobs();
+            if running == 0 {
+                return action;
+            }
+            let noun = if running == 1 { "job is" } else { "jobs are" };
+            self.status_message =
+                Some(format!("{running} {noun} still running — repeat to quit anyway"));
+            self.unconfirmed_action = Some(action);
+            return Action::None;
+        }
+        if !matches!(action, Action::Execute | Action::Print | Action::Rerun) {
+            return action;
+        }
+        let diagnostics = self.diagnostics();
+        let Some(first) = diagnostics.first() else {
+            return action;
+        };
+        let mut message = first.describe(&self.visible_flags(), &self.arg_values);
+        if diagnostics.len() > 1 {
+            message.push_str(&format!(" (+{} more)", diagnostics.len() - 1));
+        }
+        self.status_message = Some(format!("{message} — repeat to run anyway"));
+        self.unconfirmed_action = Some(action);
+        Action::None
+    }
+
+    fn handle_key_event(&mut self, key: crossterm::event::KeyEvent) -> Action {
         use crossterm::event::KeyCode;
 
         // If in execution mode, delegate to the execution component
         if self.is_executing() {
-            if let Some(ref mut exec) = self.execution {
-                if let EventResult::Action(ExecutionAction::Close) = exec.handle_key(key) {
-                    self.close_execution();
+            return self.handle_execution_key(key);
+        }
+
+        // Keys in the output pane go to the command, apart from Tab and
+        // BackTab, which return to the builder
+        if self.focus() == Focus::Output && !self.is_overlay_open() {
+            return match key.code {
+                KeyCode::Tab => {
+                    self.focus_next();
+                    Action::None
                 }
-            }
-            return Action::None;
+                KeyCode::BackTab => {
+                    self.focus_prev();
+                    Action::None
+                }
+                _ => self.handle_execution_key(key),
+            };
         }
 
-        // Ctrl+R executes command from any panel, regardless of edit/filter mode
-        if key.code == KeyCode::Char('r')
-            && key
-                .modifiers
-                .contains(crossterm::event::KeyModifiers::CONTROL)
-        {
+        // Ctrl+R (by default) executes command from any panel, regardless of edit/filter mode
+        if self.config.keys.execute.matches(&key) {
             return Action::Execute;
         }
+        if self.config.keys.rerun.matches(&key) {
+            return Action::Rerun;
+        }
 
         // If the theme picker is open, handle its keys
         if self.is_theme_picking() {
             return self.handle_theme_picker_key(key);
         }
 
+        if self.is_history_picking() {
+            return self.handle_history_picker_key(key);
+        }
+
+        if self.is_favorites_picking() {
+            return self.handle_favorites_picker_key(key);
+        }
+
+        if self.is_jobs_picking() {
+            return self.handle_jobs_picker_key(key);
+        }
+
+        if self.is_prompting() {
+            return self.handle_prompt_key(key);
+        }
+
+        if self.is_env_editing() {
+            return self.handle_env_editor_key(key);
+        }
+
+        if self.is_dir_picking() {
+            return self.handle_dir_picker_key(key);
+        }
+
         let focused_panel_is_handling_input = self.focused_panel_is_handling_input();
         if let Some(action) = self.handle_focused_panel_key(key) {
             return action;
@@ -1376,21 +3046,50 @@ impl App {
             return Action::None;
         }
 
+        let keys = &self.config.keys;
+        if keys.quit.matches(&key) {
+            return Action::Quit;
+        } else if keys.theme_picker.matches(&key) {
+            self.open_theme_picker();
+            return Action::None;
+        } else if keys.history.matches(&key) {
+            self.open_history_picker();
+            return Action::None;
+        } else if keys.favorites.matches(&key) {
+            self.open_favorites_picker();
+            return Action::None;
+        } else if keys.jobs.matches(&key) {
+            self.open_jobs_picker();
+            return Action::None;
+        } else if keys.save_favorite.matches(&key) {
+            self.start_save_favorite();
+            return Action::None;
+        } else if keys.paste_command.matches(&key) {
+            self.start_paste_command();
+            return Action::None;
+        } else if keys.reload.matches(&key) {
+            self.reload_spec();
+            return Action::None;
+        } else if keys.env.matches(&key) {
+            self.open_env_editor();
+            return Action::None;
+        } else if keys.cwd.matches(&key) {
+            self.open_dir_picker();
+            return Action::None;
+        } else if keys.layout.matches(&key) {
+            self.cycle_layout();
+            return Action::None;
+        } else if keys.next_theme.matches(&key) {
+            self.next_theme();
+            return Action::None;
+        } else if keys.prev_theme.matches(&key) {
+            self.prev_theme();
+            return Action::None;
+        } else if keys.accept.matches(&key) && self.focus() == Focus::Preview {
+            return Action::Print;
+        }
+
         match key.code {
-            KeyCode::Char('q') => Action::Quit,
-            KeyCode::Char('T') => {
-                self.open_theme_picker();
-                Action::None
-            }
-            KeyCode::Char(']') => {
-                self.next_theme();
-                Action::None
-            }
-            KeyCode::Char('[') => {
-                self.prev_theme();
-                Action::None
-            }
-            KeyCode::Char('p') => Action::None,
             KeyCode::Tab => {
                 self.focus_next();
                 Action::None
@@ -1404,6 +3103,34 @@ impl App {
         }
     }
 
+    /// Pass a key to the foreground job's command.
+    fn handle_execution_key(&mut self, key: crossterm::event::KeyEvent) -> Action {
+        let Some(exec) = self.execution_mut() else {
+            return Action::None;
+        };
+        match exec.handle_key(key) {
+            EventResult::Action(action) => self.process_execution_action(action),
+            _ => Action::None,
+        }
+    }
+
+    fn process_execution_action(&mut self, action: ExecutionAction) -> Action {
+        match action {
+            ExecutionAction::Close => self.close_execution(),
+            ExecutionAction::SaveTranscript => self.save_transcript(),
+            ExecutionAction::Background => self.background_execution(),
+            ExecutionAction::Rerun => return Action::Rerun,
+            ExecutionAction::RunAgain => {
+                if let Some(id) = self.foreground_job {
+                    self.run_job_again(id);
+                }
+            }
+            ExecutionAction::Edit => self.edit_job(),
+            ExecutionAction::ToggleWatch => self.toggle_watch(),
+        }
+        Action::None
+    }
+
     /// Start editing on the focused panel: populate edit_input from current value.
     #[allow(dead_code)] // used in tests; production code calls panel.start_editing() directly
     pub fn start_editing(&mut self) {
@@ -1470,7 +3197,11 @@ impl App {
                 );
                 self.handle_focused_panel_key(enter).unwrap_or(Action::None)
             }
-            Focus::Preview => Action::Execute,
+            Focus::Preview => match self.config.behavior.preview_enter {
+                PreviewEnter::Execute => Action::Execute,
+                PreviewEnter::Print => Action::Print,
+            },
+            Focus::Output => Action::None,
         }
     }
 
@@ -1527,6 +3258,23 @@ impl App {
 
                 self.flag_panel.start_editing(&current_value);
             }
+            FlagPanelEnterRequest::ListEdit {
+                index,
+                values,
+                max,
+                value_column,
+            } => {
+                let values = self
+                    .current_flag_values()
+                    .get(index)
+                    .and_then(|(_, value)| match value {
+                        FlagValue::Multi(list) => Some(list.clone()),
+                        _ => None,
+                    })
+                    .unwrap_or(values);
+                self.flag_panel
+                    .open_list_editor(index, values, max, value_column);
+            }
         }
     }
 
@@ -1583,6 +3331,24 @@ impl App {
                     self.arg_panel.start_editing(&current_value);
                 }
             }
+            ArgPanelEnterRequest::ListEdit {
+                index,
+                values,
+                max,
+                value_column,
+            } => {
+                let values = self
+                    .arg_values
+                    .get(index)
+                    .map(|arg| arg.values.clone())
+                    .unwrap_or(values);
+                self.arg_panel
+                    .open_list_editor(index, values, max, value_column);
+            }
+            ArgPanelEnterRequest::EditExtraArgs => {
+                let text = self.extra_args().to_string();
+                self.arg_panel.start_editing(&text);
+            }
         }
     }
 
@@ -1641,22 +3407,22 @@ impl App {
     /// Handle a mouse click on one side of a negatable flag.
     /// `target` is the desired state: `Some(true)` for the positive name, `Some(false)` for the negate name.
     /// If already in the target state, resets to `None` (omitted).
-    /// Build the full command string from the current state.
+    /// Build the full command string from the current state, quoted for
+    /// the configured shell.
     pub fn build_command(&self) -> String {
-        let preview = crate::command_builder::LiveArgPreview {
-            choice_select_index: self.arg_panel.choice_select_index(),
-            choice_select_text: self.arg_panel.choice_select_text(),
-            is_editing: self.arg_panel.is_editing(),
-            editing_index: self.arg_panel.selected_index(),
-            editing_text: self.arg_panel.editing_text(),
-        };
-        crate::command_builder::build_command(
+        let command = crate::command_builder::build_command(
             &self.spec,
             &self.flag_values,
             &self.command_path,
-            &self.arg_values,
-            &preview,
-        )
+            &self.resolved_arg_values(),
+            self.extra_args(),
+            &self.live_preview(),
+            self.config.behavior.shell,
+        );
+        // Overrides the shell can't express are reported by the preview, and
+        // refused when printing
+        crate::env::with_prefix(self.env_vars(), self.config.behavior.shell, command.clone())
+            .unwrap_or(command)
     }
 
     /// Build the command as a list of separate argument strings (for process execution).
@@ -1666,16 +3432,71 @@ impl App {
             &self.spec,
             &self.flag_values,
             &self.command_path,
-            &self.arg_values,
+            &self.resolved_arg_values(),
+            self.extra_args(),
         )
     }
 
-}
+    /// Command parts for the preview, including in-progress arg edits.
+    pub fn build_preview_parts(&self) -> Vec<String> {
+        crate::command_builder::build_preview_parts(
+            &self.spec,
+            &self.flag_values,
+            &self.command_path,
+            &self.resolved_arg_values(),
+            self.extra_args(),
+            &self.live_preview(),
+        )
+    }
 
-// --- Tree building functions ---
+    /// Build the command as a shell-quoted line for printing, changing into
+    /// the chosen working directory first. Fails when the shell can't set an
+    /// environment override.
+    pub fn build_shell_command(&self) -> Result<String, String> {
+        let shell = self.config.behavior.shell;
+        let command =
+            crate::env::with_prefix(self.env_vars(), shell, shell.join(&self.build_command_parts()))?;
+        Ok(match self.cwd {
+            Some(ref dir) => shell.in_dir(&dir.display().to_string(), &command),
+            None => command,
+        })
+    }
 
-/// Build tree nodes from a usage spec.
-pub fn build_command_tree(spec: &Spec) -> Vec<TreeNode<CmdData>> {
+    /// Argument values with relative paths made absolute against the run
+    /// directory, when `resolve_paths` is on. Args with choices are kept.
+    fn resolved_arg_values(&self) -> std::borrow::Cow<'_, [ArgValue]> {
+        let dir = match self.run_dir() {
+            Some(dir) if self.config.behavior.resolve_paths => dir,
+            _ => return std::borrow::Cow::Borrowed(&self.arg_values),
+        };
+        let resolve = |value: &mut String| {
+            if let Some(path) = crate::paths::resolve_relative(value, &dir) {
+                *value = path.display().to_string();
+            }
+        };
+        let mut args = self.arg_values.clone();
+        for arg in args.iter_mut().filter(|a| a.choices.is_empty()) {
+            resolve(&mut arg.value);
+            arg.values.iter_mut().for_each(&resolve);
+        }
+        std::borrow::Cow::Owned(args)
+    }
+
+    fn live_preview(&self) -> crate::command_builder::LiveArgPreview<'_> {
+        crate::command_builder::LiveArgPreview {
+            choice_select_index: self.arg_panel.choice_select_index(),
+            choice_select_text: self.arg_panel.choice_select_text(),
+            is_editing: self.arg_panel.is_editing(),
+            editing_index: self.arg_panel.selected_index(),
+            editing_text: self.arg_panel.editing_text(),
+        }
+    }
+}
+
+// --- Tree building functions ---
+
+/// Build tree nodes from a usage spec.
+pub fn build_command_tree(spec: &Spec) -> Vec<TreeNode<CmdData>> {
     // Build top-level commands directly (no root wrapper node)
     build_cmd_nodes(&spec.cmd, &[])
 }
@@ -1874,8 +3695,9 @@ mod tests {
     use super::*;
     use std::sync::atomic::{AtomicBool, Ordering};
     use std::sync::{Arc, Mutex, RwLock};
+    use std::time::Duration;
 
-    use crate::components::execution::ExecutionState;
+    use crate::components::execution::{wait_until, ExecutionState};
 
     fn sample_spec() -> Spec {
         let input = include_str!("../fixtures/sample.usage.kdl");
@@ -2376,6 +4198,497 @@ cmd "other" {
         }
     }
 
+    fn repeatable_flag_spec() -> Spec {
+        r#"
+name "Repeat CLI"
+bin "repeat"
+
+cmd "run" {
+    flag "-e --env <kv>" var=#true default="A=1"
+    flag "--tag <tag>" var=#true
+    flag "-q --quiet" var=#true
+}
+"#
+        .parse::<Spec>()
+        .expect("Failed to parse repeatable flag test spec")
+    }
+
+    fn flag_value(app: &App, name: &str) -> FlagValue {
+        app.current_flag_values()
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, v)| v.clone())
+            .unwrap()
+    }
+
+    #[test]
+    fn test_repeatable_flags_initial_values() {
+        let mut app = App::new(repeatable_flag_spec());
+        app.navigate_to_command(&["run"]);
+        assert_eq!(flag_value(&app, "env"), FlagValue::Multi(vec!["A=1".to_string()]));
+        assert_eq!(flag_value(&app, "tag"), FlagValue::Multi(vec![]));
+        assert_eq!(flag_value(&app, "quiet"), FlagValue::Count(0));
+        assert_eq!(app.build_command(), "repeat run --env A=1");
+    }
+
+    #[test]
+    fn test_repeatable_flags_repeat_in_command() {
+        let mut app = App::new(repeatable_flag_spec());
+        app.navigate_to_command(&["run"]);
+        set_flag(
+            &mut app,
+            "tag",
+            FlagValue::Multi(vec!["a b".to_string(), "c".to_string()]),
+        );
+        set_flag(&mut app, "quiet", FlagValue::Count(2));
+        assert_eq!(
+            app.build_command_parts(),
+            vec!["repeat", "run", "--env", "A=1", "--tag", "a b", "--tag", "c", "-qq"]
+        );
+    }
+
+    #[test]
+    fn test_list_editor_edits_repeatable_flag() {
+        use crossterm::event::KeyCode;
+
+        let mut app = App::new(repeatable_flag_spec());
+        app.navigate_to_command(&["run"]);
+        app.set_focus(Focus::Flags);
+        app.set_flag_index(0);
+
+        press(&mut app, KeyCode::Enter);
+        assert!(app.is_list_editing(), "Enter on a repeatable flag opens the list editor");
+        assert!(!app.is_list_entry_editing());
+
+        press(&mut app, KeyCode::Char('a'));
+        for c in "B=2".chars() {
+            press(&mut app, KeyCode::Char(c));
+        }
+        press(&mut app, KeyCode::Enter);
+        assert_eq!(app.build_command(), "repeat run --env A=1 --env B=2");
+
+        // Move the new entry above the default one
+        assert_eq!(press(&mut app, KeyCode::Char('K')), Action::None);
+        assert_eq!(app.build_command(), "repeat run --env B=2 --env A=1");
+
+        // q closes the editor instead of quitting
+        assert_eq!(press(&mut app, KeyCode::Char('q')), Action::None);
+        assert!(!app.is_list_editing());
+
+        // Backspace on the row removes the last value
+        press(&mut app, KeyCode::Backspace);
+        assert_eq!(
+            flag_value(&app, "env"),
+            FlagValue::Multi(vec!["B=2".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_list_editor_commits_pending_entry_on_focus_loss() {
+        use crossterm::event::KeyCode;
+
+        let mut app = App::new(repeatable_flag_spec());
+        app.navigate_to_command(&["run"]);
+        app.set_focus(Focus::Flags);
+        app.set_flag_index(1);
+
+        press(&mut app, KeyCode::Enter);
+        assert!(app.is_list_entry_editing(), "An empty list starts with a new entry");
+        press(&mut app, KeyCode::Char('x'));
+        app.set_focus(Focus::Preview);
+
+        assert!(!app.is_list_editing());
+        assert_eq!(flag_value(&app, "tag"), FlagValue::Multi(vec!["x".to_string()]));
+    }
+
+    #[test]
+    fn test_variadic_arg_values_are_separate_parts() {
+        let mut app = App::new(sample_spec());
+        app.navigate_to_command(&["run"]);
+        assert!(app.arg_values[1].var);
+        app.arg_values[0].value = "build".to_string();
+        app.arg_values[1].values = vec!["--release".to_string(), "two words".to_string()];
+
+        assert_eq!(
+            app.build_command_parts(),
+            vec!["mycli", "run", "--jobs", "4", "build", "--release", "two words"]
+        );
+        assert_eq!(
+            app.build_command(),
+            "mycli run --jobs 4 build --release 'two words'"
+        );
+    }
+
+    #[test]
+    fn test_list_editor_edits_variadic_arg() {
+        use crossterm::event::KeyCode;
+
+        let mut app = App::new(sample_spec());
+        app.navigate_to_command(&["run"]);
+        app.set_focus(Focus::Args);
+        app.set_arg_index(1);
+
+        press(&mut app, KeyCode::Enter);
+        assert!(app.is_list_entry_editing(), "An empty list starts with a new entry");
+        for c in "a b".chars() {
+            press(&mut app, KeyCode::Char(c));
+        }
+        press(&mut app, KeyCode::Enter);
+        press(&mut app, KeyCode::Char('a'));
+        press(&mut app, KeyCode::Char('c'));
+        press(&mut app, KeyCode::Enter);
+        press(&mut app, KeyCode::Esc);
+        assert!(!app.is_list_editing());
+        assert_eq!(app.arg_values[1].values, vec!["a b", "c"]);
+        assert_eq!(
+            app.build_command_parts(),
+            vec!["mycli", "run", "--jobs", "4", "a b", "c"]
+        );
+
+        // Backspace on the row removes the last value
+        press(&mut app, KeyCode::Backspace);
+        assert_eq!(app.arg_values[1].values, vec!["a b"]);
+    }
+
+    #[test]
+    fn test_variadic_arg_honors_var_max() {
+        use crossterm::event::KeyCode;
+
+        let spec = r#"
+bin "cp"
+arg "<files>" var=#true var_min=2 var_max=2
+"#
+        .parse::<Spec>()
+        .expect("Failed to parse variadic arg test spec");
+        let mut app = App::new(spec);
+        assert_eq!(app.arg_values[0].var_min, Some(2));
+        app.set_focus(Focus::Args);
+
+        press(&mut app, KeyCode::Enter);
+        for value in ["a", "b"] {
+            press(&mut app, KeyCode::Char(value.chars().next().unwrap()));
+            press(&mut app, KeyCode::Enter);
+            press(&mut app, KeyCode::Char('a'));
+        }
+        assert!(!app.is_list_entry_editing(), "No third entry can be added");
+        assert_eq!(app.arg_values[0].values, vec!["a", "b"]);
+    }
+
+    #[test]
+    fn test_snapshot_restores_variadic_arg_values() {
+        let mut app = App::new(sample_spec());
+        app.navigate_to_command(&["run"]);
+        app.arg_values[1].values = vec!["x".to_string(), "y".to_string()];
+        let snapshot = app.snapshot();
+
+        let mut restored = App::new(sample_spec());
+        assert!(restored.restore_snapshot(&snapshot).is_empty());
+        assert_eq!(restored.arg_values[1].values, vec!["x", "y"]);
+
+        // Entries saved before args were variadic hold a single value
+        let json = r#"{"command_path":["run"],"flag_values":{},"arg_values":{"run":[{"name":"args","value":"--fast"}]}}"#;
+        let old: BuilderSnapshot = serde_json::from_str(json).unwrap();
+        restored.restore_snapshot(&old);
+        assert_eq!(restored.arg_values[1].values, vec!["--fast"]);
+    }
+
+    fn double_dash_spec() -> Spec {
+        r#"
+name "Dash CLI"
+bin "dash"
+
+cmd "exec" {
+    arg "<service>"
+    arg "<command>" double_dash="required"
+}
+cmd "run" {
+    arg "<task>"
+    arg "[args...]" var=#true double_dash="automatic"
+}
+"#
+        .parse::<Spec>()
+        .expect("Failed to parse double dash test spec")
+    }
+
+    #[test]
+    fn test_extra_args_follow_double_dash() {
+        let mut app = App::new(sample_spec());
+        app.navigate_to_command(&["deploy"]);
+        app.set_arg_value(0, "prod".to_string());
+        let extra_index = app.arg_values.len();
+        app.set_arg_value(extra_index, r#"--force "two words""#.to_string());
+        assert_eq!(
+            app.build_command_parts(),
+            vec!["mycli", "deploy", "prod", "--", "--force", "two words"]
+        );
+        assert_eq!(app.build_command(), "mycli deploy prod -- --force 'two words'");
+
+        // Extra args are kept per command
+        app.navigate_to_command(&["init"]);
+        assert_eq!(app.extra_args(), "");
+        app.navigate_to_command(&["deploy"]);
+        assert_eq!(app.extra_args(), r#"--force "two words""#);
+    }
+
+    #[test]
+    fn test_extra_args_respect_arg_double_dash() {
+        let mut app = App::new(double_dash_spec());
+        app.navigate_to_command(&["exec"]);
+        app.set_arg_value(0, "web".to_string());
+        app.set_arg_value(1, "sh".to_string());
+        app.set_arg_value(2, "-c 'echo hi'".to_string());
+        assert_eq!(
+            app.build_command_parts(),
+            vec!["dash", "exec", "web", "--", "sh", "-c", "echo hi"],
+            "`--` goes before a double_dash=required arg, and only once"
+        );
+
+        app.navigate_to_command(&["run"]);
+        app.set_arg_value(0, "test".to_string());
+        app.set_arg_value(2, "--verbose".to_string());
+        assert_eq!(
+            app.build_command_parts(),
+            vec!["dash", "run", "test", "--", "--verbose"]
+        );
+        app.set_arg_values(1, vec!["unit".to_string()]);
+        assert_eq!(
+            app.build_command_parts(),
+            vec!["dash", "run", "test", "unit", "--verbose"],
+            "no `--` is needed after a double_dash=automatic arg"
+        );
+    }
+
+    #[test]
+    fn test_edit_extra_args_row() {
+        use crossterm::event::KeyCode;
+
+        let mut app = App::new(sample_spec());
+        app.navigate_to_command(&["deploy"]);
+        app.set_arg_value(0, "prod".to_string());
+        app.set_focus(Focus::Args);
+        app.set_arg_index(1);
+
+        press(&mut app, KeyCode::Enter);
+        assert!(app.is_editing());
+        for c in "-x 'open".chars() {
+            press(&mut app, KeyCode::Char(c));
+        }
+        assert_eq!(
+            app.build_preview_parts(),
+            vec!["mycli", "deploy", "prod", "--", "-x", "'open"],
+            "the preview splits loosely while a quote is open"
+        );
+        press(&mut app, KeyCode::Enter);
+        assert_eq!(app.extra_args(), "-x 'open");
+        let diagnostics = app.diagnostics();
+        assert_eq!(
+            diagnostics[0].describe(&app.visible_flags(), &app.arg_values),
+            "--: unterminated single quote"
+        );
+
+        press(&mut app, KeyCode::Backspace);
+        assert_eq!(app.extra_args(), "");
+        assert_eq!(app.build_command_parts(), vec!["mycli", "deploy", "prod"]);
+    }
+
+    #[test]
+    fn test_snapshot_restores_extra_args() {
+        let mut app = App::new(sample_spec());
+        app.navigate_to_command(&["run"]);
+        app.set_arg_value(2, "--fast".to_string());
+        let snapshot = app.snapshot();
+        assert_eq!(snapshot.extra_args.get("run").map(String::as_str), Some("--fast"));
+
+        let mut restored = App::new(sample_spec());
+        assert!(restored.restore_snapshot(&snapshot).is_empty());
+        assert_eq!(restored.extra_args(), "--fast");
+    }
+
+    fn env_spec() -> Spec {
+        r#"
+name "Env CLI"
+bin "envcli"
+
+flag "--token <token>" global=#true env="ENVCLI_TOKEN"
+
+cmd "deploy" {
+    arg "<target>" env="ENVCLI_TARGET"
+    flag "--region <region>" env="ENVCLI_REGION"
+}
+
+cmd "other"
+"#
+        .parse::<Spec>()
+        .expect("Failed to parse env test spec")
+    }
+
+    #[test]
+    fn test_env_editor_sets_variables_per_command() {
+        use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+        let mut app = App::new(env_spec());
+        app.navigate_to_command(&["deploy"]);
+        app.set_focus(Focus::Preview);
+        press(&mut app, KeyCode::Char('E'));
+        assert!(app.is_env_editing());
+
+        // Spec variables are listed: flags first, then args
+        press(&mut app, KeyCode::Enter);
+        assert!(app.is_env_entry_editing());
+        app.handle_key(KeyEvent::new(KeyCode::Char('u'), KeyModifiers::CONTROL));
+        app.handle_paste("ENVCLI_REGION=eu west");
+        press(&mut app, KeyCode::Enter);
+        press(&mut app, KeyCode::Down);
+        press(&mut app, KeyCode::Char('u'));
+        press(&mut app, KeyCode::Esc);
+        assert!(!app.is_env_editing());
+
+        assert_eq!(
+            app.env_vars(),
+            [
+                EnvVar::set("ENVCLI_REGION", "eu west"),
+                EnvVar::unset("ENVCLI_TOKEN"),
+            ]
+        );
+        assert_eq!(
+            app.build_command(),
+            "env -u ENVCLI_TOKEN ENVCLI_REGION='eu west' envcli deploy"
+        );
+        assert_eq!(app.build_shell_command().unwrap(), app.build_command());
+        assert_eq!(app.build_command_parts(), vec!["envcli", "deploy"]);
+
+        // Overrides belong to the command path
+        app.navigate_to_command(&["other"]);
+        assert!(app.env_vars().is_empty());
+        assert_eq!(app.build_command(), "envcli other");
+        app.navigate_to_command(&["deploy"]);
+        assert_eq!(app.env_vars().len(), 2);
+    }
+
+    #[test]
+    fn test_snapshot_restores_env() {
+        let mut app = App::new(env_spec());
+        app.navigate_to_command(&["deploy"]);
+        app.set_env_vars(vec![EnvVar::set("ENVCLI_REGION", "us")]);
+        let snapshot = app.snapshot();
+        assert_eq!(snapshot.env["deploy"], [EnvVar::set("ENVCLI_REGION", "us")]);
+
+        let json = serde_json::to_string(&snapshot).unwrap();
+        let snapshot: BuilderSnapshot = serde_json::from_str(&json).unwrap();
+        let mut restored = App::new(env_spec());
+        assert!(restored.restore_snapshot(&snapshot).is_empty());
+        assert_eq!(restored.env_vars(), [EnvVar::set("ENVCLI_REGION", "us")]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_spawned_command_gets_env_overrides() {
+        let parts = [
+            "sh".to_string(),
+            "-c".to_string(),
+            "echo \"[${ENVCLI_REGION}] [${HOME-unset}]\"".to_string(),
+        ];
+        let env = [EnvVar::set("ENVCLI_REGION", "eu west"), EnvVar::unset("HOME")];
+        let size = ratatui::layout::Size {
+            width: 80,
+            height: 24,
+        };
+        let exec = ExecutionComponent::spawn("sh".to_string(), &parts, &env, None, size, 0).unwrap();
+        let printed = || exec.transcript().to_text().contains("[eu west] [unset]");
+        assert!(wait_until(printed, Duration::from_secs(5)));
+    }
+
+    fn cwd_spec() -> Spec {
+        r#"
+name "Dir CLI"
+bin "dircli"
+
+arg "<file>"
+arg "[more]..." var=#true
+"#
+        .parse::<Spec>()
+        .expect("Failed to parse cwd test spec")
+    }
+
+    #[test]
+    fn test_working_directory_for_runs() {
+        use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+        let dir = std::env::temp_dir().join(format!("tuisage-test-cwd-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let dir = dir.canonicalize().unwrap();
+
+        let mut app = App::new(cwd_spec());
+        app.set_focus(Focus::Preview);
+        press(&mut app, KeyCode::Char('D'));
+        assert!(app.is_dir_picking());
+        press(&mut app, KeyCode::Char('/'));
+        app.handle_key(KeyEvent::new(KeyCode::Char('u'), KeyModifiers::CONTROL));
+        app.handle_paste(&dir.display().to_string());
+        press(&mut app, KeyCode::Enter);
+        // ./ is selected in the typed directory
+        press(&mut app, KeyCode::Enter);
+        assert!(!app.is_dir_picking());
+        assert_eq!(app.cwd.as_deref(), Some(dir.as_path()));
+        assert_eq!(app.recent_dirs().first(), Some(&dir));
+
+        app.set_arg_value(0, "./notes.txt".to_string());
+        app.set_arg_values(1, vec!["../shared".to_string(), "plain".to_string()]);
+        assert_eq!(
+            app.build_shell_command().unwrap(),
+            format!("cd {} && dircli ./notes.txt ../shared plain", dir.display())
+        );
+
+        // Relative paths resolve against the chosen directory once enabled;
+        // words stay words even when a file of that name exists there
+        std::fs::write(dir.join("plain"), "").unwrap();
+        press(&mut app, KeyCode::Char('D'));
+        press(&mut app, KeyCode::Char('p'));
+        press(&mut app, KeyCode::Esc);
+        assert!(app.config.behavior.resolve_paths);
+        assert_eq!(
+            app.build_command_parts(),
+            vec![
+                "dircli".to_string(),
+                dir.join("notes.txt").display().to_string(),
+                dir.join("../shared").display().to_string(),
+                "plain".to_string(),
+            ]
+        );
+
+        // The directory is part of the snapshot
+        let json = serde_json::to_string(&app.snapshot()).unwrap();
+        let snapshot: BuilderSnapshot = serde_json::from_str(&json).unwrap();
+        let mut restored = App::new(cwd_spec());
+        assert!(restored.restore_snapshot(&snapshot).is_empty());
+        assert_eq!(restored.cwd.as_deref(), Some(dir.as_path()));
+
+        press(&mut app, KeyCode::Char('D'));
+        press(&mut app, KeyCode::Char('r'));
+        assert_eq!(app.cwd, None);
+        assert_eq!(app.build_shell_command().unwrap().split_whitespace().next(), Some("dircli"));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+        let warnings = restored.restore_snapshot(&snapshot);
+        assert_eq!(warnings, vec![format!("directory '{}'", dir.display())]);
+        assert_eq!(restored.cwd, None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_spawned_command_runs_in_cwd() {
+        let dir = std::env::temp_dir().canonicalize().unwrap();
+        let parts = ["pwd".to_string()];
+        let size = ratatui::layout::Size {
+            width: 200,
+            height: 24,
+        };
+        let exec =
+            ExecutionComponent::spawn("pwd".to_string(), &parts, &[], Some(&dir), size, 0).unwrap();
+        let printed = || exec.transcript().to_text().contains(&dir.display().to_string());
+        assert!(wait_until(printed, Duration::from_secs(5)));
+    }
+
     #[test]
     fn test_key_handling_quit() {
         let mut app = App::new(sample_spec());
@@ -3643,6 +5956,7 @@ cmd "other" {
     fn test_ctrl_r_executes_from_any_panel() {
         let mut app = App::new(sample_spec());
         app.navigate_to_command(&["deploy"]);
+        app.set_arg_value(0, "prod".to_string());
 
         // Test from Commands panel
         app.set_focus(Focus::Commands);
@@ -3665,6 +5979,50 @@ cmd "other" {
         assert_eq!(app.handle_key(ctrl_r), Action::Execute);
     }
 
+    #[test]
+    fn test_invalid_command_needs_confirmation() {
+        let mut app = App::new(sample_spec());
+        app.navigate_to_command(&["deploy"]);
+        app.set_focus(Focus::Preview);
+        let ctrl_r = crossterm::event::KeyEvent::new(
+            crossterm::event::KeyCode::Char('r'),
+            crossterm::event::KeyModifiers::CONTROL,
+        );
+
+        assert_eq!(app.handle_key(ctrl_r), Action::None);
+        assert_eq!(
+            app.status_message.as_deref(),
+            Some("<environment>: required — repeat to run anyway")
+        );
+
+        // Any other key in between cancels the confirmation
+        press(&mut app, crossterm::event::KeyCode::Down);
+        assert_eq!(app.handle_key(ctrl_r), Action::None);
+        assert_eq!(app.handle_key(ctrl_r), Action::Execute);
+
+        // Printing is held back the same way
+        assert_eq!(press(&mut app, crossterm::event::KeyCode::Char('p')), Action::None);
+        assert_eq!(press(&mut app, crossterm::event::KeyCode::Char('p')), Action::Print);
+    }
+
+    #[test]
+    fn test_diagnostics_track_current_values() {
+        let mut app = App::new(sample_spec());
+        app.navigate_to_command(&["deploy"]);
+        assert_eq!(app.diagnostics().len(), 1);
+
+        app.set_arg_value(0, "qa".to_string());
+        let diagnostics = app.diagnostics();
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(
+            diagnostics[0].describe(&app.visible_flags(), &app.arg_values),
+            "<environment>: 'qa' is not one of dev|staging|prod"
+        );
+
+        app.set_arg_value(0, "prod".to_string());
+        assert!(app.diagnostics().is_empty());
+    }
+
     #[test]
     fn test_build_command_parts_basic() {
         let app = App::new(sample_spec());
@@ -3751,7 +6109,7 @@ cmd "other" {
         let app = App::new(sample_spec());
         assert_eq!(app.mode, AppMode::Builder);
         assert!(!app.is_executing());
-        assert!(app.execution.is_none());
+        assert!(app.execution().is_none());
     }
 
     #[test]
@@ -3776,148 +6134,1119 @@ cmd "other" {
         app.start_execution(ExecutionComponent::new(state));
         assert_eq!(app.mode, AppMode::Executing);
         assert!(app.is_executing());
-        assert!(app.execution.is_some());
-        assert!(!app.execution.as_ref().unwrap().exited());
+        assert!(app.execution().is_some());
+        assert!(!app.execution().unwrap().exited());
 
         // Simulate process exit
         exited.store(true, Ordering::Relaxed);
-        assert!(app.execution.as_ref().unwrap().exited());
+        assert!(app.execution().unwrap().exited());
+
+        // Close execution
+        app.close_execution();
+        assert_eq!(app.mode, AppMode::Builder);
+        assert!(!app.is_executing());
+        assert!(app.execution().is_none());
+    }
+
+    #[test]
+    fn test_execution_exit_status() {
+        let mut app = App::new(sample_spec());
+
+        let parser = Arc::new(RwLock::new(vt100::Parser::new(24, 80, 0)));
+        let exited = Arc::new(AtomicBool::new(true));
+        let exit_status = Arc::new(Mutex::new(Some("0".to_string())));
+        let pty_writer: Arc<Mutex<Option<Box<dyn std::io::Write + Send>>>> =
+            Arc::new(Mutex::new(None));
+
+        let state = ExecutionState {
+            command_display: "mycli deploy".to_string(),
+            parser,
+            pty_writer,
+            pty_master: Arc::new(Mutex::new(None)),
+            exited,
+            exit_status,
+        };
+
+        app.start_execution(ExecutionComponent::new(state));
+        assert_eq!(app.execution().unwrap().exit_status(), Some("0".to_string()));
+    }
+
+    #[test]
+    fn test_execution_key_closes_on_exit() {
+        let mut app = App::new(sample_spec());
+
+        let parser = Arc::new(RwLock::new(vt100::Parser::new(24, 80, 0)));
+        let exited = Arc::new(AtomicBool::new(true));
+        let exit_status = Arc::new(Mutex::new(Some("0".to_string())));
+        let pty_writer: Arc<Mutex<Option<Box<dyn std::io::Write + Send>>>> =
+            Arc::new(Mutex::new(None));
+
+        let state = ExecutionState {
+            command_display: "mycli".to_string(),
+            parser,
+            pty_writer,
+            pty_master: Arc::new(Mutex::new(None)),
+            exited,
+            exit_status,
+        };
+
+        app.start_execution(ExecutionComponent::new(state));
+        assert!(app.is_executing());
+
+        // Pressing Esc when exited should close execution and return to builder
+        let esc = crossterm::event::KeyEvent::new(
+            crossterm::event::KeyCode::Esc,
+            crossterm::event::KeyModifiers::NONE,
+        );
+        let result = app.handle_key(esc);
+        assert_eq!(result, Action::None);
+        assert!(!app.is_executing());
+        assert_eq!(app.mode, AppMode::Builder);
+    }
+
+    #[test]
+    fn test_execution_key_enter_closes_on_exit() {
+        let mut app = App::new(sample_spec());
+
+        let parser = Arc::new(RwLock::new(vt100::Parser::new(24, 80, 0)));
+        let exited = Arc::new(AtomicBool::new(true));
+        let exit_status = Arc::new(Mutex::new(None));
+        let pty_writer: Arc<Mutex<Option<Box<dyn std::io::Write + Send>>>> =
+            Arc::new(Mutex::new(None));
+
+        let state = ExecutionState {
+            command_display: "mycli".to_string(),
+            parser,
+            pty_writer,
+            pty_master: Arc::new(Mutex::new(None)),
+            exited,
+            exit_status,
+        };
+
+        app.start_execution(ExecutionComponent::new(state));
+
+        let enter = crossterm::event::KeyEvent::new(
+            crossterm::event::KeyCode::Enter,
+            crossterm::event::KeyModifiers::NONE,
+        );
+        let result = app.handle_key(enter);
+        assert_eq!(result, Action::None);
+        assert!(!app.is_executing());
+    }
+
+    #[test]
+    fn test_preview_enter_returns_execute_action() {
+        let mut app = App::new(sample_spec());
+        app.set_arg_value(0, "demo".to_string());
+        app.set_focus(Focus::Preview);
+
+        let enter = crossterm::event::KeyEvent::new(
+            crossterm::event::KeyCode::Enter,
+            crossterm::event::KeyModifiers::NONE,
+        );
+        let result = app.handle_key(enter);
+        assert_eq!(result, Action::Execute);
+    }
+
+    #[test]
+    fn test_preview_enter_prints_when_configured() {
+        let mut app = App::new(sample_spec());
+        app.set_arg_value(0, "demo".to_string());
+        app.config.behavior.preview_enter = PreviewEnter::Print;
+        app.set_focus(Focus::Preview);
+        assert_eq!(press(&mut app, crossterm::event::KeyCode::Enter), Action::Print);
+
+        // Ctrl+R still executes
+        let ctrl_r = crossterm::event::KeyEvent::new(
+            crossterm::event::KeyCode::Char('r'),
+            crossterm::event::KeyModifiers::CONTROL,
+        );
+        assert_eq!(app.handle_key(ctrl_r), Action::Execute);
+    }
+
+    #[test]
+    fn test_p_on_preview_prints() {
+        let mut app = App::new(sample_spec());
+        app.set_arg_value(0, "demo".to_string());
+        app.set_focus(Focus::Preview);
+        assert_eq!(press(&mut app, crossterm::event::KeyCode::Char('p')), Action::Print);
+    }
+
+    #[test]
+    fn test_build_shell_command_quotes_parts() {
+        let mut app = App::new(sample_spec());
+        app.navigate_to_command(&["deploy"]);
+        app.set_arg_value(0, "it's $HOME".to_string());
+        assert_eq!(app.build_shell_command().unwrap(), "mycli deploy 'it'\\''s $HOME'");
+
+        app.set_arg_value(0, "prod".to_string());
+        assert_eq!(app.build_shell_command().unwrap(), "mycli deploy prod");
+    }
+
+    #[test]
+    fn test_build_shell_command_uses_configured_shell() {
+        let mut app = App::new(sample_spec());
+        app.navigate_to_command(&["deploy"]);
+        app.set_arg_value(0, "it's".to_string());
+        app.config.behavior.shell = crate::quoting::Dialect::PowerShell;
+        assert_eq!(app.build_shell_command().unwrap(), "mycli deploy 'it''s'");
+        assert_eq!(app.build_command(), app.build_shell_command().unwrap());
+    }
+
+    #[test]
+    fn test_build_shell_command_sets_env_for_the_shell() {
+        use crate::quoting::Dialect;
+
+        let mut app = App::new(env_spec());
+        app.navigate_to_command(&["deploy"]);
+        app.set_env_vars(vec![
+            EnvVar::set("ENVCLI_REGION", "eu west"),
+            EnvVar::unset("ENVCLI_TOKEN"),
+        ]);
+        app.config.behavior.shell = Dialect::PowerShell;
+        assert_eq!(
+            app.build_shell_command().unwrap(),
+            "$env:ENVCLI_REGION='eu west'; $env:ENVCLI_TOKEN=$null; envcli deploy"
+        );
+        app.config.behavior.shell = Dialect::Cmd;
+        assert_eq!(
+            app.build_shell_command().unwrap(),
+            r#"set "ENVCLI_REGION=eu west" && set "ENVCLI_TOKEN=" && envcli deploy"#
+        );
+
+        // cmd can't write some values, so the command isn't printed
+        app.set_env_vars(vec![EnvVar::set("ENVCLI_REGION", "100%")]);
+        assert_eq!(
+            app.build_shell_command(),
+            Err("cmd can't write the value of ENVCLI_REGION".to_string())
+        );
+    }
+
+    #[test]
+    fn test_configured_keybindings() {
+        let mut app = App::new(sample_spec());
+        app.set_arg_value(0, "demo".to_string());
+        app.config = crate::config::Config::parse(
+            "[keys]\nquit = \"ctrl+q\"\nexecute = \"f5\"\nhistory = \"alt+h\"",
+        )
+        .unwrap();
+        app.set_focus(Focus::Preview);
+
+        assert_eq!(press(&mut app, crossterm::event::KeyCode::Char('q')), Action::None);
+        let ctrl_q = crossterm::event::KeyEvent::new(
+            crossterm::event::KeyCode::Char('q'),
+            crossterm::event::KeyModifiers::CONTROL,
+        );
+        assert_eq!(app.handle_key(ctrl_q), Action::Quit);
+        assert_eq!(press(&mut app, crossterm::event::KeyCode::F(5)), Action::Execute);
+
+        app.history
+            .record(HistoryEntry {
+                spec: app.spec_id(),
+                timestamp: 0,
+                cwd: None,
+                command: app.build_command(),
+                argv: app.build_command_parts(),
+                exit_status: None,
+                state: app.snapshot(),
+            })
+            .unwrap();
+        press(&mut app, crossterm::event::KeyCode::Char('H'));
+        assert!(!app.is_history_picking(), "Default key is unbound");
+        let alt_h = crossterm::event::KeyEvent::new(
+            crossterm::event::KeyCode::Char('h'),
+            crossterm::event::KeyModifiers::ALT,
+        );
+        app.handle_key(alt_h);
+        assert!(app.is_history_picking());
+    }
+
+    #[test]
+    fn test_execution_key_does_not_close_while_running() {
+        let mut app = App::new(sample_spec());
+
+        let parser = Arc::new(RwLock::new(vt100::Parser::new(24, 80, 0)));
+        let exited = Arc::new(AtomicBool::new(false)); // still running
+        let exit_status = Arc::new(Mutex::new(None));
+        let pty_writer: Arc<Mutex<Option<Box<dyn std::io::Write + Send>>>> =
+            Arc::new(Mutex::new(None));
+
+        let state = ExecutionState {
+            command_display: "mycli".to_string(),
+            parser,
+            pty_writer,
+            pty_master: Arc::new(Mutex::new(None)),
+            exited,
+            exit_status,
+        };
+
+        app.start_execution(ExecutionComponent::new(state));
+
+        // Pressing 'q' while running should NOT close (it's forwarded to PTY)
+        let q = crossterm::event::KeyEvent::new(
+            crossterm::event::KeyCode::Char('q'),
+            crossterm::event::KeyModifiers::NONE,
+        );
+        let result = app.handle_key(q);
+        assert_eq!(result, Action::None);
+        assert!(app.is_executing(), "Should still be executing");
+    }
+
+    /// An execution whose output has scrolled 20 lines into the scrollback.
+    fn execution_with_scrollback(exited: bool) -> ExecutionComponent {
+        let parser = Arc::new(RwLock::new(vt100::Parser::new(5, 20, 100)));
+        for i in 0..25 {
+            parser.write().unwrap().process(format!("line {i}\r\n").as_bytes());
+        }
+        ExecutionComponent::new(ExecutionState {
+            command_display: "mycli".to_string(),
+            parser,
+            pty_writer: Arc::new(Mutex::new(None)),
+            pty_master: Arc::new(Mutex::new(None)),
+            exited: Arc::new(AtomicBool::new(exited)),
+            exit_status: Arc::new(Mutex::new(Some("0".to_string()))),
+        })
+    }
+
+    #[test]
+    fn test_execution_scrolls_after_exit() {
+        use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+        let mut app = App::new(sample_spec());
+        app.start_execution(execution_with_scrollback(true));
+        let exec = |app: &App| app.execution().unwrap().scroll_position();
+        assert_eq!(exec(&app), (0, 21));
+
+        app.handle_key(KeyEvent::new(KeyCode::PageUp, KeyModifiers::NONE));
+        assert_eq!(exec(&app), (4, 21), "a page is the screen height less one line");
+        app.handle_key(KeyEvent::new(KeyCode::Home, KeyModifiers::NONE));
+        assert_eq!(exec(&app), (21, 21));
+        app.handle_key(KeyEvent::new(KeyCode::Down, KeyModifiers::NONE));
+        assert_eq!(exec(&app), (20, 21));
+        app.handle_key(KeyEvent::new(KeyCode::End, KeyModifiers::NONE));
+        assert_eq!(exec(&app), (0, 21));
+        assert!(app.is_executing(), "scroll keys don't close the view");
+    }
+
+    #[test]
+    fn test_execution_scroll_mode_while_running() {
+        use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseEvent, MouseEventKind};
+
+        let mut app = App::new(sample_spec());
+        app.start_execution(execution_with_scrollback(false));
+        fn exec(app: &App) -> &ExecutionComponent {
+            app.execution().unwrap()
+        }
+        assert!(!exec(&app).is_scrolling());
+
+        // Keys go to the process until scroll mode is toggled on
+        app.handle_key(KeyEvent::new(KeyCode::Up, KeyModifiers::NONE));
+        assert_eq!(exec(&app).scroll_position(), (0, 21));
+        press_prefixed(&mut app, KeyEvent::new(KeyCode::Char('s'), KeyModifiers::ALT));
+        assert!(exec(&app).is_scrolling());
+        app.handle_key(KeyEvent::new(KeyCode::Up, KeyModifiers::NONE));
+        assert_eq!(exec(&app).scroll_position(), (1, 21));
+
+        // Esc returns to the live screen
+        app.handle_key(KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE));
+        assert!(!exec(&app).is_scrolling());
+        assert_eq!(exec(&app).scroll_position(), (0, 21));
+        assert!(app.is_executing());
+
+        // The wheel scrolls back, and scrolling down to the bottom resumes input
+        let wheel = |kind| MouseEvent {
+            kind,
+            column: 0,
+            row: 0,
+            modifiers: KeyModifiers::NONE,
+        };
+        app.handle_mouse(wheel(MouseEventKind::ScrollUp));
+        assert!(exec(&app).is_scrolling());
+        assert_eq!(exec(&app).scroll_position(), (3, 21));
+        app.handle_mouse(wheel(MouseEventKind::ScrollDown));
+        assert!(!exec(&app).is_scrolling());
+        assert_eq!(exec(&app).scroll_position(), (0, 21));
+    }
+
+    #[test]
+    fn test_execution_saves_transcript() {
+        use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+        let dir = std::env::temp_dir().join(format!("tuisage-test-app-transcript-{}", std::process::id()));
+        let mut app = App::new(sample_spec());
+        app.config.behavior.transcript_dir = Some(dir.clone());
+
+        // `s` saves once the command has exited
+        app.start_execution(execution_with_scrollback(true));
+        app.handle_key(KeyEvent::new(KeyCode::Char('s'), KeyModifiers::NONE));
+        let saved = app.execution().unwrap().transcript_path().unwrap().to_path_buf();
+        assert!(saved.starts_with(&dir));
+        assert!(std::fs::read_to_string(&saved).unwrap().starts_with("# command: mycli\n"));
+        app.close_execution();
+
+        // Closing saves the transcript when it wasn't saved already
+        std::fs::remove_dir_all(&dir).unwrap();
+        app.start_execution(execution_with_scrollback(true));
+        app.close_execution();
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 3);
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    /// A running execution, with the flag that marks it as exited.
+    fn running_execution(command: &str) -> (ExecutionComponent, Arc<AtomicBool>) {
+        let exited = Arc::new(AtomicBool::new(false));
+        let component = ExecutionComponent::new(ExecutionState {
+            command_display: command.to_string(),
+            parser: Arc::new(RwLock::new(vt100::Parser::new(24, 80, 0))),
+            pty_writer: Arc::new(Mutex::new(None)),
+            pty_master: Arc::new(Mutex::new(None)),
+            exited: exited.clone(),
+            exit_status: Arc::new(Mutex::new(Some("Exited with code 3".to_string()))),
+        });
+        (component, exited)
+    }
+
+    #[test]
+    fn test_background_and_foreground_jobs() {
+        use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+        let mut app = App::new(sample_spec());
+        let (first, _) = running_execution("mycli build");
+        app.start_execution(first);
+        press_prefixed(&mut app, KeyEvent::new(KeyCode::Char('z'), KeyModifiers::CONTROL));
+        assert!(!app.is_executing());
+        assert_eq!(app.status_message.as_deref(), Some("[1] moved to the background — J lists jobs"));
+
+        let (second, _) = running_execution("mycli test");
+        app.start_execution(second);
+        press_prefixed(&mut app, KeyEvent::new(KeyCode::Char('z'), KeyModifiers::CONTROL));
+        assert_eq!(app.running_jobs(), 2);
+
+        app.handle_key(KeyEvent::new(KeyCode::Char('J'), KeyModifiers::NONE));
+        assert!(app.is_jobs_picking());
+        assert_eq!(app.jobs_picker.len(), 2);
+        app.handle_key(KeyEvent::new(KeyCode::Down, KeyModifiers::NONE));
+        app.handle_key(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+        assert!(app.is_executing());
+        assert_eq!(app.execution().unwrap().command_display(), "mycli test");
+    }
+
+    #[test]
+    fn test_finished_background_job_is_reported_and_dismissed() {
+        use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+        let mut app = App::new(sample_spec());
+        let (component, exited) = running_execution("mycli build");
+        app.start_execution(component);
+        app.background_execution();
+        assert!(!app.poll_jobs());
+        assert!(app.jobs[0].status().starts_with("● running "));
+
+        exited.store(true, Ordering::Relaxed);
+        assert!(app.poll_jobs());
+        assert_eq!(
+            app.status_message.as_deref(),
+            Some("[1] mycli build finished — ✗ exit 3 · 0s")
+        );
+        assert!(!app.poll_jobs(), "Each job is reported once");
+
+        // Finished jobs can be dismissed from the list
+        app.open_jobs_picker();
+        app.handle_key(KeyEvent::new(KeyCode::Char('d'), KeyModifiers::NONE));
+        assert!(app.jobs.is_empty());
+        assert!(!app.is_jobs_picking());
+    }
+
+    #[test]
+    fn test_running_jobs_cannot_be_dismissed() {
+        use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+        let mut app = App::new(sample_spec());
+        app.start_execution(running_execution("mycli build").0);
+        app.background_execution();
+        app.open_jobs_picker();
+        app.handle_key(KeyEvent::new(KeyCode::Char('d'), KeyModifiers::NONE));
+        assert_eq!(app.jobs.len(), 1);
+        assert!(app.is_jobs_picking());
+        assert_eq!(app.jobs_picker.len(), 1);
+        assert_eq!(app.status_message.as_deref(), Some("[1] is still running"));
+    }
+
+    #[test]
+    fn test_quit_with_running_jobs_needs_confirmation() {
+        use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+        let mut app = App::new(sample_spec());
+        let q = KeyEvent::new(KeyCode::Char('q'), KeyModifiers::NONE);
+        let (component, exited) = running_execution("mycli build");
+        app.start_execution(component);
+        app.background_execution();
+
+        assert_eq!(app.handle_key(q), Action::None);
+        assert_eq!(
+            app.status_message.as_deref(),
+            Some("1 job is still running — repeat to quit anyway")
+        );
+        assert_eq!(app.handle_key(q), Action::Quit);
+
+        // Ctrl+C goes through the same confirmation
+        assert_eq!(app.quit(), Action::None);
+        assert_eq!(app.quit(), Action::Quit);
+
+        exited.store(true, Ordering::Relaxed);
+        assert_eq!(app.handle_key(q), Action::Quit);
+    }
+
+    /// Collects what is written to a PTY.
+    #[derive(Clone, Default)]
+    struct CapturedInput(Arc<Mutex<Vec<u8>>>);
+
+    impl std::io::Write for CapturedInput {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl CapturedInput {
+        fn take(&self) -> Vec<u8> {
+            std::mem::take(&mut self.0.lock().unwrap())
+        }
+    }
+
+    /// A running execution whose PTY input is captured, after the process
+    /// wrote `output` (e.g. to set terminal modes).
+    fn capturing_execution(output: &[u8]) -> (ExecutionComponent, CapturedInput) {
+        let input = CapturedInput::default();
+        let parser = Arc::new(RwLock::new(vt100::Parser::new(20, 80, 0)));
+        parser.write().unwrap().process(output);
+        let component = ExecutionComponent::new(ExecutionState {
+            command_display: "vim".to_string(),
+            parser,
+            pty_writer: Arc::new(Mutex::new(Some(Box::new(input.clone())))),
+            pty_master: Arc::new(Mutex::new(None)),
+            exited: Arc::new(AtomicBool::new(false)),
+            exit_status: Arc::new(Mutex::new(None)),
+        });
+        (component, input)
+    }
+
+    #[test]
+    fn test_execution_forwards_keys_and_paste() {
+        use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+        let mut app = App::new(sample_spec());
+        // Application cursor keys and bracketed paste
+        let (component, input) = capturing_execution(b"\x1b[?1h\x1b[?2004h");
+        app.start_execution(component);
+
+        app.handle_key(KeyEvent::new(KeyCode::Up, KeyModifiers::NONE));
+        app.handle_key(KeyEvent::new(KeyCode::F(5), KeyModifiers::NONE));
+        app.handle_key(KeyEvent::new(KeyCode::Char('w'), KeyModifiers::CONTROL));
+        app.handle_key(KeyEvent::new(KeyCode::Char('f'), KeyModifiers::ALT));
+        app.handle_key(KeyEvent::new(KeyCode::PageDown, KeyModifiers::NONE));
+        assert_eq!(input.take(), b"\x1bOA\x1b[15~\x17\x1bf\x1b[6~");
+
+        app.handle_paste("echo hi\n");
+        assert_eq!(input.take(), b"\x1b[200~echo hi\r\x1b[201~");
+
+        // Not forwarded while scrolling
+        press_prefixed(&mut app, KeyEvent::new(KeyCode::Char('s'), KeyModifiers::ALT));
+        app.handle_paste("ls");
+        assert_eq!(input.take(), b"");
+    }
+
+    #[test]
+    fn test_execution_keys_need_the_prefix() {
+        use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+        let mut app = App::new(sample_spec());
+        let (component, input) = capturing_execution(b"");
+        app.start_execution(component);
+
+        // The execution view's own keys reach the command
+        app.handle_key(KeyEvent::new(KeyCode::Char('z'), KeyModifiers::CONTROL));
+        for c in ['t', 'k', 's', 'r', 'w'] {
+            assert_eq!(app.handle_key(KeyEvent::new(KeyCode::Char(c), KeyModifiers::ALT)), Action::None);
+        }
+        assert_eq!(input.take(), b"\x1a\x1bt\x1bk\x1bs\x1br\x1bw");
+        assert!(app.is_executing());
+        assert!(!app.execution().unwrap().is_scrolling());
+
+        // The prefix pressed twice reaches the command too
+        press_prefixed(&mut app, KeyEvent::new(KeyCode::Char('g'), KeyModifiers::CONTROL));
+        assert_eq!(input.take(), b"\x07");
+
+        // After the prefix, other keys are only consumed
+        press_prefixed(&mut app, KeyEvent::new(KeyCode::Char('x'), KeyModifiers::NONE));
+        assert_eq!(input.take(), b"");
+
+        press_prefixed(&mut app, KeyEvent::new(KeyCode::Char('z'), KeyModifiers::CONTROL));
+        assert!(!app.is_executing());
+        assert_eq!(app.running_jobs(), 1);
+        assert_eq!(input.take(), b"");
+    }
+
+    #[test]
+    fn test_paste_types_into_text_fields_only() {
+        let mut app = App::new(sample_spec());
+        app.handle_paste("S");
+        assert!(!app.is_prompting(), "pasted text doesn't trigger shortcuts");
+
+        app.start_save_favorite();
+        app.prompt.open("Save favorite as", "");
+        app.handle_paste("deploy\tprod\n");
+        assert_eq!(app.prompt.text(), "deploy prod");
+    }
+
+    #[test]
+    fn test_paste_command_loads_builder_state() {
+        use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+        let mut app = App::new(sample_spec());
+        app.env_by_path
+            .insert("init".to_string(), vec![EnvVar::set("KEEP", "1")]);
+        app.handle_key(KeyEvent::new(KeyCode::Char('P'), KeyModifiers::NONE));
+        assert!(app.is_pasting_command());
+        app.handle_paste("DEBUG=1 mycli -v config add --force theme 'dark blue'\n");
+        app.handle_key(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+        assert!(!app.is_prompting());
+        assert_eq!(app.command_path, vec!["config", "set"]);
+        assert_eq!(
+            app.build_command(),
+            "DEBUG=1 mycli -v config set --force theme 'dark blue'"
+        );
+        assert_eq!(app.status_message, None);
+        assert_eq!(
+            app.env_by_path.get("init"),
+            Some(&vec![EnvVar::set("KEEP", "1")]),
+            "other commands keep their overrides"
+        );
+
+        // Words that don't fit are reported, the rest is loaded
+        app.start_paste_command();
+        app.handle_paste("mycli deploy prod --bogus 'x y'");
+        app.handle_key(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+        assert_eq!(app.build_command(), "mycli deploy prod");
+        assert_eq!(
+            app.status_message.as_deref(),
+            Some("Not placed: --bogus 'x y'")
+        );
+
+        let err = app.load_command_line("mycli 'open").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Can't parse the command: unterminated single quote"
+        );
+    }
+
+    #[test]
+    fn test_initial_command() {
+        let mut app = App::new(sample_spec());
+        app.load_command_line("mycli deploy prod --tag v2").unwrap();
+        assert_eq!(app.command_path, vec!["deploy"]);
+        assert_eq!(app.arg_values[0].value, "prod");
+        assert_eq!(
+            app.current_flag_values()
+                .iter()
+                .find(|(name, _)| name == "tag")
+                .map(|(_, value)| value),
+            Some(&FlagValue::String("v2".to_string()))
+        );
+        assert_eq!(app.build_command(), "mycli deploy --tag v2 prod");
+
+        // Words after `--` are used as they are, without splitting
+        let mut app = App::new(sample_spec());
+        let parts: Vec<String> = ["mycli", "init", "my app", "--template", "full", "--bogus"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        app.load_command_parts(&parts);
+        assert_eq!(app.command_path, vec!["init"]);
+        assert_eq!(app.build_command(), "mycli init --template full 'my app'");
+        assert_eq!(app.status_message.as_deref(), Some("Not placed: --bogus"));
+    }
+
+    #[test]
+    fn test_reload_spec_keeps_builder_state() {
+        use crossterm::event::KeyCode;
+
+        let dir = std::env::temp_dir().join(format!("tuisage-test-reload-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("mycli.usage.kdl");
+        let text = include_str!("../fixtures/sample.usage.kdl");
+        std::fs::write(&path, text).unwrap();
+        let mut app = App::new(sample_spec());
+        app.set_spec_source(SpecSource::File(path.clone()), None);
+        assert!(app.is_watching_spec());
+        app.load_command_line("mycli deploy prod --tag v2 --yes").unwrap();
+
+        // The tag flag is removed and a command added
+        let changed = text.replace("    flag \"--tag <tag>\" help=\"Docker image tag\"\n", "");
+        std::fs::write(&path, format!("{changed}cmd \"lint\"\n")).unwrap();
+        press(&mut app, KeyCode::Char('R'));
+        assert!(app.spec.cmd.find_subcommand("lint").is_some());
+        assert_eq!(app.command_path, vec!["deploy"]);
+        assert_eq!(app.build_command(), "mycli deploy --yes prod");
+        assert_eq!(app.status_message.as_deref(), Some("Not restored: flag 'tag'"));
+
+        // A spec that doesn't parse leaves the current one in place
+        std::fs::write(&path, "cmd \"broken\" {").unwrap();
+        app.reload_spec();
+        assert!(app
+            .spec_error
+            .as_deref()
+            .is_some_and(|e| e.starts_with("Failed to parse usage spec")));
+        assert_eq!(app.build_command(), "mycli deploy --yes prod");
+
+        std::fs::write(&path, text).unwrap();
+        app.reload_spec();
+        assert_eq!(app.spec_error, None);
+        assert_eq!(app.status_message.as_deref(), Some("Reloaded the spec"));
+        assert!(app.spec.cmd.find_subcommand("lint").is_none());
+        assert_eq!(app.build_command(), "mycli deploy --yes prod");
+        let _ = std::fs::remove_dir_all(&dir);
+
+        // Stdin is read only once
+        let mut app = App::new(sample_spec());
+        app.set_spec_source(SpecSource::File(PathBuf::from("-")), None);
+        assert!(!app.is_watching_spec());
+        app.reload_spec();
+        assert_eq!(
+            app.status_message.as_deref(),
+            Some("A spec read from stdin can't be reloaded")
+        );
+    }
 
-        // Close execution
-        app.close_execution();
-        assert_eq!(app.mode, AppMode::Builder);
-        assert!(!app.is_executing());
-        assert!(app.execution.is_none());
+    #[test]
+    fn test_spec_reloads_when_its_file_changes() {
+        let dir =
+            std::env::temp_dir().join(format!("tuisage-test-reload-watch-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("mycli.usage.kdl");
+        let text = include_str!("../fixtures/sample.usage.kdl");
+        std::fs::write(&path, text).unwrap();
+        let mut app = App::new(sample_spec());
+        app.config.watch.debounce = crate::config::HumanDuration(Duration::from_millis(50));
+        app.set_spec_source(SpecSource::File(path.clone()), Some("mycli2".to_string()));
+
+        std::fs::write(&path, format!("{text}cmd \"lint\"\n")).unwrap();
+        let app = std::cell::RefCell::new(app);
+        let reloaded = || {
+            let mut app = app.borrow_mut();
+            app.poll_spec_reload();
+            app.spec.cmd.find_subcommand("lint").is_some()
+        };
+        assert!(wait_until(reloaded, Duration::from_secs(5)));
+        // The bin override applies to the reloaded spec too
+        assert_eq!(app.borrow().spec.bin, "mycli2");
+        let _ = std::fs::remove_dir_all(&dir);
     }
 
+    #[cfg(unix)]
     #[test]
-    fn test_execution_exit_status() {
+    fn test_cached_spec_is_refreshed_in_the_background() {
+        let dir =
+            std::env::temp_dir().join(format!("tuisage-test-spec-refresh-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let command = "printf 'bin \"mycli\"\ncmd \"lint\"'";
+        let cache = SpecCache::new(&dir, command, dir.clone(), &[]);
         let mut app = App::new(sample_spec());
+        app.set_spec_source(SpecSource::Command(command.to_string()), None);
+        app.set_spec_cache(cache.clone(), Some("cached output".to_string()));
+        assert!(app.is_refreshing_spec());
+
+        let app = std::cell::RefCell::new(app);
+        let refreshed = || {
+            let mut app = app.borrow_mut();
+            app.poll_spec_reload();
+            !app.is_refreshing_spec()
+        };
+        assert!(wait_until(refreshed, Duration::from_secs(5)));
+        let app = app.into_inner();
+        assert!(app.spec.cmd.find_subcommand("lint").is_some());
+        assert_eq!(app.command_path, vec!["lint"]);
+        assert_eq!(
+            app.status_message.as_deref(),
+            Some("Not restored: command 'init'")
+        );
+        // The new output was cached
+        assert!(cache.load().is_some());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
 
-        let parser = Arc::new(RwLock::new(vt100::Parser::new(24, 80, 0)));
-        let exited = Arc::new(AtomicBool::new(true));
-        let exit_status = Arc::new(Mutex::new(Some("0".to_string())));
-        let pty_writer: Arc<Mutex<Option<Box<dyn std::io::Write + Send>>>> =
-            Arc::new(Mutex::new(None));
+    #[test]
+    fn test_execution_forwards_mouse_when_requested() {
+        use crate::components::RenderableComponent;
+        use crossterm::event::{KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
+        use ratatui::buffer::Buffer;
 
-        let state = ExecutionState {
-            command_display: "mycli deploy".to_string(),
-            parser,
-            pty_writer,
-            pty_master: Arc::new(Mutex::new(None)),
-            exited,
-            exit_status,
+        let click = MouseEvent {
+            kind: MouseEventKind::Down(MouseButton::Left),
+            column: 10,
+            row: 5,
+            modifiers: KeyModifiers::NONE,
+        };
+        let render = |app: &mut App| {
+            let area = Rect::new(0, 0, 80, 24);
+            let colors = crate::theme::UiColors::from_palette(&app.palette());
+            app.execution_mut()
+                .unwrap()
+                .render(area, &mut Buffer::empty(area), &colors);
         };
 
-        app.start_execution(ExecutionComponent::new(state));
-        assert_eq!(app.execution.as_ref().unwrap().exit_status(), Some("0".to_string()));
-    }
+        // Without mouse reporting, clicks are not forwarded
+        let mut app = App::new(sample_spec());
+        let (component, input) = capturing_execution(b"");
+        app.start_execution(component);
+        render(&mut app);
+        app.handle_mouse(click);
+        assert_eq!(input.take(), b"");
 
-    #[test]
-    fn test_execution_key_closes_on_exit() {
+        // SGR mouse reporting, relative to the output below the command pane
         let mut app = App::new(sample_spec());
+        let (component, input) = capturing_execution(b"\x1b[?1000h\x1b[?1006h");
+        app.start_execution(component);
+        render(&mut app);
+        app.handle_mouse(click);
+        assert_eq!(input.take(), b"\x1b[<0;11;3M");
+
+        // Clicks on the command pane are not
+        app.handle_mouse(MouseEvent { row: 1, ..click });
+        assert_eq!(input.take(), b"");
+    }
+
+    /// Spawn a real command, as run from the builder.
+    #[cfg(unix)]
+    fn spawn_execution_of(script: &str) -> ExecutionComponent {
+        let parts = ["sh".to_string(), "-c".to_string(), script.to_string()];
+        let size = ratatui::layout::Size {
+            width: 80,
+            height: 24,
+        };
+        ExecutionComponent::spawn(script.to_string(), &parts, &[], None, size, 0).unwrap()
+    }
 
-        let parser = Arc::new(RwLock::new(vt100::Parser::new(24, 80, 0)));
-        let exited = Arc::new(AtomicBool::new(true));
-        let exit_status = Arc::new(Mutex::new(Some("0".to_string())));
-        let pty_writer: Arc<Mutex<Option<Box<dyn std::io::Write + Send>>>> =
-            Arc::new(Mutex::new(None));
+    #[cfg(unix)]
+    #[test]
+    fn test_terminate_and_kill_keys_signal_the_command() {
+        use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 
-        let state = ExecutionState {
-            command_display: "mycli".to_string(),
-            parser,
-            pty_writer,
-            pty_master: Arc::new(Mutex::new(None)),
-            exited,
-            exit_status,
-        };
+        let mut app = App::new(sample_spec());
+        app.start_execution(spawn_execution_of("sleep 30"));
+        press_prefixed(&mut app, KeyEvent::new(KeyCode::Char('t'), KeyModifiers::ALT));
+        let exec = app.execution().unwrap();
+        assert!(wait_until(|| exec.exited(), Duration::from_secs(5)));
+        assert_eq!(exec.exit_status().as_deref(), Some("Terminated by SIGTERM"));
+        assert_eq!(exec.exit_summary(), "Terminated by SIGTERM");
+        app.close_execution();
 
-        app.start_execution(ExecutionComponent::new(state));
-        assert!(app.is_executing());
+        // A command ignoring SIGTERM needs SIGKILL
+        app.start_execution(spawn_execution_of("trap '' TERM; sleep 30"));
+        std::thread::sleep(Duration::from_millis(200));
+        press_prefixed(&mut app, KeyEvent::new(KeyCode::Char('t'), KeyModifiers::ALT));
+        let exec = app.execution().unwrap();
+        assert!(!wait_until(|| exec.exited(), Duration::from_millis(300)));
+        press_prefixed(&mut app, KeyEvent::new(KeyCode::Char('k'), KeyModifiers::ALT));
+        let exec = app.execution().unwrap();
+        assert!(wait_until(|| exec.exited(), Duration::from_secs(5)));
+        assert_eq!(exec.exit_summary(), "Terminated by SIGKILL");
+    }
 
-        // Pressing Esc when exited should close execution and return to builder
-        let esc = crossterm::event::KeyEvent::new(
-            crossterm::event::KeyCode::Esc,
-            crossterm::event::KeyModifiers::NONE,
+    #[cfg(unix)]
+    #[test]
+    fn test_timeout_escalates_to_kill() {
+        let exec = spawn_execution_of("trap '' TERM; sleep 30")
+            .with_timeout(Some(Duration::from_millis(300)), Duration::from_millis(300));
+        assert!(wait_until(|| exec.exited(), Duration::from_secs(5)));
+        assert_eq!(
+            exec.exit_summary(),
+            "Terminated by SIGKILL, timed out after 300ms"
         );
-        let result = app.handle_key(esc);
-        assert_eq!(result, Action::None);
-        assert!(!app.is_executing());
-        assert_eq!(app.mode, AppMode::Builder);
     }
 
+    #[cfg(unix)]
     #[test]
-    fn test_execution_key_enter_closes_on_exit() {
+    fn test_quit_stops_running_jobs() {
         let mut app = App::new(sample_spec());
+        app.config.behavior.kill_after = crate::config::HumanDuration(Duration::from_millis(300));
+        app.start_execution(spawn_execution_of("sleep 30"));
+        app.background_execution();
+        app.start_execution(spawn_execution_of("trap '' TERM; sleep 30"));
+        std::thread::sleep(Duration::from_millis(200));
 
-        let parser = Arc::new(RwLock::new(vt100::Parser::new(24, 80, 0)));
-        let exited = Arc::new(AtomicBool::new(true));
-        let exit_status = Arc::new(Mutex::new(None));
-        let pty_writer: Arc<Mutex<Option<Box<dyn std::io::Write + Send>>>> =
-            Arc::new(Mutex::new(None));
+        app.stop_jobs();
+        assert_eq!(app.running_jobs(), 0);
+        let statuses: Vec<_> = app.jobs.iter().map(|j| j.execution.exit_summary()).collect();
+        assert_eq!(statuses, ["Terminated by SIGTERM", "Terminated by SIGKILL"]);
+    }
 
-        let state = ExecutionState {
-            command_display: "mycli".to_string(),
-            parser,
-            pty_writer,
-            pty_master: Arc::new(Mutex::new(None)),
-            exited,
-            exit_status,
-        };
+    fn sleep_spec() -> Spec {
+        r#"
+name "Sleep"
+bin "sleep"
 
-        app.start_execution(ExecutionComponent::new(state));
+arg "<seconds>"
+"#
+        .parse::<Spec>()
+        .expect("Failed to parse sleep test spec")
+    }
 
-        let enter = crossterm::event::KeyEvent::new(
-            crossterm::event::KeyCode::Enter,
-            crossterm::event::KeyModifiers::NONE,
-        );
-        let result = app.handle_key(enter);
-        assert_eq!(result, Action::None);
+    #[test]
+    fn test_split_layout_keeps_the_builder() {
+        use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+        let mut app = App::new(sample_spec());
+        app.config.behavior.layout = OutputLayout::Right;
+        app.set_focus(Focus::Preview);
+        app.start_execution(spawn_execution_of("cat"));
         assert!(!app.is_executing());
+        assert!(app.has_output_pane());
+        assert_eq!(app.focus(), Focus::Preview);
+
+        // Tab reaches the output pane, whose keys go to the command
+        press(&mut app, KeyCode::Tab);
+        assert_eq!(app.focus(), Focus::Output);
+        assert_eq!(app.handle_key(KeyEvent::new(KeyCode::Char('q'), KeyModifiers::NONE)), Action::None);
+        press(&mut app, KeyCode::BackTab);
+        assert_eq!(app.focus(), Focus::Preview);
+
+        // Backgrounding the job removes the pane and its focus
+        press(&mut app, KeyCode::Tab);
+        press_prefixed(&mut app, KeyEvent::new(KeyCode::Char('z'), KeyModifiers::CONTROL));
+        assert!(!app.has_output_pane());
+        assert_eq!(app.focus(), Focus::Preview);
+        assert_eq!(app.running_jobs(), 1);
+
+        // The layout key cycles back to full screen, showing the job there
+        app.foreground(app.jobs[0].id);
+        assert_eq!(app.focus(), Focus::Output);
+        press(&mut app, KeyCode::Tab);
+        press(&mut app, KeyCode::Char('L'));
+        assert_eq!(app.config.behavior.layout, OutputLayout::Below);
+        assert!(app.has_output_pane());
+        press(&mut app, KeyCode::Char('L'));
+        assert_eq!(app.config.behavior.layout, OutputLayout::Full);
+        assert!(app.is_executing());
+        app.finish_jobs();
     }
 
+    #[cfg(unix)]
     #[test]
-    fn test_preview_enter_returns_execute_action() {
-        let mut app = App::new(sample_spec());
+    fn test_rerun_replaces_the_latest_run() {
+        use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+        let size = ratatui::layout::Size {
+            width: 80,
+            height: 24,
+        };
+        let rerun = KeyEvent::new(KeyCode::Char('r'), KeyModifiers::ALT);
+        let mut app = App::new(sleep_spec());
+        app.config.behavior.layout = OutputLayout::Below;
+        app.arg_values[0].value = "30".to_string();
+        app.spawn_execution(size).unwrap();
+        let first = app.foreground_job.unwrap();
+
+        // From the output pane, the rerun key stops the command and runs it again
+        app.set_focus(Focus::Output);
+        assert_eq!(press_prefixed(&mut app, rerun), Action::Rerun);
+        app.rerun(size).unwrap();
+        let second = app.foreground_job.unwrap();
+        assert_ne!(first, second);
+        assert_eq!(app.jobs.len(), 1);
+        assert!(app.has_output_pane());
+
+        // The replaced run stops in the background and is recorded once it has
+        assert_eq!(app.stopping.len(), 1);
+        assert!(wait_until(
+            || app.stopping[0].execution.exited(),
+            Duration::from_secs(5)
+        ));
+        app.poll_jobs();
+        assert!(app.stopping.is_empty());
+        let entries = app.history.entries_for(&app.spec_id());
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].exit_status.as_deref(), Some("Terminated by SIGTERM"));
+
+        // From the builder, an invalid command needs confirming first
         app.set_focus(Focus::Preview);
+        app.arg_values[0].value.clear();
+        assert_eq!(app.handle_key(rerun), Action::None);
+        assert_eq!(app.handle_key(rerun), Action::Rerun);
+        app.finish_jobs();
+    }
 
-        let enter = crossterm::event::KeyEvent::new(
-            crossterm::event::KeyCode::Enter,
-            crossterm::event::KeyModifiers::NONE,
-        );
-        let result = app.handle_key(enter);
-        assert_eq!(result, Action::Execute);
+    #[cfg(unix)]
+    #[test]
+    fn test_rerun_doesnt_wait_for_the_old_run() {
+        let size = ratatui::layout::Size {
+            width: 80,
+            height: 24,
+        };
+        let mut app = App::new(sleep_spec());
+        app.config.behavior.kill_after = crate::config::HumanDuration(Duration::from_millis(500));
+        app.start_execution(spawn_execution_of("trap '' TERM; sleep 30"));
+        std::thread::sleep(Duration::from_millis(200));
+
+        app.arg_values[0].value = "30".to_string();
+        let started = std::time::Instant::now();
+        app.rerun(size).unwrap();
+        assert!(started.elapsed() < Duration::from_millis(500));
+        assert_eq!(app.running_jobs(), 1);
+
+        // SIGKILL follows once the grace period is over
+        assert!(wait_until(
+            || app.stopping[0].execution.exited(),
+            Duration::from_secs(5)
+        ));
+        assert_eq!(app.stopping[0].execution.exit_summary(), "Terminated by SIGKILL");
+        app.finish_jobs();
+    }
+
+    fn echo_spec() -> Spec {
+        r#"
+name "Echo"
+bin "echo"
+
+arg "<text>"
+"#
+        .parse::<Spec>()
+        .expect("Failed to parse echo test spec")
     }
 
+    #[cfg(unix)]
     #[test]
-    fn test_execution_key_does_not_close_while_running() {
-        let mut app = App::new(sample_spec());
+    fn test_run_again_from_the_execution_view() {
+        use crossterm::event::KeyCode;
 
-        let parser = Arc::new(RwLock::new(vt100::Parser::new(24, 80, 0)));
-        let exited = Arc::new(AtomicBool::new(false)); // still running
-        let exit_status = Arc::new(Mutex::new(None));
-        let pty_writer: Arc<Mutex<Option<Box<dyn std::io::Write + Send>>>> =
-            Arc::new(Mutex::new(None));
+        let size = ratatui::layout::Size {
+            width: 80,
+            height: 24,
+        };
+        let mut app = App::new(echo_spec());
+        app.arg_values[0].value = "hello".to_string();
+        app.spawn_execution(size).unwrap();
+        let exited = |app: &App| app.execution().unwrap().exited();
+        assert!(wait_until(|| exited(&app), Duration::from_secs(5)));
+
+        // Changes in the builder don't affect running the same command again
+        app.arg_values[0].value = "changed".to_string();
+        press(&mut app, KeyCode::Char('r'));
+        assert!(app.is_executing());
+        assert_eq!(app.jobs.len(), 1);
+        assert_eq!(app.history.entries_for(&app.spec_id()).len(), 1);
+        assert!(wait_until(|| exited(&app), Duration::from_secs(5)));
+        let exec = app.execution().unwrap();
+        assert_eq!(exec.run_number(), 2);
+        let text = exec.screen_text();
+        assert_eq!(text.matches("hello").count(), 2, "output is appended: {text}");
+        assert!(text.contains("── run 1 · exit 0 · 0s ──"));
+        assert!(!text.contains("changed"));
+
+        // With rerun_output = "clear", only the latest run is shown
+        app.config.behavior.rerun_output = RerunOutput::Clear;
+        press(&mut app, KeyCode::Char('r'));
+        assert!(wait_until(|| exited(&app), Duration::from_secs(5)));
+        let text = app.execution().unwrap().screen_text();
+        assert_eq!(text.matches("hello").count(), 1, "output is cleared: {text}");
+        assert_eq!(app.history.entries_for(&app.spec_id()).len(), 2);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_edit_from_the_execution_view() {
+        use crossterm::event::KeyCode;
 
-        let state = ExecutionState {
-            command_display: "mycli".to_string(),
-            parser,
-            pty_writer,
-            pty_master: Arc::new(Mutex::new(None)),
-            exited,
-            exit_status,
+        let size = ratatui::layout::Size {
+            width: 80,
+            height: 24,
         };
+        let mut app = App::new(echo_spec());
+        app.arg_values[0].value = "hello".to_string();
+        app.spawn_execution(size).unwrap();
+        assert!(wait_until(
+            || app.execution().unwrap().exited(),
+            Duration::from_secs(5)
+        ));
+
+        // The builder gets the state the command was run with
+        app.arg_values[0].value = "changed".to_string();
+        press(&mut app, KeyCode::Char('e'));
+        assert!(!app.is_executing());
+        assert!(app.jobs.is_empty());
+        assert_eq!(app.focus(), Focus::Preview);
+        assert_eq!(app.arg_values[0].value, "hello");
+        assert_eq!(app.history.entries_for(&app.spec_id()).len(), 1);
+    }
 
-        app.start_execution(ExecutionComponent::new(state));
+    #[cfg(unix)]
+    #[test]
+    fn test_watch_mode_runs_again() {
+        use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 
-        // Pressing 'q' while running should NOT close (it's forwarded to PTY)
-        let q = crossterm::event::KeyEvent::new(
-            crossterm::event::KeyCode::Char('q'),
-            crossterm::event::KeyModifiers::NONE,
-        );
-        let result = app.handle_key(q);
-        assert_eq!(result, Action::None);
-        assert!(app.is_executing(), "Should still be executing");
+        let size = ratatui::layout::Size {
+            width: 80,
+            height: 24,
+        };
+        let watch = KeyEvent::new(KeyCode::Char('w'), KeyModifiers::ALT);
+        let mut app = App::new(sleep_spec());
+        app.config.watch.interval = Some(crate::config::HumanDuration(Duration::from_millis(200)));
+        app.arg_values[0].value = "30".to_string();
+        app.spawn_execution(size).unwrap();
+        press_prefixed(&mut app, watch);
+        assert_eq!(app.watching_jobs(), 1);
+        assert!(app.jobs[0].status().ends_with(" · watching"));
+
+        // Not due yet
+        app.poll_watches();
+        assert_eq!(app.execution().unwrap().run_number(), 1);
+
+        // A run still going when the command is due again is stopped first,
+        // and the next one starts once it has exited
+        std::thread::sleep(Duration::from_millis(250));
+        app.poll_watches();
+        assert_eq!(app.execution().unwrap().run_number(), 1);
+        assert!(app.jobs[0].restarting);
+        assert!(wait_until(
+            || app.execution().unwrap().exited(),
+            Duration::from_secs(5)
+        ));
+        app.poll_watches();
+        assert_eq!(app.execution().unwrap().run_number(), 2);
+        assert!(!app.jobs[0].restarting);
+        assert!(!app.execution().unwrap().exited());
+        let entries = app.history.entries_for(&app.spec_id());
+        assert_eq!(entries[0].exit_status.as_deref(), Some("Terminated by SIGTERM"));
+
+        // Toggling again stops watching
+        press_prefixed(&mut app, watch);
+        assert_eq!(app.watching_jobs(), 0);
+        std::thread::sleep(Duration::from_millis(250));
+        app.poll_watches();
+        assert_eq!(app.execution().unwrap().run_number(), 2);
+        app.finish_jobs();
+    }
+
+    #[test]
+    fn test_watch_mode_reports_a_missing_directory() {
+        use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+        let size = ratatui::layout::Size {
+            width: 80,
+            height: 24,
+        };
+        let mut app = App::new(echo_spec());
+        app.config.watch.dir = Some(PathBuf::from("no-such-dir-for-watching"));
+        app.arg_values[0].value = "hello".to_string();
+        app.spawn_execution(size).unwrap();
+        app.handle_key(KeyEvent::new(KeyCode::Char('w'), KeyModifiers::ALT));
+        assert_eq!(app.watching_jobs(), 0);
+        app.finish_jobs();
     }
 
     #[test]
@@ -3956,7 +7285,7 @@ cmd "other" {
         app.resize_pty(40, 120);
 
         // Verify the execution component still exists
-        assert!(app.execution.is_some());
+        assert!(app.execution().is_some());
     }
 
     #[test]
@@ -4911,6 +8240,16 @@ cmd "other" {
         app.handle_key(enter);
         assert!(!app.is_theme_picking());
         assert_eq!(app.theme_name, ThemeName::Nord);
+        assert_eq!(app.config.theme_name(), Some(ThemeName::Nord), "Confirmed theme is saved");
+    }
+
+    #[test]
+    fn test_theme_picker_cancel_does_not_save() {
+        let mut app = App::new(sample_spec());
+        app.open_theme_picker();
+        press(&mut app, crossterm::event::KeyCode::Down);
+        press(&mut app, crossterm::event::KeyCode::Esc);
+        assert_eq!(app.config.theme, None);
     }
 
     #[test]
@@ -5619,4 +8958,227 @@ cmd "other" {
             "Should have selected a value"
         );
     }
+
+    fn set_flag(app: &mut App, name: &str, value: FlagValue) {
+        let values = app.current_flag_values_mut();
+        if let Some((_, v)) = values.iter_mut().find(|(n, _)| n == name) {
+            *v = value;
+        }
+    }
+
+    #[test]
+    fn test_snapshot_restore_roundtrip() {
+        let mut app = App::new(sample_spec());
+        app.navigate_to_command(&["deploy"]);
+        set_flag(&mut app, "tag", FlagValue::String("v2".to_string()));
+        set_flag(&mut app, "yes", FlagValue::Bool(true));
+        app.set_arg_value(0, "prod".to_string());
+        let expected = app.build_command();
+        let snapshot = app.snapshot();
+
+        let mut restored = App::new(sample_spec());
+        let warnings = restored.restore_snapshot(&snapshot);
+        assert!(warnings.is_empty(), "unexpected warnings: {warnings:?}");
+        assert_eq!(restored.command_path, vec!["deploy"]);
+        assert_eq!(restored.command_panel.path(), &["deploy".to_string()]);
+        assert_eq!(restored.build_command(), expected);
+    }
+
+    #[test]
+    fn test_snapshot_serializes_to_json() {
+        let mut app = App::new(sample_spec());
+        app.navigate_to_command(&["run"]);
+        set_flag(&mut app, "jobs", FlagValue::String("8".to_string()));
+        let snapshot = app.snapshot();
+        let json = serde_json::to_string(&snapshot).unwrap();
+        let parsed: BuilderSnapshot = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, snapshot);
+    }
+
+    #[test]
+    fn test_restore_skips_names_missing_from_spec() {
+        let mut app = App::new(sample_spec());
+        app.navigate_to_command(&["deploy"]);
+        set_flag(&mut app, "tag", FlagValue::String("v2".to_string()));
+        let mut snapshot = app.snapshot();
+        let deploy_flags = snapshot.flag_values.get_mut("deploy").unwrap();
+        deploy_flags.push(("removed".to_string(), FlagValue::Bool(true)));
+        deploy_flags.push(("unset-removed".to_string(), FlagValue::Bool(false)));
+        snapshot.arg_values.get_mut("deploy").unwrap().push(SavedArg {
+            name: "gone".to_string(),
+            value: "x".to_string(),
+            values: Vec::new(),
+        });
+
+        let mut restored = App::new(sample_spec());
+        let warnings = restored.restore_snapshot(&snapshot);
+        assert_eq!(warnings, vec!["flag 'removed'", "arg 'gone'"]);
+        assert!(restored.build_command().contains("--tag v2"));
+    }
+
+    #[test]
+    fn test_restore_truncates_unknown_command_path() {
+        let mut app = App::new(sample_spec());
+        let snapshot = BuilderSnapshot {
+            command_path: vec!["config".to_string(), "nope".to_string()],
+            ..Default::default()
+        };
+        let warnings = app.restore_snapshot(&snapshot);
+        assert_eq!(warnings, vec!["command 'nope'"]);
+        assert_eq!(app.command_path, vec!["config"]);
+    }
+
+    #[test]
+    fn test_history_picker_restores_entry() {
+        let mut app = App::new(sample_spec());
+        app.navigate_to_command(&["deploy"]);
+        app.set_arg_value(0, "staging".to_string());
+        let snapshot = app.snapshot();
+        let command = app.build_command();
+        app.history
+            .record(HistoryEntry {
+                spec: app.spec_id(),
+                timestamp: 0,
+                cwd: None,
+                command: command.clone(),
+                argv: app.build_command_parts(),
+                exit_status: Some("Success".to_string()),
+                state: snapshot,
+            })
+            .unwrap();
+
+        app.navigate_to_command(&["init"]);
+        let h = crossterm::event::KeyEvent::new(
+            crossterm::event::KeyCode::Char('H'),
+            crossterm::event::KeyModifiers::SHIFT,
+        );
+        app.handle_key(h);
+        assert!(app.is_history_picking());
+
+        let enter = crossterm::event::KeyEvent::new(
+            crossterm::event::KeyCode::Enter,
+            crossterm::event::KeyModifiers::NONE,
+        );
+        app.handle_key(enter);
+        assert!(!app.is_history_picking());
+        assert_eq!(app.build_command(), command);
+        assert_eq!(app.focus(), Focus::Preview);
+    }
+
+    #[test]
+    fn test_history_picker_without_entries_shows_message() {
+        let mut app = App::new(sample_spec());
+        app.open_history_picker();
+        assert!(!app.is_history_picking());
+        assert!(app.status_message.is_some());
+
+        // Any key clears the message
+        let down = crossterm::event::KeyEvent::new(
+            crossterm::event::KeyCode::Down,
+            crossterm::event::KeyModifiers::NONE,
+        );
+        app.handle_key(down);
+        assert!(app.status_message.is_none());
+    }
+
+    fn press(app: &mut App, code: crossterm::event::KeyCode) -> Action {
+        app.handle_key(crossterm::event::KeyEvent::new(
+            code,
+            crossterm::event::KeyModifiers::NONE,
+        ))
+    }
+
+    /// Press an execution view key after the prefix, as while a command runs.
+    fn press_prefixed(app: &mut App, key: crossterm::event::KeyEvent) -> Action {
+        app.handle_key(crossterm::event::KeyEvent::new(
+            crossterm::event::KeyCode::Char('g'),
+            crossterm::event::KeyModifiers::CONTROL,
+        ));
+        app.handle_key(key)
+    }
+
+    #[test]
+    fn test_save_and_load_favorite() {
+        use crossterm::event::KeyCode;
+
+        let mut app = App::new(sample_spec());
+        app.navigate_to_command(&["deploy"]);
+        set_flag(&mut app, "tag", FlagValue::String("latest".to_string()));
+        set_flag(&mut app, "skip-tests", FlagValue::Bool(true));
+        app.set_arg_value(0, "prod".to_string());
+        app.set_focus(Focus::Preview);
+        let command = app.build_command();
+
+        press(&mut app, KeyCode::Char('S'));
+        assert!(app.is_prompting());
+        for c in "-prod".chars() {
+            press(&mut app, KeyCode::Char(c));
+        }
+        press(&mut app, KeyCode::Enter);
+        assert!(!app.is_prompting());
+        let favorite = app.favorites.find("deploy-prod").expect("favorite saved");
+        assert_eq!(favorite.command, command);
+
+        app.navigate_to_command(&["init"]);
+        app.set_focus(Focus::Preview);
+        press(&mut app, KeyCode::Char('F'));
+        assert!(app.is_favorites_picking());
+        press(&mut app, KeyCode::Enter);
+        assert_eq!(app.build_command(), command);
+    }
+
+    #[test]
+    fn test_favorites_picker_delete() {
+        use crossterm::event::KeyCode;
+
+        let mut app = App::new(sample_spec());
+        for name in ["a", "b"] {
+            app.favorites
+                .save(Favorite {
+                    name: name.to_string(),
+                    command: "mycli".to_string(),
+                    state: app.snapshot(),
+                })
+                .unwrap();
+        }
+        app.set_focus(Focus::Preview);
+        press(&mut app, KeyCode::Char('F'));
+        press(&mut app, KeyCode::Char('d'));
+        let names: Vec<&str> = app.favorites.list().iter().map(|f| f.name.as_str()).collect();
+        assert_eq!(names, vec!["b"]);
+        assert!(app.is_favorites_picking());
+    }
+
+    #[test]
+    fn test_apply_preset() {
+        let mut app = App::new(sample_spec());
+        app.navigate_to_command(&["deploy"]);
+        set_flag(&mut app, "tag", FlagValue::String("v2".to_string()));
+        let mut state = app.snapshot();
+        state
+            .flag_values
+            .get_mut("deploy")
+            .unwrap()
+            .push(("renamed".to_string(), FlagValue::String("x".to_string())));
+        app.favorites
+            .save(Favorite {
+                name: "tagged".to_string(),
+                command: app.build_command(),
+                state,
+            })
+            .unwrap();
+
+        let mut fresh = App::new(sample_spec());
+        fresh.favorites = std::mem::replace(&mut app.favorites, Favorites::in_memory());
+        let err = fresh.apply_preset("missing").unwrap_err().to_string();
+        assert!(err.contains("available: tagged"), "{err}");
+
+        fresh.apply_preset("tagged").unwrap();
+        assert_eq!(fresh.command_path, vec!["deploy"]);
+        assert!(fresh.build_command().contains("--tag v2"));
+        assert_eq!(
+            fresh.status_message.as_deref(),
+            Some("Not restored: flag 'renamed'")
+        );
+    }
 }
@@ -1,8 +1,10 @@
 use std::collections::HashMap;
 
+use usage::spec::arg::SpecDoubleDashChoices;
 use usage::{Spec, SpecFlag};
 
 use crate::app::{ArgValue, FlagValue};
+use crate::quoting::{split_words, Dialect};
 
 /// Resolve the flag spec for a given name, checking the provided flags first,
 /// then falling back to global flags on the root command.
@@ -13,72 +15,6 @@ fn find_flag_spec<'a>(name: &str, flags: &'a [SpecFlag], global_flags: &'a [Spec
         .or_else(|| global_flags.iter().find(|f| f.name == name && f.global))
 }
 
-/// Format a flag and its value as a single display string (for the command preview).
-/// Returns `None` if the flag is unset / default.
-pub fn format_flag_value(
-    name: &str,
-    value: &FlagValue,
-    flags: &[SpecFlag],
-    global_flags: &[SpecFlag],
-) -> Option<String> {
-    let flag = find_flag_spec(name, flags, global_flags)?;
-
-    match value {
-        FlagValue::Bool(true) => {
-            let prefix = if let Some(long) = flag.long.first() {
-                format!("--{long}")
-            } else if let Some(short) = flag.short.first() {
-                format!("-{short}")
-            } else {
-                return None;
-            };
-            Some(prefix)
-        }
-        FlagValue::Bool(false) => None,
-        FlagValue::NegBool(None) => None,
-        FlagValue::NegBool(Some(true)) => {
-            let prefix = if let Some(long) = flag.long.first() {
-                format!("--{long}")
-            } else if let Some(short) = flag.short.first() {
-                format!("-{short}")
-            } else {
-                return None;
-            };
-            Some(prefix)
-        }
-        FlagValue::NegBool(Some(false)) => flag.negate.clone(),
-        FlagValue::Count(0) => None,
-        FlagValue::Count(n) => {
-            if let Some(short) = flag.short.first() {
-                Some(format!("-{}", short.to_string().repeat(*n as usize)))
-            } else if let Some(long) = flag.long.first() {
-                Some(
-                    std::iter::repeat_n(format!("--{long}"), *n as usize)
-                        .collect::<Vec<_>>()
-                        .join(" "),
-                )
-            } else {
-                None
-            }
-        }
-        FlagValue::String(s) if s.is_empty() => None,
-        FlagValue::String(s) => {
-            let prefix = if let Some(long) = flag.long.first() {
-                format!("--{long}")
-            } else if let Some(short) = flag.short.first() {
-                format!("-{short}")
-            } else {
-                return None;
-            };
-            if s.contains(' ') {
-                Some(format!("{prefix} \"{s}\""))
-            } else {
-                Some(format!("{prefix} {s}"))
-            }
-        }
-    }
-}
-
 /// Append flag parts (as separate process arguments) to the parts list.
 pub fn format_flag_parts(
     name: &str,
@@ -134,6 +70,19 @@ pub fn format_flag_parts(
             }
             parts.push(s.clone());
         }
+        FlagValue::Multi(values) => {
+            let name = if let Some(long) = flag.long.first() {
+                format!("--{long}")
+            } else if let Some(short) = flag.short.first() {
+                format!("-{short}")
+            } else {
+                return;
+            };
+            for value in values {
+                parts.push(name.clone());
+                parts.push(value.clone());
+            }
+        }
     }
 }
 
@@ -145,7 +94,8 @@ pub struct LiveArgPreview<'a> {
     pub choice_select_text: &'a str,
     /// Whether inline editing is active.
     pub is_editing: bool,
-    /// Index of the currently selected arg (for inline editing).
+    /// Index of the currently selected arg (for inline editing); one past
+    /// the last arg for the extra args row.
     pub editing_index: usize,
     /// Current text in the inline editor.
     pub editing_text: &'a str,
@@ -166,73 +116,41 @@ fn effective_arg_value<'a>(
     }
 }
 
+/// Resolve the effective extra args text, using live preview state when
+/// the extra args row is being edited.
+fn effective_extra_args<'a>(
+    arg_count: usize,
+    extra_args: &'a str,
+    preview: &'a LiveArgPreview<'a>,
+) -> &'a str {
+    if preview.is_editing && preview.editing_index == arg_count {
+        preview.editing_text
+    } else {
+        extra_args
+    }
+}
+
 /// Build the full command string from the current state (for display).
-/// Values containing spaces are quoted.
+///
+/// This is `build_preview_parts()` quoted for `dialect`, so the displayed
+/// string splits back into exactly the arguments that would be executed.
 pub fn build_command(
     spec: &Spec,
     flag_values: &HashMap<String, Vec<(String, FlagValue)>>,
     command_path: &[String],
     arg_values: &[ArgValue],
+    extra_args: &str,
     preview: &LiveArgPreview,
+    dialect: Dialect,
 ) -> String {
-    let mut parts: Vec<String> = Vec::new();
-
-    let bin = if spec.bin.is_empty() {
-        &spec.name
-    } else {
-        &spec.bin
-    };
-    parts.push(bin.clone());
-
-    // Global flag values from root
-    let root_key = String::new();
-    if let Some(root_flags) = flag_values.get(&root_key) {
-        for (name, value) in root_flags {
-            if let Some(flag_str) = format_flag_value(name, value, &spec.cmd.flags, &spec.cmd.flags)
-            {
-                parts.push(flag_str);
-            }
-        }
-    }
-
-    // Subcommand path with per-level flags
-    let mut cmd = &spec.cmd;
-    for (i, name) in command_path.iter().enumerate() {
-        parts.push(name.clone());
-
-        if let Some(sub) = cmd.find_subcommand(name) {
-            cmd = sub;
-
-            let path_key = command_path[..=i].join(" ");
-            if let Some(level_flags) = flag_values.get(&path_key) {
-                for (fname, fvalue) in level_flags {
-                    let is_global = spec.cmd.flags.iter().any(|f| f.global && f.name == *fname);
-                    if is_global {
-                        continue;
-                    }
-                    if let Some(flag_str) =
-                        format_flag_value(fname, fvalue, &cmd.flags, &spec.cmd.flags)
-                    {
-                        parts.push(flag_str);
-                    }
-                }
-            }
-        }
-    }
-
-    // Positional arg values (with live preview)
-    for (i, arg) in arg_values.iter().enumerate() {
-        let value = effective_arg_value(i, arg, preview);
-        if !value.is_empty() {
-            if value.contains(' ') {
-                parts.push(format!("\"{value}\""));
-            } else {
-                parts.push(value.to_string());
-            }
-        }
-    }
-
-    parts.join(" ")
+    dialect.join(&build_preview_parts(
+        spec,
+        flag_values,
+        command_path,
+        arg_values,
+        extra_args,
+        preview,
+    ))
 }
 
 /// Build the command as a list of separate argument strings (for process execution).
@@ -242,6 +160,37 @@ pub fn build_command_parts(
     flag_values: &HashMap<String, Vec<(String, FlagValue)>>,
     command_path: &[String],
     arg_values: &[ArgValue],
+    extra_args: &str,
+) -> Vec<String> {
+    collect_parts(spec, flag_values, command_path, arg_values, extra_args, None)
+}
+
+/// Like `build_command_parts()`, but with in-progress arg edits applied.
+pub fn build_preview_parts(
+    spec: &Spec,
+    flag_values: &HashMap<String, Vec<(String, FlagValue)>>,
+    command_path: &[String],
+    arg_values: &[ArgValue],
+    extra_args: &str,
+    preview: &LiveArgPreview,
+) -> Vec<String> {
+    collect_parts(
+        spec,
+        flag_values,
+        command_path,
+        arg_values,
+        extra_args,
+        Some(preview),
+    )
+}
+
+fn collect_parts(
+    spec: &Spec,
+    flag_values: &HashMap<String, Vec<(String, FlagValue)>>,
+    command_path: &[String],
+    arg_values: &[ArgValue],
+    extra_args: &str,
+    preview: Option<&LiveArgPreview>,
 ) -> Vec<String> {
     let mut parts: Vec<String> = Vec::new();
 
@@ -284,10 +233,45 @@ pub fn build_command_parts(
     }
 
     // Positional arg values (unquoted — each is a separate process arg)
-    for arg in arg_values {
-        if !arg.value.is_empty() {
-            parts.push(arg.value.clone());
+    let mut after_double_dash = false;
+    for (i, arg) in arg_values.iter().enumerate() {
+        let values: Vec<&str> = if arg.var {
+            arg.values.iter().map(String::as_str).collect()
+        } else {
+            let value = match preview {
+                Some(preview) => effective_arg_value(i, arg, preview),
+                None => &arg.value,
+            };
+            Some(value).filter(|v| !v.is_empty()).into_iter().collect()
+        };
+        if values.is_empty() {
+            continue;
+        }
+        match arg.double_dash {
+            SpecDoubleDashChoices::Required if !after_double_dash => {
+                parts.push("--".to_string());
+                after_double_dash = true;
+            }
+            // Everything after this arg is positional, as if `--` was given
+            SpecDoubleDashChoices::Automatic => after_double_dash = true,
+            _ => {}
+        }
+        parts.extend(values.into_iter().map(String::from));
+    }
+
+    // Extra args, split like a shell would (best effort while a quote is
+    // still open)
+    let extra_args = match preview {
+        Some(preview) => effective_extra_args(arg_values.len(), extra_args, preview),
+        None => extra_args,
+    };
+    let extra = split_words(extra_args)
+        .unwrap_or_else(|_| extra_args.split_whitespace().map(String::from).collect());
+    if !extra.is_empty() {
+        if !after_double_dash {
+            parts.push("--".to_string());
         }
+        parts.extend(extra);
     }
 
     parts
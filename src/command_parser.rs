@@ -0,0 +1,646 @@
+//! Parsing a command line back into builder state.
+//!
+//! The inverse of `command_builder::build_command_parts()`: the words of a
+//! command are matched against the spec, walking into subcommands (by name
+//! or alias), resolving long, short, bundled and negated flags, and
+//! assigning positionals to arguments. The result is a [`BuilderSnapshot`]
+//! for `App::restore_snapshot()`, along with the words that had no place in
+//! it.
+
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+use usage::spec::arg::SpecDoubleDashChoices;
+use usage::{Spec, SpecCommand, SpecFlag};
+
+use crate::app::{collect_visible_flags, App, ArgValue, BuilderSnapshot, FlagValue, SavedArg};
+use crate::env::EnvVar;
+use crate::quoting::{split_words, Dialect};
+
+/// Builder state parsed from a command line.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ParsedCommand {
+    pub state: BuilderSnapshot,
+    /// Words that could not be placed, in the order they were given.
+    pub unplaced: Vec<String>,
+}
+
+/// Parse a command line, split into words like a POSIX shell does.
+pub fn parse_command_line(spec: &Spec, line: &str) -> Result<ParsedCommand, String> {
+    Ok(parse_command_parts(spec, &split_words(line)?))
+}
+
+/// Parse the words of a command. Leading `NAME=value` assignments become
+/// environment overrides, and the spec's binary is skipped when given.
+pub fn parse_command_parts(spec: &Spec, parts: &[String]) -> ParsedCommand {
+    let mut parts = parts;
+    let mut env = Vec::new();
+    while let Some(var) = parts.first().and_then(|p| assignment(p)) {
+        env.push(var);
+        parts = &parts[1..];
+    }
+    let mut parser = Parser::new(spec);
+    parser.parse(skip_bin(spec, parts));
+    parser.finish(env)
+}
+
+/// The program a command runs: its first word after any `NAME=value`
+/// assignments.
+pub fn program(parts: &[String]) -> Option<&str> {
+    parts
+        .iter()
+        .find(|p| assignment(p).is_none())
+        .map(String::as_str)
+}
+
+/// A `NAME=value` word before the command.
+fn assignment(word: &str) -> Option<EnvVar> {
+    let (name, value) = word.split_once('=')?;
+    crate::env::is_valid_name(name).then(|| EnvVar::set(name, value))
+}
+
+/// Skip the spec's binary at the start of `parts`. A path to it counts too,
+/// e.g. `./target/debug/mycli` for `mycli`.
+fn skip_bin<'a>(spec: &Spec, parts: &'a [String]) -> &'a [String] {
+    let bin = if spec.bin.is_empty() {
+        &spec.name
+    } else {
+        &spec.bin
+    };
+    let words: Vec<&str> = bin.split_whitespace().collect();
+    let Some((program, rest)) = words.split_first() else {
+        return parts;
+    };
+    let Some((given, given_rest)) = parts.split_first() else {
+        return parts;
+    };
+    let same_program =
+        given == program || Path::new(given).file_name() == Path::new(program).file_name();
+    if same_program
+        && given_rest.len() >= rest.len()
+        && rest.iter().zip(given_rest).all(|(a, b)| a == b)
+    {
+        &parts[words.len()..]
+    } else {
+        parts
+    }
+}
+
+/// Whether `name` is a global flag, whose value is kept at the root.
+fn is_global(spec: &Spec, name: &str) -> bool {
+    spec.cmd.flags.iter().any(|f| f.global && f.name == name)
+}
+
+struct Parser<'a> {
+    spec: &'a Spec,
+    cmd: &'a SpecCommand,
+    path: Vec<String>,
+    /// Values of the visible flags of each command on the path, keyed by
+    /// command path as in `App::flag_values`.
+    flag_values: HashMap<String, Vec<(String, FlagValue)>>,
+    /// Repeatable flags given so far, whose defaults are replaced by the
+    /// first value.
+    given: HashSet<(String, String)>,
+    /// Arg values of the commands on the path left behind.
+    arg_values_by_path: HashMap<String, Vec<ArgValue>>,
+    /// Arg values of the current command.
+    arg_values: Vec<ArgValue>,
+    /// Index of the arg the next positional goes to.
+    arg_index: usize,
+    /// Whether the variadic arg at `arg_index` has been given a value.
+    var_started: bool,
+    /// Whether a positional was given, after which words are no longer
+    /// subcommands.
+    positional_given: bool,
+    /// Whether `--` was given or an arg asked to behave as if it had been:
+    /// every word from here on is positional.
+    only_positionals: bool,
+    /// Whether the built command will have a `--` before the next arg, so a
+    /// word starting with `-` can go there.
+    built_double_dash: bool,
+    /// Words for the extra args row.
+    extra: Vec<String>,
+    unplaced: Vec<String>,
+}
+
+impl<'a> Parser<'a> {
+    fn new(spec: &'a Spec) -> Self {
+        let mut parser = Self {
+            spec,
+            cmd: &spec.cmd,
+            path: Vec::new(),
+            flag_values: HashMap::new(),
+            given: HashSet::new(),
+            arg_values_by_path: HashMap::new(),
+            arg_values: Vec::new(),
+            arg_index: 0,
+            var_started: false,
+            positional_given: false,
+            only_positionals: false,
+            built_double_dash: false,
+            extra: Vec::new(),
+            unplaced: Vec::new(),
+        };
+        parser.start(&spec.cmd);
+        parser
+    }
+
+    fn parse(&mut self, words: &[String]) {
+        let mut words = words.iter();
+        while let Some(word) = words.next() {
+            if self.only_positionals {
+                self.positional(word);
+            } else if word == "--" {
+                self.only_positionals = true;
+            } else if let Some(flag) = self.find_flag(|f| f.negate.as_deref() == Some(word)) {
+                if !self.switch(flag, false) {
+                    self.unplaced.push(word.clone());
+                }
+            } else if let Some(long) = word.strip_prefix("--") {
+                self.long_flag(word, long, &mut words);
+            } else if self.is_short_flags(word) {
+                self.short_flags(word, &mut words);
+            } else if let Some(sub) = self
+                .cmd
+                .find_subcommand(word)
+                .filter(|_| !self.positional_given)
+            {
+                self.enter(sub);
+            } else {
+                self.positional(word);
+            }
+        }
+    }
+
+    /// Move into a subcommand.
+    fn enter(&mut self, cmd: &'a SpecCommand) {
+        self.arg_values_by_path
+            .insert(self.path.join(" "), std::mem::take(&mut self.arg_values));
+        self.path.push(cmd.name.clone());
+        self.start(cmd);
+    }
+
+    /// Start taking flags and args for `cmd`, the last one on the path.
+    fn start(&mut self, cmd: &'a SpecCommand) {
+        self.cmd = cmd;
+        self.flag_values.insert(
+            self.path.join(" "),
+            collect_visible_flags(cmd, self.spec)
+                .into_iter()
+                .map(|f| (f.name.clone(), App::default_flag_value(f)))
+                .collect(),
+        );
+        self.arg_values = App::default_arg_values_for_command(cmd);
+        self.arg_index = 0;
+        self.var_started = false;
+    }
+
+    fn find_flag(&self, matches: impl Fn(&SpecFlag) -> bool) -> Option<&'a SpecFlag> {
+        collect_visible_flags(self.cmd, self.spec)
+            .into_iter()
+            .find(|f| matches(f))
+    }
+
+    /// Whether `word` is one or more short flags. `-` alone and negative
+    /// numbers are positionals, unless a flag uses the digit.
+    fn is_short_flags(&self, word: &str) -> bool {
+        let Some(rest) = word.strip_prefix('-') else {
+            return false;
+        };
+        match rest.chars().next() {
+            None => false,
+            Some(c) if c.is_ascii_digit() => self.find_flag(|f| f.short.contains(&c)).is_some(),
+            Some(_) => true,
+        }
+    }
+
+    /// Key of the command path a flag's value is kept at.
+    fn flag_key(&self, flag: &SpecFlag) -> String {
+        if is_global(self.spec, &flag.name) {
+            String::new()
+        } else {
+            self.path.join(" ")
+        }
+    }
+
+    fn slot(&mut self, flag: &SpecFlag) -> Option<&mut FlagValue> {
+        let key = self.flag_key(flag);
+        self.flag_values
+            .get_mut(&key)?
+            .iter_mut()
+            .find(|(name, _)| *name == flag.name)
+            .map(|(_, value)| value)
+    }
+
+    /// Set a flag given without a value, or its negation. Returns false when
+    /// the flag needs a value.
+    fn switch(&mut self, flag: &SpecFlag, on: bool) -> bool {
+        match (self.slot(flag), on) {
+            (Some(FlagValue::Bool(b)), true) => *b = true,
+            (Some(FlagValue::NegBool(state)), on) => *state = Some(on),
+            (Some(FlagValue::Count(n)), true) => *n += 1,
+            _ => return false,
+        }
+        true
+    }
+
+    /// Set a flag's value. Returns false when the flag takes no value.
+    fn set_value(&mut self, flag: &SpecFlag, value: &str) -> bool {
+        let first = self.given.insert((self.flag_key(flag), flag.name.clone()));
+        match self.slot(flag) {
+            Some(FlagValue::String(s)) => *s = value.to_string(),
+            Some(FlagValue::Multi(values)) => {
+                if first {
+                    values.clear();
+                }
+                values.push(value.to_string());
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    /// `--name`, `--name value` or `--name=value`.
+    fn long_flag<'w>(
+        &mut self,
+        word: &str,
+        long: &str,
+        words: &mut impl Iterator<Item = &'w String>,
+    ) {
+        let (name, inline) = match long.split_once('=') {
+            Some((name, value)) => (name, Some(value)),
+            None => (long, None),
+        };
+        let Some(flag) = self.find_flag(|f| f.long.iter().any(|l| l == name)) else {
+            self.unplaced.push(word.to_string());
+            return;
+        };
+        let placed = match (flag.arg.is_some(), inline) {
+            (true, Some(value)) => self.set_value(flag, value),
+            (true, None) => match words.next() {
+                Some(value) => self.set_value(flag, value),
+                None => false,
+            },
+            (false, None) => self.switch(flag, true),
+            (false, Some(_)) => false,
+        };
+        if !placed {
+            self.unplaced.push(word.to_string());
+        }
+    }
+
+    /// `-a`, bundled `-abc`, and a value after the last one that takes it:
+    /// `-ofile`, `-o=file` or `-o file`. Nothing is set unless every flag in
+    /// the bundle is known.
+    fn short_flags<'w>(&mut self, word: &str, words: &mut impl Iterator<Item = &'w String>) {
+        let mut flags: Vec<(&'a SpecFlag, Option<String>)> = Vec::new();
+        for (i, c) in word.char_indices().skip(1) {
+            let Some(flag) = self.find_flag(|f| f.short.contains(&c)) else {
+                self.unplaced.push(word.to_string());
+                return;
+            };
+            if flag.arg.is_none() {
+                flags.push((flag, None));
+                continue;
+            }
+            let rest = &word[i + c.len_utf8()..];
+            let value = match rest.strip_prefix('=').unwrap_or(rest) {
+                "" => words.next().cloned(),
+                rest => Some(rest.to_string()),
+            };
+            let Some(value) = value else {
+                self.unplaced.push(word.to_string());
+                return;
+            };
+            flags.push((flag, Some(value)));
+            break;
+        }
+        for (flag, value) in flags {
+            match value {
+                Some(value) => self.set_value(flag, &value),
+                None => self.switch(flag, true),
+            };
+        }
+    }
+
+    /// Assign a word to the next arg, the extra args, or nowhere.
+    fn positional(&mut self, word: &str) {
+        self.positional_given = true;
+        if !self.extra.is_empty() {
+            self.extra.push(word.to_string());
+            return;
+        }
+        let Some(arg) = self.arg_values.get_mut(self.arg_index) else {
+            if self.only_positionals {
+                self.extra.push(word.to_string());
+            } else {
+                self.unplaced.push(word.to_string());
+            }
+            return;
+        };
+        // Without a `--` in the built command, a word like a flag after the
+        // user's `--` would be read as one
+        let built_double_dash = self.built_double_dash
+            || matches!(
+                arg.double_dash,
+                SpecDoubleDashChoices::Required | SpecDoubleDashChoices::Automatic
+            );
+        if self.only_positionals && word.starts_with('-') && !built_double_dash {
+            self.extra.push(word.to_string());
+            return;
+        }
+        self.built_double_dash = built_double_dash;
+        if arg.double_dash == SpecDoubleDashChoices::Automatic {
+            self.only_positionals = true;
+        }
+        let next = if arg.var {
+            if !self.var_started {
+                arg.values.clear();
+                self.var_started = true;
+            }
+            arg.values.push(word.to_string());
+            arg.var_max.is_some_and(|max| arg.values.len() >= max)
+        } else {
+            arg.value = word.to_string();
+            true
+        };
+        if next {
+            self.arg_index += 1;
+            self.var_started = false;
+        }
+    }
+
+    fn finish(mut self, env: Vec<EnvVar>) -> ParsedCommand {
+        let key = self.path.join(" ");
+        self.arg_values_by_path
+            .insert(key.clone(), std::mem::take(&mut self.arg_values));
+
+        // The builder keeps global flags at every level too
+        let globals: Vec<(String, FlagValue)> = self
+            .flag_values
+            .get("")
+            .into_iter()
+            .flatten()
+            .filter(|(name, _)| is_global(self.spec, name))
+            .cloned()
+            .collect();
+        for (_, values) in self.flag_values.iter_mut().filter(|(k, _)| !k.is_empty()) {
+            for (name, value) in values.iter_mut() {
+                if let Some((_, global)) = globals.iter().find(|(n, _)| n == name) {
+                    *value = global.clone();
+                }
+            }
+        }
+
+        let mut state = BuilderSnapshot {
+            command_path: self.path,
+            flag_values: self.flag_values,
+            arg_values: self
+                .arg_values_by_path
+                .into_iter()
+                .map(|(key, args)| {
+                    let saved = args
+                        .into_iter()
+                        .map(|a| SavedArg {
+                            name: a.name,
+                            value: a.value,
+                            values: a.values,
+                        })
+                        .collect();
+                    (key, saved)
+                })
+                .collect(),
+            ..Default::default()
+        };
+        if !self.extra.is_empty() {
+            state
+                .extra_args
+                .insert(key.clone(), Dialect::Posix.join(&self.extra));
+        }
+        if !env.is_empty() {
+            state.env.insert(key, env);
+        }
+        ParsedCommand {
+            state,
+            unplaced: self.unplaced,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_spec() -> Spec {
+        include_str!("../fixtures/sample.usage.kdl")
+            .parse::<Spec>()
+            .expect("Failed to parse sample spec")
+    }
+
+    fn parse(line: &str) -> ParsedCommand {
+        parse_command_line(&sample_spec(), line).unwrap()
+    }
+
+    fn flag<'s>(parsed: &'s ParsedCommand, key: &str, name: &str) -> &'s FlagValue {
+        parsed.state.flag_values[key]
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, v)| v)
+            .unwrap()
+    }
+
+    fn args(parsed: &ParsedCommand, key: &str) -> Vec<(String, String, Vec<String>)> {
+        parsed.state.arg_values[key]
+            .iter()
+            .map(|a| (a.name.clone(), a.value.clone(), a.values.clone()))
+            .collect()
+    }
+
+    #[test]
+    fn test_subcommands_and_aliases() {
+        let parsed = parse("mycli config add theme dark");
+        assert_eq!(parsed.state.command_path, ["config", "set"]);
+        assert_eq!(
+            args(&parsed, "config set"),
+            [
+                ("key".to_string(), "theme".to_string(), vec![]),
+                ("value".to_string(), "dark".to_string(), vec![]),
+            ]
+        );
+        assert!(parsed.unplaced.is_empty());
+
+        // The binary is optional, and matched by file name
+        assert_eq!(
+            parse("plugin i auth").state.command_path,
+            ["plugin", "install"]
+        );
+        assert_eq!(
+            parse("./bin/mycli deploy prod").state.command_path,
+            ["deploy"]
+        );
+    }
+
+    #[test]
+    fn test_flag_forms() {
+        let parsed = parse("mycli -vv run --env=prod -j 8 --dry-run --no-color -w build");
+        assert_eq!(parsed.state.command_path, ["run"]);
+        assert_eq!(flag(&parsed, "", "verbose"), &FlagValue::Count(2));
+        assert_eq!(
+            flag(&parsed, "run", "verbose"),
+            &FlagValue::Count(2),
+            "global flags are kept at every level"
+        );
+        assert_eq!(
+            flag(&parsed, "run", "env"),
+            &FlagValue::String("prod".into())
+        );
+        assert_eq!(flag(&parsed, "run", "jobs"), &FlagValue::String("8".into()));
+        assert_eq!(flag(&parsed, "run", "dry-run"), &FlagValue::Bool(true));
+        assert_eq!(
+            flag(&parsed, "run", "color"),
+            &FlagValue::NegBool(Some(false))
+        );
+        assert_eq!(flag(&parsed, "run", "watch"), &FlagValue::Bool(true));
+        assert_eq!(args(&parsed, "run")[0].1, "build");
+        assert!(parsed.unplaced.is_empty());
+
+        // Bundled short flags, with a value after the last one
+        let parsed = parse("mycli run -wvjprod x");
+        assert_eq!(
+            flag(&parsed, "run", "jobs"),
+            &FlagValue::String("prod".into())
+        );
+        assert_eq!(flag(&parsed, "run", "watch"), &FlagValue::Bool(true));
+        assert_eq!(flag(&parsed, "", "verbose"), &FlagValue::Count(1));
+        assert_eq!(args(&parsed, "run")[0].1, "x");
+    }
+
+    #[test]
+    fn test_positionals() {
+        let parsed = parse("mycli run test a 'b c' -- --verbose -x");
+        assert_eq!(
+            args(&parsed, "run"),
+            [
+                ("task".to_string(), "test".to_string(), vec![]),
+                (
+                    "args".to_string(),
+                    String::new(),
+                    vec!["a".to_string(), "b c".to_string()]
+                ),
+            ]
+        );
+        assert_eq!(
+            parsed.state.extra_args["run"], "--verbose -x",
+            "flags after `--` go where the built command keeps them positional"
+        );
+
+        // A word that can't be a subcommand once a positional was given
+        let parsed = parse("mycli plugin install auth list");
+        assert_eq!(parsed.state.command_path, ["plugin", "install"]);
+        assert_eq!(args(&parsed, "plugin install")[1].1, "list");
+
+        // Negative numbers are values
+        assert_eq!(args(&parse("mycli init -1"), "init")[0].1, "-1");
+    }
+
+    #[test]
+    fn test_unplaced_words() {
+        let parsed = parse("mycli deploy --bogus prod --tag v1 -yz extra --rollback=yes --tag");
+        assert_eq!(parsed.state.command_path, ["deploy"]);
+        assert_eq!(args(&parsed, "deploy")[0].1, "prod");
+        assert_eq!(
+            flag(&parsed, "deploy", "tag"),
+            &FlagValue::String("v1".into())
+        );
+        assert_eq!(
+            flag(&parsed, "deploy", "yes"),
+            &FlagValue::Bool(false),
+            "a bundle with an unknown flag is not applied"
+        );
+        assert_eq!(
+            parsed.unplaced,
+            ["--bogus", "-yz", "extra", "--rollback=yes", "--tag"]
+        );
+
+        // A subcommand's flags don't apply to its parent
+        let parsed = parse("mycli --json config get key");
+        assert_eq!(parsed.unplaced, ["--json"]);
+    }
+
+    #[test]
+    fn test_env_assignments() {
+        let parsed = parse("RUST_LOG=debug A=1 mycli init demo");
+        assert_eq!(
+            parsed.state.env["init"],
+            [EnvVar::set("RUST_LOG", "debug"), EnvVar::set("A", "1")]
+        );
+        assert_eq!(args(&parsed, "init")[0].1, "demo");
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let spec = sample_spec();
+        let mut flag_values = HashMap::new();
+        flag_values.insert(
+            String::new(),
+            vec![
+                ("verbose".to_string(), FlagValue::Count(3)),
+                ("user".to_string(), FlagValue::String("me".into())),
+            ],
+        );
+        flag_values.insert(
+            "run".to_string(),
+            vec![
+                ("env".to_string(), FlagValue::String("dev".into())),
+                ("jobs".to_string(), FlagValue::String("4".into())),
+                ("color".to_string(), FlagValue::NegBool(Some(false))),
+            ],
+        );
+        let mut arg_values =
+            App::default_arg_values_for_command(spec.cmd.find_subcommand("run").unwrap());
+        arg_values[0].value = "lint".to_string();
+        arg_values[1].values = vec!["src".to_string(), "a b".to_string()];
+        let path = vec!["run".to_string()];
+        let parts = crate::command_builder::build_command_parts(
+            &spec,
+            &flag_values,
+            &path,
+            &arg_values,
+            "--quiet",
+        );
+
+        let parsed = parse_command_parts(&spec, &parts);
+        assert!(parsed.unplaced.is_empty(), "{:?}", parsed.unplaced);
+        let saved_args: Vec<ArgValue> = parsed.state.arg_values["run"]
+            .iter()
+            .zip(App::default_arg_values_for_command(
+                spec.cmd.find_subcommand("run").unwrap(),
+            ))
+            .map(|(saved, mut arg)| {
+                arg.value = saved.value.clone();
+                arg.values = saved.values.clone();
+                arg
+            })
+            .collect();
+        let rebuilt = crate::command_builder::build_command_parts(
+            &spec,
+            &parsed.state.flag_values,
+            &parsed.state.command_path,
+            &saved_args,
+            parsed
+                .state
+                .extra_args
+                .get("run")
+                .map_or("", String::as_str),
+        );
+        assert_eq!(rebuilt, parts);
+    }
+
+    #[test]
+    fn test_invalid_quoting() {
+        assert_eq!(
+            parse_command_line(&sample_spec(), "mycli init 'open").unwrap_err(),
+            "unterminated single quote"
+        );
+    }
+}
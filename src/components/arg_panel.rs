@@ -15,12 +15,15 @@ use ratatui::{
 
 use crate::app::ArgValue;
 use crate::theme::UiColors;
+use crate::validation::{Diagnostic, Target};
 
-use super::list_panel_base::{ChoiceEvent, EditEvent, FocusLostEvent, ListPanelBase, MouseResult};
+use super::list_panel_base::{
+    ChoiceEvent, EditEvent, FocusLostEvent, ListEvent, ListPanelBase, MouseResult,
+};
 use super::filterable::{FilterableItem, Filterable};
 use super::{
-    build_help_line, panel_block, panel_title, push_edit_cursor, push_highlighted_name,
-    push_selection_cursor, render_help_overlays, render_panel_scrollbar, selection_bg, Component,
+    build_help_line, panel_block, panel_title, push_diagnostics, push_edit_cursor,
+    push_highlighted_name, push_selection_cursor, render_help_overlays, render_panel_scrollbar, selection_bg, Component,
     EventResult, ItemContext, OverlayRequest, PanelState,
 };
 
@@ -42,6 +45,8 @@ pub enum ArgPanelAction {
     ValueChanged { index: usize, value: String },
     /// Inline edit finished (Enter/Esc committed the value).
     EditFinished { index: usize, value: String },
+    /// List editor changed the values of a variadic arg at the given index.
+    ListChanged { index: usize, values: Vec<String> },
 }
 
 /// Panel-owned interpretation of Enter on an argument row.
@@ -59,8 +64,20 @@ pub enum ArgPanelEnterRequest {
         current_value: String,
         value_column: u16,
     },
+    ListEdit {
+        index: usize,
+        values: Vec<String>,
+        max: Option<usize>,
+        value_column: u16,
+    },
+    /// Edit the extra args row (after the last arg).
+    EditExtraArgs,
 }
 
+/// Label of the row for extra arguments passed after `--`.
+const EXTRA_ARGS_LABEL: &str = "-- [extra...]";
+const EXTRA_ARGS_HELP: &str = "Passed after --";
+
 // ── ArgPanelComponent ───────────────────────────────────────────────
 
 /// An argument list panel that owns its navigation state and handles events.
@@ -124,6 +141,11 @@ impl ArgPanelComponent {
                 name_texts: vec![a.name.clone()],
                 help: a.help.clone(),
             })
+            .chain(std::iter::once(FilterableItem {
+                key: "--".to_string(),
+                name_texts: vec![EXTRA_ARGS_LABEL.to_string()],
+                help: Some(EXTRA_ARGS_HELP.to_string()),
+            }))
             .collect();
         self.base.set_filterable_items(items);
     }
@@ -133,6 +155,7 @@ impl ArgPanelComponent {
             .iter()
             .enumerate()
             .filter_map(|(index, _)| Self::build_enter_request(index, args))
+            .chain(std::iter::once(ArgPanelEnterRequest::EditExtraArgs))
             .collect();
     }
 
@@ -236,12 +259,48 @@ impl ArgPanelComponent {
         self.base.editing_text()
     }
 
+    // ── List editor (delegated) ─────────────────────────────────────
+
+    pub fn is_list_editing(&self) -> bool {
+        self.base.is_list_editing()
+    }
+
+    pub fn is_list_entry_editing(&self) -> bool {
+        self.base.is_list_entry_editing()
+    }
+
+    pub fn open_list_editor(
+        &mut self,
+        index: usize,
+        values: Vec<String>,
+        max: Option<usize>,
+        value_column: u16,
+    ) {
+        self.base.open_list_editor(index, values, max, value_column);
+    }
+
+    pub fn handle_list_editor_click(
+        &mut self,
+        col: u16,
+        row: u16,
+        overlay_rect: Option<Rect>,
+    ) -> EventResult<ArgPanelAction> {
+        Self::map_list_event(self.base.handle_list_editor_click(col, row, overlay_rect))
+    }
+
     fn build_enter_request(index: usize, args: &[ArgValue]) -> Option<ArgPanelEnterRequest> {
         let arg = args.get(index)?;
         let current_value = arg.value.clone();
         let value_column = Self::value_column_for_arg(arg);
 
-        if !arg.choices.is_empty() {
+        if arg.var {
+            Some(ArgPanelEnterRequest::ListEdit {
+                index,
+                values: arg.values.clone(),
+                max: arg.var_max,
+                value_column,
+            })
+        } else if !arg.choices.is_empty() {
             Some(ArgPanelEnterRequest::ChoiceSelect {
                 index,
                 choices: arg.choices.clone(),
@@ -265,7 +324,7 @@ impl ArgPanelComponent {
     fn value_column_for_arg(arg: &ArgValue) -> u16 {
         // Layout: border(1) + cursor "▶ "(2) + indicator "● "(2) + "<name>"(name+2) + " = "(3)
         let indicator_width = 2usize; // "● " or "○ "
-        let arg_display_len = arg.name.chars().count() + 2; // includes <> or []
+        let arg_display_len = arg_display_string(arg).chars().count();
         (1 + 2 + indicator_width + arg_display_len + 3) as u16
     }
 
@@ -295,6 +354,15 @@ impl ArgPanelComponent {
         }
     }
 
+    fn map_list_event(event: ListEvent) -> EventResult<ArgPanelAction> {
+        match event {
+            ListEvent::Changed { index, values } => {
+                EventResult::Action(ArgPanelAction::ListChanged { index, values })
+            }
+            ListEvent::Consumed => EventResult::Consumed,
+        }
+    }
+
     // ── Rendering ───────────────────────────────────────────────────
 
     pub fn render_with_data(
@@ -331,6 +399,8 @@ impl ArgPanelComponent {
 
         let panel = ArgPanel {
             arg_values: data.arg_values,
+            extra_args: data.extra_args,
+            diagnostics: data.diagnostics,
             arg_index: self.base.list_state.selected_index,
             scroll_offset: self.base.list_state.scroll as usize,
             hovered_index: self.base.hovered_index,
@@ -343,22 +413,14 @@ impl ArgPanelComponent {
         };
         panel.render(area, buf);
 
-        // Update choice_select anchor now that we know the panel area
-        if self.base.choice_select.is_open() {
-            if let Some(cs_idx) = self.base.choice_select_index {
-                let scroll = self.base.list_state.scroll as usize;
-                let row_in_viewport = cs_idx.saturating_sub(scroll);
-                let anchor_y = area.y + 1 + row_in_viewport as u16;
-                let anchor_x = area.x + self.base.value_column.saturating_sub(1);
-                let anchor = Rect::new(
-                    anchor_x,
-                    anchor_y,
-                    area.width
-                        .saturating_sub(self.base.value_column.saturating_sub(1)),
-                    1,
-                );
-                self.base.choice_select.set_anchor(anchor);
-            }
+        // Update overlay anchors now that we know the panel area
+        if let Some(cs_idx) = self.base.choice_select_index {
+            let anchor = self.base.value_anchor(area, cs_idx);
+            self.base.choice_select.set_anchor(anchor);
+        }
+        if let Some(list_idx) = self.base.list_editor_index {
+            let anchor = self.base.value_anchor(area, list_idx);
+            self.base.list_editor.set_anchor(anchor);
         }
     }
 
@@ -392,12 +454,20 @@ impl Component for ArgPanelComponent {
             FocusLostEvent::EditFinished { index, value } => {
                 EventResult::Action(ArgPanelAction::EditFinished { index, value })
             }
+            FocusLostEvent::ListChanged { index, values } => {
+                EventResult::Action(ArgPanelAction::ListChanged { index, values })
+            }
             FocusLostEvent::Consumed => EventResult::Consumed,
             FocusLostEvent::NotHandled => EventResult::NotHandled,
         }
     }
 
     fn handle_key(&mut self, key: KeyEvent) -> EventResult<ArgPanelAction> {
+        // Delegate to the list editor when open
+        if self.base.is_list_editing() {
+            return Self::map_list_event(self.base.handle_list_editor_key(key));
+        }
+
         // Delegate to choice select when open
         if self.base.is_choosing() {
             match self.base.handle_choice_key(key) {
@@ -489,6 +559,10 @@ impl Filterable for ArgPanelComponent {
 /// Provided by the parent since arg values live in App.
 pub struct ArgRenderData<'a> {
     pub arg_values: &'a [ArgValue],
+    /// Extra arguments for after `--`, as typed.
+    pub extra_args: &'a str,
+    /// Validation problems of the current command, shown next to their args.
+    pub diagnostics: &'a [Diagnostic],
 }
 
 // ── Score computation ───────────────────────────────────────────────
@@ -498,6 +572,8 @@ pub struct ArgRenderData<'a> {
 /// Props for the argument panel rendering.
 struct ArgPanel<'a> {
     arg_values: &'a [ArgValue],
+    extra_args: &'a str,
+    diagnostics: &'a [Diagnostic],
     arg_index: usize,
     scroll_offset: usize,
     hovered_index: Option<usize>,
@@ -518,7 +594,7 @@ impl Widget for ArgPanel<'_> {
         let block = panel_block(title, ps);
 
         let mut help_entries: Vec<(usize, Line<'static>)> = Vec::new();
-        let items: Vec<ListItem> = self
+        let mut items: Vec<ListItem> = self
             .arg_values
             .iter()
             .enumerate()
@@ -541,8 +617,7 @@ impl Widget for ArgPanel<'_> {
                 }
 
                 // Arg name with highlighting
-                let bracket = if arg_val.required { "<>" } else { "[]" };
-                let arg_display = format!("{}{}{}", &bracket[..1], arg_val.name, &bracket[1..]);
+                let arg_display = arg_display_string(arg_val);
 
                 push_highlighted_name(&mut spans, &arg_display, colors.arg, &ctx, ps, colors);
 
@@ -551,7 +626,9 @@ impl Widget for ArgPanel<'_> {
 
                 let is_choice_selecting = self.choice_select_arg_index == Some(i);
 
-                if is_choice_selecting || is_editing {
+                if arg_val.var {
+                    render_var_values(&mut spans, arg_val, colors);
+                } else if is_choice_selecting || is_editing {
                     push_edit_cursor(
                         &mut spans,
                         &self.edit_before_cursor,
@@ -580,7 +657,7 @@ impl Widget for ArgPanel<'_> {
 
                 // Show choices if arg has them and we're not editing
                 if !arg_val.choices.is_empty()
-                    && !arg_val.value.is_empty()
+                    && arg_val.is_set()
                     && !is_editing
                     && !is_choice_selecting
                 {
@@ -590,6 +667,15 @@ impl Widget for ArgPanel<'_> {
                     ));
                 }
 
+                if !is_editing && !is_choice_selecting {
+                    let messages = self
+                        .diagnostics
+                        .iter()
+                        .filter(|d| d.target == Target::Arg(i))
+                        .map(|d| d.message.as_str());
+                    push_diagnostics(&mut spans, messages, colors);
+                }
+
                 // Collect help text for overlay
                 if let Some(ref help) = arg_val.help {
                     if !help.is_empty() {
@@ -606,8 +692,9 @@ impl Widget for ArgPanel<'_> {
                 item
             })
             .collect();
+        items.push(self.extra_args_item(&mut help_entries));
 
-        let total_args = self.arg_values.len();
+        let total_args = items.len();
         let mut state = ListState::default()
             .with_selected(if ps.is_focused {
                 Some(self.arg_index)
@@ -625,3 +712,79 @@ impl Widget for ArgPanel<'_> {
         render_help_overlays(buf, &help_entries, self.scroll_offset, inner);
     }
 }
+
+impl ArgPanel<'_> {
+    /// The row after the args, holding the extra args typed for after `--`.
+    fn extra_args_item(&self, help_entries: &mut Vec<(usize, Line<'static>)>) -> ListItem<'static> {
+        let ps = self.panel_state;
+        let colors = self.colors;
+        let i = self.arg_values.len();
+        let is_selected = ps.is_focused && i == self.arg_index;
+        let is_hovered = self.hovered_index == Some(i) && !is_selected;
+        let is_editing = is_selected && self.editing;
+
+        let ctx = ItemContext::new("--", is_selected, ps);
+
+        let mut spans = Vec::new();
+        push_selection_cursor(&mut spans, is_selected, colors);
+        spans.push(Span::styled("○ ", Style::default().fg(colors.help)));
+        push_highlighted_name(&mut spans, EXTRA_ARGS_LABEL, colors.arg, &ctx, ps, colors);
+        spans.push(Span::styled(" = ", Style::default().fg(colors.help)));
+
+        if is_editing {
+            push_edit_cursor(
+                &mut spans,
+                &self.edit_before_cursor,
+                &self.edit_after_cursor,
+                colors,
+            );
+        } else if self.extra_args.is_empty() {
+            spans.push(Span::styled("(empty)", Style::default().fg(colors.default_val)));
+        } else {
+            spans.push(Span::styled(
+                self.extra_args.to_string(),
+                Style::default().fg(colors.value),
+            ));
+            let messages = self
+                .diagnostics
+                .iter()
+                .filter(|d| d.target == Target::ExtraArgs)
+                .map(|d| d.message.as_str());
+            push_diagnostics(&mut spans, messages, colors);
+        }
+
+        help_entries.push((i, build_help_line(EXTRA_ARGS_HELP, &ctx, ps, colors)));
+
+        let mut item = ListItem::new(Line::from(spans));
+        if is_selected {
+            item = item.style(selection_bg(is_editing, colors));
+        } else if is_hovered {
+            item = item.style(Style::default().bg(colors.hover_bg));
+        }
+        item
+    }
+}
+
+/// Build the arg display string: `<name>` when required, `[name]` when
+/// optional, with `...` appended for variadic args.
+fn arg_display_string(arg: &ArgValue) -> String {
+    let bracket = if arg.required { "<>" } else { "[]" };
+    let dots = if arg.var { "..." } else { "" };
+    format!("{}{}{}{}", &bracket[..1], arg.name, dots, &bracket[1..])
+}
+
+/// Render the values of a variadic arg.
+fn render_var_values(spans: &mut Vec<Span<'static>>, arg: &ArgValue, colors: &UiColors) {
+    if arg.values.is_empty() {
+        spans.push(Span::styled(
+            "(empty)",
+            Style::default().fg(colors.default_val),
+        ));
+    }
+    for (i, value) in arg.values.iter().enumerate() {
+        if i > 0 {
+            spans.push(Span::styled(", ", Style::default().fg(colors.help)));
+        }
+        spans.push(Span::styled(value.clone(), Style::default().fg(colors.value)));
+    }
+}
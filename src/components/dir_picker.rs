@@ -0,0 +1,751 @@
+//! Working directory picker overlay component.
+//!
+//! Chooses the directory the next run happens in, in a box centered below
+//! the command preview. The Browse tab walks the file system starting from
+//! the current choice; the Recent tab lists directories commands ran in
+//! before. A path can also be typed, with `~` for the home directory.
+
+use std::path::{Path, PathBuf};
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseEvent};
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph, Widget},
+};
+use ratatui_interact::components::InputState;
+
+use super::{
+    push_edit_cursor, scroll_offset, Component, EventResult, OverlayContent, OverlayRequest,
+};
+use crate::paths;
+use crate::theme::UiColors;
+
+/// Actions emitted by the directory picker for the parent to process.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DirPickerAction {
+    /// A directory was chosen to run commands in.
+    Chosen(PathBuf),
+    /// Go back to running commands in TuiSage's own working directory.
+    Reset,
+    /// Resolving relative paths in arguments was turned on or off.
+    ResolvePaths(bool),
+    /// The picker was closed without choosing.
+    Cancelled,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Tab {
+    Browse,
+    Recent,
+}
+
+/// A row of the Browse tab.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Entry {
+    /// The directory being browsed, chosen with Enter.
+    Current,
+    Parent,
+    Child(String),
+}
+
+/// Self-contained working directory picker overlay.
+pub struct DirPickerComponent {
+    state: Option<DirPickerInner>,
+    /// The viewport area, set by the UI coordinator before collecting overlays.
+    viewport: Rect,
+}
+
+struct DirPickerInner {
+    tab: Tab,
+    /// Directory being browsed.
+    dir: PathBuf,
+    /// Names of its subdirectories, sorted, without hidden ones.
+    children: Vec<String>,
+    recent: Vec<PathBuf>,
+    selected_index: usize,
+    /// Inline path input while a directory is being typed.
+    input: Option<InputState>,
+    /// Why the last directory could not be opened.
+    error: Option<String>,
+    resolve_paths: bool,
+}
+
+impl DirPickerInner {
+    fn entries(&self) -> Vec<Entry> {
+        let mut entries = vec![Entry::Current];
+        if self.dir.parent().is_some() {
+            entries.push(Entry::Parent);
+        }
+        entries.extend(self.children.iter().cloned().map(Entry::Child));
+        entries
+    }
+
+    fn len(&self) -> usize {
+        match self.tab {
+            Tab::Browse => self.entries().len(),
+            Tab::Recent => self.recent.len(),
+        }
+    }
+
+    /// The directory in the selected row.
+    fn selected_dir(&self) -> Option<PathBuf> {
+        match self.tab {
+            Tab::Browse => match self.entries().get(self.selected_index)? {
+                Entry::Current => Some(self.dir.clone()),
+                Entry::Parent => self.dir.parent().map(Path::to_path_buf),
+                Entry::Child(name) => Some(self.dir.join(name)),
+            },
+            Tab::Recent => self.recent.get(self.selected_index).cloned(),
+        }
+    }
+
+    /// Browse `dir`, staying where we are with an error if it can't be read.
+    fn browse(&mut self, dir: PathBuf) -> bool {
+        match read_subdirs(&dir) {
+            Ok(children) => {
+                self.dir = dir;
+                self.children = children;
+                self.tab = Tab::Browse;
+                self.selected_index = 0;
+                self.error = None;
+                true
+            }
+            Err(e) => {
+                self.error = Some(format!("{}: {e}", dir.display()));
+                false
+            }
+        }
+    }
+
+    /// Browse the parent directory, selecting the one we came from.
+    fn go_up(&mut self) {
+        let Some(parent) = self.dir.parent().map(Path::to_path_buf) else {
+            return;
+        };
+        let child = self
+            .dir
+            .file_name()
+            .map(|name| Entry::Child(name.to_string_lossy().into_owned()));
+        if self.browse(parent) {
+            if let Some(index) = child.and_then(|c| self.entries().iter().position(|e| *e == c)) {
+                self.selected_index = index;
+            }
+        }
+    }
+
+    /// Enter on a row: choose the directory being browsed or a recent one,
+    /// or open the selected subdirectory.
+    fn activate(&mut self) -> Option<DirPickerAction> {
+        let dir = self.selected_dir()?;
+        match (self.tab, self.entries().get(self.selected_index)) {
+            (Tab::Browse, Some(Entry::Parent)) => self.go_up(),
+            (Tab::Browse, Some(Entry::Child(_))) => {
+                self.browse(dir);
+            }
+            _ => return Some(DirPickerAction::Chosen(dir)),
+        }
+        None
+    }
+
+    /// Type a path, starting from the directory being browsed.
+    fn start_typing(&mut self) {
+        let mut text = self.dir.display().to_string();
+        if !text.ends_with(std::path::MAIN_SEPARATOR) {
+            text.push(std::path::MAIN_SEPARATOR);
+        }
+        self.input = Some(InputState::new(&text));
+        self.error = None;
+    }
+
+    /// Browse the typed directory, which may be relative to the one being
+    /// browsed. Returns whether it exists; otherwise the
+    /// input stays open with an error.
+    fn commit_input(&mut self) -> bool {
+        let Some(ref input) = self.input else {
+            return false;
+        };
+        let text = input.text().trim().to_string();
+        if text.is_empty() {
+            self.cancel_input();
+            return false;
+        }
+        let path = self.dir.join(paths::expand_home(&text));
+        let path = match path.canonicalize() {
+            Ok(path) if path.is_dir() => path,
+            _ => {
+                self.error = Some(format!("'{text}' is not a directory"));
+                return false;
+            }
+        };
+        if !self.browse(path) {
+            return false;
+        }
+        self.input = None;
+        true
+    }
+
+    fn cancel_input(&mut self) {
+        self.input = None;
+        self.error = None;
+    }
+
+    fn handle_typing_key(&mut self, key: KeyEvent) -> EventResult<DirPickerAction> {
+        match key.code {
+            KeyCode::Enter => {
+                self.commit_input();
+                return EventResult::Consumed;
+            }
+            KeyCode::Esc => {
+                self.cancel_input();
+                return EventResult::Consumed;
+            }
+            _ => {}
+        }
+        let Some(ref mut input) = self.input else {
+            return EventResult::NotHandled;
+        };
+        match key.code {
+            KeyCode::Char('u') if key.modifiers.contains(KeyModifiers::CONTROL) => input.clear(),
+            KeyCode::Char('w') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                input.delete_word_backward();
+            }
+            KeyCode::Char(_) if key.modifiers.contains(KeyModifiers::CONTROL) => {}
+            KeyCode::Char(c) => input.insert_char(c),
+            KeyCode::Backspace => {
+                input.delete_char_backward();
+            }
+            KeyCode::Delete => {
+                input.delete_char_forward();
+            }
+            KeyCode::Left => input.move_left(),
+            KeyCode::Right => input.move_right(),
+            KeyCode::Home => input.move_home(),
+            KeyCode::End => input.move_end(),
+            _ => {}
+        }
+        EventResult::Consumed
+    }
+}
+
+/// Subdirectories of `dir`, following symlinks and skipping hidden ones.
+fn read_subdirs(dir: &Path) -> std::io::Result<Vec<String>> {
+    let mut names: Vec<String> = std::fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path().is_dir())
+        .map(|entry| entry.file_name().to_string_lossy().into_owned())
+        .filter(|name| !name.starts_with('.'))
+        .collect();
+    names.sort_by_key(|name| name.to_lowercase());
+    Ok(names)
+}
+
+impl DirPickerComponent {
+    pub fn new() -> Self {
+        Self {
+            state: None,
+            viewport: Rect::ZERO,
+        }
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.state.is_some()
+    }
+
+    /// Open the picker browsing `dir`, with the recently used directories
+    /// and whether relative paths in arguments are resolved.
+    pub fn open(&mut self, dir: PathBuf, recent: Vec<PathBuf>, resolve_paths: bool) {
+        let mut inner = DirPickerInner {
+            tab: Tab::Browse,
+            dir: dir.clone(),
+            children: Vec::new(),
+            recent,
+            selected_index: 0,
+            input: None,
+            error: None,
+            resolve_paths,
+        };
+        inner.browse(dir);
+        self.state = Some(inner);
+    }
+
+    pub fn close(&mut self) {
+        self.state = None;
+    }
+
+    /// Whether a path is being typed.
+    pub fn is_typing(&self) -> bool {
+        self.state.as_ref().is_some_and(|s| s.input.is_some())
+    }
+
+    /// Set the viewport so collect_overlays can compute the anchor position.
+    pub fn set_viewport(&mut self, viewport: Rect) {
+        self.viewport = viewport;
+    }
+
+    #[cfg(test)]
+    pub fn dir(&self) -> Option<&Path> {
+        self.state.as_ref().map(|s| s.dir.as_path())
+    }
+
+    #[cfg(test)]
+    pub fn selected_dir(&self) -> Option<PathBuf> {
+        self.state.as_ref().and_then(|s| s.selected_dir())
+    }
+
+    #[cfg(test)]
+    pub fn error(&self) -> Option<&str> {
+        self.state.as_ref().and_then(|s| s.error.as_deref())
+    }
+
+    /// Handle a mouse click. Clicking a row selects it, and clicking the
+    /// selected row activates it; clicking outside the overlay closes it.
+    pub fn click_at(
+        &mut self,
+        col: u16,
+        row: u16,
+        overlay_rect: Option<Rect>,
+    ) -> Option<DirPickerAction> {
+        let inner = self.state.as_mut()?;
+
+        if let Some(rect) = overlay_rect {
+            let inside = col >= rect.x
+                && col < rect.x + rect.width
+                && row >= rect.y
+                && row < rect.y + rect.height;
+            if inside {
+                // Rows start below the tabs and the path line
+                if inner.input.is_none() && row > rect.y + 2 {
+                    let scroll = scroll_offset(inner.selected_index, visible_rows(rect));
+                    let idx = (row - rect.y - 3) as usize + scroll;
+                    if idx == inner.selected_index {
+                        let action = inner.activate();
+                        if action.is_some() {
+                            self.close();
+                        }
+                        return action;
+                    }
+                    if idx < inner.len() {
+                        inner.selected_index = idx;
+                    }
+                }
+                return None;
+            }
+        }
+
+        self.close();
+        Some(DirPickerAction::Cancelled)
+    }
+}
+
+/// Rows available for directories inside the borders, below the tabs and
+/// the path line.
+fn visible_rows(area: Rect) -> usize {
+    area.height.saturating_sub(4) as usize
+}
+
+impl Component for DirPickerComponent {
+    type Action = DirPickerAction;
+
+    fn handle_key(&mut self, key: KeyEvent) -> EventResult<Self::Action> {
+        let Some(ref mut inner) = self.state else {
+            return EventResult::NotHandled;
+        };
+
+        if inner.input.is_some() {
+            return inner.handle_typing_key(key);
+        }
+
+        let len = inner.len();
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => {
+                self.close();
+                EventResult::Action(DirPickerAction::Cancelled)
+            }
+            KeyCode::Tab | KeyCode::BackTab => {
+                inner.tab = match inner.tab {
+                    Tab::Browse => Tab::Recent,
+                    Tab::Recent => Tab::Browse,
+                };
+                inner.selected_index = 0;
+                EventResult::Consumed
+            }
+            KeyCode::Enter => match inner.activate() {
+                Some(action) => {
+                    self.close();
+                    EventResult::Action(action)
+                }
+                None => EventResult::Consumed,
+            },
+            KeyCode::Char(' ') => match inner.selected_dir() {
+                Some(dir) => {
+                    self.close();
+                    EventResult::Action(DirPickerAction::Chosen(dir))
+                }
+                None => EventResult::Consumed,
+            },
+            KeyCode::Right | KeyCode::Char('l') => {
+                match (inner.tab, inner.entries().get(inner.selected_index)) {
+                    (Tab::Browse, Some(Entry::Current)) => {}
+                    (Tab::Browse, Some(Entry::Parent)) => inner.go_up(),
+                    _ => {
+                        if let Some(dir) = inner.selected_dir() {
+                            inner.browse(dir);
+                        }
+                    }
+                }
+                EventResult::Consumed
+            }
+            KeyCode::Left | KeyCode::Char('h') | KeyCode::Backspace => {
+                if inner.tab == Tab::Browse {
+                    inner.go_up();
+                }
+                EventResult::Consumed
+            }
+            KeyCode::Char('~') => {
+                if let Some(home) = paths::home_dir() {
+                    inner.browse(home);
+                }
+                EventResult::Consumed
+            }
+            KeyCode::Char('/') | KeyCode::Char('e') => {
+                inner.start_typing();
+                EventResult::Consumed
+            }
+            KeyCode::Char('r') => {
+                self.close();
+                EventResult::Action(DirPickerAction::Reset)
+            }
+            KeyCode::Char('p') => {
+                inner.resolve_paths = !inner.resolve_paths;
+                EventResult::Action(DirPickerAction::ResolvePaths(inner.resolve_paths))
+            }
+            KeyCode::Up | KeyCode::Char('k') => {
+                inner.selected_index = inner.selected_index.saturating_sub(1);
+                EventResult::Consumed
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                if inner.selected_index + 1 < len {
+                    inner.selected_index += 1;
+                }
+                EventResult::Consumed
+            }
+            KeyCode::Home => {
+                inner.selected_index = 0;
+                EventResult::Consumed
+            }
+            KeyCode::End => {
+                inner.selected_index = len.saturating_sub(1);
+                EventResult::Consumed
+            }
+            _ => EventResult::Consumed,
+        }
+    }
+
+    fn handle_mouse(&mut self, _event: MouseEvent, _area: Rect) -> EventResult<Self::Action> {
+        EventResult::NotHandled
+    }
+
+    fn collect_overlays(&mut self) -> Vec<OverlayRequest> {
+        let Some(ref inner) = self.state else {
+            return vec![];
+        };
+
+        let rows: Vec<String> = match inner.tab {
+            Tab::Browse => inner
+                .entries()
+                .into_iter()
+                .map(|entry| match entry {
+                    Entry::Current => "./".to_string(),
+                    Entry::Parent => "../".to_string(),
+                    Entry::Child(name) => format!("{name}/"),
+                })
+                .collect(),
+            Tab::Recent => inner
+                .recent
+                .iter()
+                .map(|dir| paths::display_dir(dir))
+                .collect(),
+        };
+        let path = paths::display_dir(&inner.dir);
+        let editing = inner.input.as_ref().map(|input| {
+            (
+                input.text_before_cursor().to_string(),
+                input.text_after_cursor().to_string(),
+            )
+        });
+
+        let editing_len = inner.input.as_ref().map(|i| i.text().chars().count() + 1);
+        let widest = rows
+            .iter()
+            .map(|row| row.chars().count())
+            .chain([path.chars().count()])
+            .chain(editing_len)
+            .max()
+            .unwrap_or(0)
+            .max(44) as u16;
+        // "▶ " prefix (2) + padding (2) + borders (2)
+        let width = (widest + 6).min(self.viewport.width.saturating_sub(4));
+        let height = (rows.len().max(1) as u16 + 4).min(self.viewport.height.saturating_sub(5));
+
+        // Horizontally centered, just below the command preview
+        let anchor_x = self.viewport.x + self.viewport.width.saturating_sub(width) / 2;
+        let anchor = Rect::new(anchor_x, self.viewport.y + 3, 0, 0);
+
+        vec![OverlayRequest {
+            anchor,
+            size: (width, height),
+            content: Box::new(DirPickerOverlay {
+                tab: inner.tab,
+                path,
+                rows,
+                selected_index: inner.selected_index,
+                editing,
+                error: inner.error.clone(),
+                resolve_paths: inner.resolve_paths,
+            }),
+        }]
+    }
+}
+
+/// Snapshot of directory picker data needed to render the overlay.
+struct DirPickerOverlay {
+    tab: Tab,
+    /// The directory being browsed, for display.
+    path: String,
+    rows: Vec<String>,
+    selected_index: usize,
+    /// Text before and after the cursor of the path being typed.
+    editing: Option<(String, String)>,
+    error: Option<String>,
+    resolve_paths: bool,
+}
+
+impl OverlayContent for DirPickerOverlay {
+    fn render(&self, area: Rect, buf: &mut Buffer, colors: &UiColors) {
+        Clear.render(area, buf);
+        let footer = match self.error {
+            Some(ref error) => {
+                Span::styled(format!(" {error} "), Style::default().fg(colors.required))
+            }
+            None => Span::styled(
+                if self.resolve_paths {
+                    " relative paths: resolved (p) "
+                } else {
+                    " relative paths: as typed (p) "
+                },
+                Style::default().fg(colors.help),
+            ),
+        };
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(colors.active_border))
+            .title(" Working directory ")
+            .title_style(
+                Style::default()
+                    .fg(colors.active_border)
+                    .add_modifier(Modifier::BOLD),
+            )
+            .title_bottom(Line::from(footer));
+
+        let tab_style = |tab: Tab| {
+            if tab == self.tab {
+                Style::default()
+                    .fg(colors.active_border)
+                    .add_modifier(Modifier::BOLD | Modifier::UNDERLINED)
+            } else {
+                Style::default().fg(colors.help)
+            }
+        };
+        let mut lines = vec![Line::from(vec![
+            Span::raw("  "),
+            Span::styled("Browse", tab_style(Tab::Browse)),
+            Span::raw("  "),
+            Span::styled("Recent", tab_style(Tab::Recent)),
+            Span::styled("  (Tab)", Style::default().fg(colors.help)),
+        ])];
+
+        lines.push(match self.editing {
+            Some((ref before, ref after)) => {
+                let mut spans = vec![Span::raw("  ")];
+                push_edit_cursor(&mut spans, before, after, colors);
+                Line::from(spans).style(Style::default().bg(colors.editing_bg))
+            }
+            None => Line::from(vec![
+                Span::raw("  "),
+                Span::styled(self.path.clone(), Style::default().fg(colors.value)),
+            ]),
+        });
+
+        if self.rows.is_empty() {
+            lines.push(Line::from(Span::styled(
+                "  (no recent directories)",
+                Style::default().fg(colors.help).italic(),
+            )));
+        }
+        let scroll = scroll_offset(self.selected_index, visible_rows(area));
+        for (i, row) in self.rows.iter().enumerate().skip(scroll) {
+            let is_selected = i == self.selected_index && self.editing.is_none();
+            let mut spans = Vec::new();
+            if is_selected {
+                spans.push(Span::styled(
+                    "▶ ",
+                    Style::default()
+                        .fg(colors.active_border)
+                        .add_modifier(Modifier::BOLD),
+                ));
+            } else {
+                spans.push(Span::raw("  "));
+            }
+            let style = if self.tab == Tab::Browse && i == 0 {
+                Style::default().fg(colors.help)
+            } else {
+                Style::default().fg(colors.arg)
+            };
+            let style = if is_selected {
+                style.add_modifier(Modifier::BOLD)
+            } else {
+                style
+            };
+            spans.push(Span::styled(row.clone(), style));
+            if self.tab == Tab::Browse && i == 0 {
+                spans.push(Span::styled(
+                    " (run here)",
+                    Style::default().fg(colors.help),
+                ));
+            }
+            let line = Line::from(spans);
+            lines.push(if is_selected {
+                line.style(Style::default().bg(colors.selected_bg))
+            } else {
+                line
+            });
+        }
+
+        Paragraph::new(lines).block(block).render(area, buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::test_keys::{key, type_text};
+
+    /// A temporary directory tree: `root/{apps/web,packages/api,.git}`.
+    fn tree(name: &str) -> PathBuf {
+        let root =
+            std::env::temp_dir().join(format!("tuisage-test-dirs-{name}-{}", std::process::id()));
+        for dir in ["apps/web", "packages/api", ".git"] {
+            std::fs::create_dir_all(root.join(dir)).unwrap();
+        }
+        std::fs::write(root.join("README.md"), "").unwrap();
+        root.canonicalize().unwrap()
+    }
+
+    #[test]
+    fn test_browse_and_choose() {
+        let root = tree("browse");
+        let mut picker = DirPickerComponent::new();
+        picker.open(root.clone(), Vec::new(), false);
+        // ./, ../, apps/, packages/ — files and hidden directories are skipped
+        picker.handle_key(key(KeyCode::End));
+        assert_eq!(picker.selected_dir(), Some(root.join("packages")));
+
+        picker.handle_key(key(KeyCode::Enter));
+        assert_eq!(picker.dir(), Some(root.join("packages").as_path()));
+        picker.handle_key(key(KeyCode::End));
+        assert_eq!(
+            picker.handle_key(key(KeyCode::Char(' '))),
+            EventResult::Action(DirPickerAction::Chosen(root.join("packages/api")))
+        );
+        assert!(!picker.is_open());
+
+        // Going up selects the directory we came from
+        picker.open(root.join("apps/web"), Vec::new(), false);
+        picker.handle_key(key(KeyCode::Left));
+        assert_eq!(picker.selected_dir(), Some(root.join("apps/web")));
+        picker.handle_key(key(KeyCode::Home));
+        assert_eq!(
+            picker.handle_key(key(KeyCode::Enter)),
+            EventResult::Action(DirPickerAction::Chosen(root.join("apps")))
+        );
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn test_typed_path() {
+        let root = tree("typed");
+        let mut picker = DirPickerComponent::new();
+        picker.open(root.clone(), Vec::new(), false);
+        picker.handle_key(key(KeyCode::Char('/')));
+        assert!(picker.is_typing());
+        picker.handle_key(KeyEvent::new(KeyCode::Char('u'), KeyModifiers::CONTROL));
+        type_text(&mut picker, "nope");
+        picker.handle_key(key(KeyCode::Enter));
+        assert!(picker.is_typing());
+        assert_eq!(picker.error(), Some("'nope' is not a directory"));
+
+        // Relative to the directory being browsed
+        picker.handle_key(KeyEvent::new(KeyCode::Char('u'), KeyModifiers::CONTROL));
+        type_text(&mut picker, "apps/../packages");
+        picker.handle_key(key(KeyCode::Enter));
+        assert!(!picker.is_typing());
+        assert_eq!(picker.dir(), Some(root.join("packages").as_path()));
+        assert_eq!(picker.error(), None);
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn test_recent_reset_and_resolve_toggle() {
+        let mut picker = DirPickerComponent::new();
+        let recent = vec![PathBuf::from("/one"), PathBuf::from("/two")];
+        picker.open(std::env::temp_dir(), recent, false);
+        picker.handle_key(key(KeyCode::Tab));
+        picker.handle_key(key(KeyCode::Down));
+        assert_eq!(
+            picker.handle_key(key(KeyCode::Enter)),
+            EventResult::Action(DirPickerAction::Chosen(PathBuf::from("/two")))
+        );
+
+        picker.open(std::env::temp_dir(), Vec::new(), false);
+        assert_eq!(
+            picker.handle_key(key(KeyCode::Char('p'))),
+            EventResult::Action(DirPickerAction::ResolvePaths(true))
+        );
+        assert!(picker.is_open(), "Toggling path resolution keeps it open");
+        assert_eq!(
+            picker.handle_key(key(KeyCode::Char('r'))),
+            EventResult::Action(DirPickerAction::Reset)
+        );
+        assert!(!picker.is_open());
+    }
+
+    #[test]
+    fn test_click_rows_and_outside() {
+        let root = tree("click");
+        let mut picker = DirPickerComponent::new();
+        picker.open(root.clone(), Vec::new(), false);
+        let rect = Some(Rect::new(10, 3, 50, 8));
+        // Rows start below the border, tabs and path: ./ is on row 6
+        assert_eq!(picker.click_at(20, 8, rect), None);
+        assert_eq!(picker.selected_dir(), Some(root.join("apps")));
+        assert_eq!(picker.click_at(20, 8, rect), None, "Opens the directory");
+        assert_eq!(picker.dir(), Some(root.join("apps").as_path()));
+        assert_eq!(
+            picker.click_at(20, 6, rect),
+            Some(DirPickerAction::Chosen(root.join("apps"))),
+            "./ is selected in the new directory, so clicking it chooses it"
+        );
+        assert!(!picker.is_open());
+
+        picker.open(root.clone(), Vec::new(), false);
+        assert_eq!(
+            picker.click_at(0, 0, rect),
+            Some(DirPickerAction::Cancelled)
+        );
+        assert!(!picker.is_open());
+        std::fs::remove_dir_all(root).unwrap();
+    }
+}
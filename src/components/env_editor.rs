@@ -0,0 +1,815 @@
+//! Environment editor overlay component.
+//!
+//! Lists the environment variable overrides for the next run in a box
+//! centered below the command preview. Variables named by `env=` in the
+//! spec's flags and args are always listed, showing the inherited value
+//! until they are overridden. Entries are typed as `NAME=value`; every
+//! change is reported to the parent with the complete list of overrides.
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseEvent};
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph, Widget},
+};
+use ratatui_interact::components::InputState;
+
+use super::{
+    push_edit_cursor, scroll_offset, Component, EventResult, OverlayContent, OverlayRequest,
+};
+use crate::env::EnvVar;
+use crate::theme::UiColors;
+
+/// Actions emitted by the environment editor for the parent to process.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EnvEditorAction {
+    /// An override was added, edited, unset or removed. Contains all of them.
+    Changed(Vec<EnvVar>),
+    /// The editor was closed.
+    Closed,
+}
+
+/// A variable the spec reads, listed even when it isn't overridden.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EnvSuggestion {
+    pub name: String,
+    /// The flag or arg that reads it, e.g. `"--token"`.
+    pub source: String,
+    /// Value in TuiSage's own environment, which the command inherits.
+    pub inherited: Option<String>,
+}
+
+/// A row of the overlay.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Row {
+    /// A suggested variable, by index into `suggestions`.
+    Suggested(usize),
+    /// An override for a variable that isn't suggested, by index into `vars`.
+    Extra(usize),
+}
+
+/// Self-contained environment editor overlay.
+pub struct EnvEditorComponent {
+    state: Option<EnvEditorInner>,
+    /// The viewport area, set by the UI coordinator before collecting overlays.
+    viewport: Rect,
+}
+
+struct EnvEditorInner {
+    vars: Vec<EnvVar>,
+    suggestions: Vec<EnvSuggestion>,
+    selected_index: usize,
+    /// Inline `NAME=value` input while a variable is being added or edited.
+    input: Option<InputState>,
+    /// Whether the input adds a new variable rather than editing the
+    /// selected row.
+    adding: bool,
+    /// Why the last input could not be saved.
+    error: Option<String>,
+}
+
+impl EnvEditorInner {
+    fn rows(&self) -> Vec<Row> {
+        let suggested = (0..self.suggestions.len()).map(Row::Suggested);
+        let extra = self
+            .vars
+            .iter()
+            .enumerate()
+            .filter(|(_, var)| !self.suggestions.iter().any(|s| s.name == var.name))
+            .map(|(i, _)| Row::Extra(i));
+        suggested.chain(extra).collect()
+    }
+
+    /// Index into `vars` of the override shown in a row, if any.
+    fn var_index(&self, row: Row) -> Option<usize> {
+        match row {
+            Row::Suggested(i) => {
+                let name = &self.suggestions[i].name;
+                self.vars.iter().position(|v| v.name == *name)
+            }
+            Row::Extra(i) => Some(i),
+        }
+    }
+
+    fn selected_row(&self) -> Option<Row> {
+        self.rows().get(self.selected_index).copied()
+    }
+
+    fn select_name(&mut self, name: &str) {
+        let rows = self.rows();
+        if let Some(index) = rows.iter().position(|&row| match row {
+            Row::Suggested(i) => self.suggestions[i].name == name,
+            Row::Extra(i) => self.vars[i].name == name,
+        }) {
+            self.selected_index = index;
+        }
+    }
+
+    fn clamp_selection(&mut self) {
+        self.selected_index = self.selected_index.min(self.rows().len().saturating_sub(1));
+    }
+
+    fn start_adding(&mut self) {
+        self.input = Some(InputState::empty());
+        self.adding = true;
+        self.error = None;
+    }
+
+    /// Edit the selected row, pre-filled with its override or, for a
+    /// suggested variable, the inherited value.
+    fn start_editing(&mut self) {
+        let Some(row) = self.selected_row() else {
+            self.start_adding();
+            return;
+        };
+        let text = match (row, self.var_index(row)) {
+            (_, Some(i)) => {
+                let var = &self.vars[i];
+                format!("{}={}", var.name, var.value.as_deref().unwrap_or_default())
+            }
+            (Row::Suggested(i), None) => {
+                let suggestion = &self.suggestions[i];
+                format!(
+                    "{}={}",
+                    suggestion.name,
+                    suggestion.inherited.as_deref().unwrap_or_default()
+                )
+            }
+            (Row::Extra(_), None) => String::new(),
+        };
+        self.input = Some(InputState::new(&text));
+        self.adding = false;
+        self.error = None;
+    }
+
+    /// Save the typed variable, replacing any override with the same name
+    /// (and the one being edited, if it was renamed). Returns whether it
+    /// could be saved; otherwise the input stays open with an error.
+    fn commit_edit(&mut self) -> bool {
+        let Some(ref input) = self.input else {
+            return false;
+        };
+        if input.text().trim().is_empty() {
+            self.cancel_edit();
+            return false;
+        }
+        let var = match EnvVar::parse(input.text()) {
+            Ok(var) => var,
+            Err(e) => {
+                self.error = Some(e);
+                return false;
+            }
+        };
+        self.input = None;
+        self.error = None;
+        let edited = if self.adding {
+            None
+        } else {
+            self.selected_row().and_then(|row| self.var_index(row))
+        };
+        let existing = self.vars.iter().position(|v| v.name == var.name);
+        match (edited, existing) {
+            (_, Some(i)) => {
+                self.vars[i] = var.clone();
+                if let Some(edited) = edited.filter(|&e| e != i) {
+                    self.vars.remove(edited);
+                }
+            }
+            (Some(i), None) => self.vars[i] = var.clone(),
+            (None, None) => self.vars.push(var.clone()),
+        }
+        self.select_name(&var.name);
+        true
+    }
+
+    fn cancel_edit(&mut self) {
+        self.input = None;
+        self.error = None;
+    }
+
+    /// Unset the selected variable, or stop unsetting it.
+    fn toggle_unset(&mut self) -> bool {
+        let Some(row) = self.selected_row() else {
+            return false;
+        };
+        match (row, self.var_index(row)) {
+            (_, Some(i)) if self.vars[i].value.is_none() => {
+                self.vars.remove(i);
+                self.clamp_selection();
+            }
+            (_, Some(i)) => self.vars[i].value = None,
+            (Row::Suggested(i), None) => {
+                let name = self.suggestions[i].name.clone();
+                self.vars.push(EnvVar::unset(name));
+            }
+            (Row::Extra(_), None) => return false,
+        }
+        true
+    }
+
+    /// Remove the override in the selected row, going back to the
+    /// inherited value.
+    fn remove_selected(&mut self) -> bool {
+        let Some(i) = self.selected_row().and_then(|row| self.var_index(row)) else {
+            return false;
+        };
+        self.vars.remove(i);
+        self.clamp_selection();
+        true
+    }
+
+    fn handle_editing_key(&mut self, key: KeyEvent) -> EventResult<EnvEditorAction> {
+        match key.code {
+            KeyCode::Enter => {
+                if self.commit_edit() {
+                    return EventResult::Action(EnvEditorAction::Changed(self.vars.clone()));
+                }
+                return EventResult::Consumed;
+            }
+            KeyCode::Esc => {
+                self.cancel_edit();
+                return EventResult::Consumed;
+            }
+            _ => {}
+        }
+        let Some(ref mut input) = self.input else {
+            return EventResult::NotHandled;
+        };
+        match key.code {
+            KeyCode::Char('u') if key.modifiers.contains(KeyModifiers::CONTROL) => input.clear(),
+            KeyCode::Char('w') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                input.delete_word_backward();
+            }
+            KeyCode::Char(_) if key.modifiers.contains(KeyModifiers::CONTROL) => {}
+            KeyCode::Char(c) => input.insert_char(c),
+            KeyCode::Backspace => {
+                input.delete_char_backward();
+            }
+            KeyCode::Delete => {
+                input.delete_char_forward();
+            }
+            KeyCode::Left => input.move_left(),
+            KeyCode::Right => input.move_right(),
+            KeyCode::Home => input.move_home(),
+            KeyCode::End => input.move_end(),
+            _ => {}
+        }
+        EventResult::Consumed
+    }
+}
+
+impl EnvEditorComponent {
+    pub fn new() -> Self {
+        Self {
+            state: None,
+            viewport: Rect::ZERO,
+        }
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.state.is_some()
+    }
+
+    /// Open the editor with the current overrides and the variables the
+    /// spec reads. With nothing to list, it starts straight away with a new
+    /// entry.
+    pub fn open(&mut self, vars: Vec<EnvVar>, suggestions: Vec<EnvSuggestion>) {
+        let mut inner = EnvEditorInner {
+            vars,
+            suggestions,
+            selected_index: 0,
+            input: None,
+            adding: false,
+            error: None,
+        };
+        if inner.rows().is_empty() {
+            inner.start_adding();
+        }
+        self.state = Some(inner);
+    }
+
+    pub fn close(&mut self) {
+        self.state = None;
+    }
+
+    /// Commit any variable still being typed and close the editor.
+    /// Returns the overrides if that changed them.
+    pub fn finish(&mut self) -> Option<Vec<EnvVar>> {
+        let mut inner = self.state.take()?;
+        inner.commit_edit().then_some(inner.vars)
+    }
+
+    /// Whether a variable is being added or edited.
+    pub fn is_editing_entry(&self) -> bool {
+        self.state.as_ref().is_some_and(|s| s.input.is_some())
+    }
+
+    /// Set the viewport so collect_overlays can compute the anchor position.
+    pub fn set_viewport(&mut self, viewport: Rect) {
+        self.viewport = viewport;
+    }
+
+    #[cfg(test)]
+    pub fn vars(&self) -> Option<&[EnvVar]> {
+        self.state.as_ref().map(|s| s.vars.as_slice())
+    }
+
+    #[cfg(test)]
+    pub fn selected_index(&self) -> Option<usize> {
+        self.state.as_ref().map(|s| s.selected_index)
+    }
+
+    #[cfg(test)]
+    pub fn error(&self) -> Option<&str> {
+        self.state.as_ref().and_then(|s| s.error.as_deref())
+    }
+
+    /// Handle a mouse click. Clicking a row selects it; clicking outside
+    /// the overlay commits any pending edit and closes the editor.
+    pub fn click_at(
+        &mut self,
+        col: u16,
+        row: u16,
+        overlay_rect: Option<Rect>,
+    ) -> Option<EnvEditorAction> {
+        let inner = self.state.as_mut()?;
+
+        if let Some(rect) = overlay_rect {
+            let inside = col >= rect.x
+                && col < rect.x + rect.width
+                && row >= rect.y
+                && row < rect.y + rect.height;
+            if inside {
+                if inner.input.is_none() && row > rect.y {
+                    let scroll = scroll_offset(inner.selected_index, visible_rows(rect));
+                    let idx = (row - rect.y - 1) as usize + scroll;
+                    if idx < inner.rows().len() {
+                        inner.selected_index = idx;
+                    }
+                }
+                return None;
+            }
+        }
+
+        Some(match self.finish() {
+            Some(vars) => EnvEditorAction::Changed(vars),
+            None => EnvEditorAction::Closed,
+        })
+    }
+}
+
+/// Rows available for variables inside the borders.
+fn visible_rows(area: Rect) -> usize {
+    area.height.saturating_sub(2) as usize
+}
+
+impl Component for EnvEditorComponent {
+    type Action = EnvEditorAction;
+
+    fn handle_key(&mut self, key: KeyEvent) -> EventResult<Self::Action> {
+        let Some(ref mut inner) = self.state else {
+            return EventResult::NotHandled;
+        };
+
+        if inner.input.is_some() {
+            return inner.handle_editing_key(key);
+        }
+
+        let len = inner.rows().len();
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => {
+                self.close();
+                EventResult::Action(EnvEditorAction::Closed)
+            }
+            KeyCode::Char('a') | KeyCode::Char('+') | KeyCode::Insert => {
+                inner.start_adding();
+                EventResult::Consumed
+            }
+            KeyCode::Enter | KeyCode::Char('e') => {
+                inner.start_editing();
+                EventResult::Consumed
+            }
+            KeyCode::Char('u') => {
+                if inner.toggle_unset() {
+                    return EventResult::Action(EnvEditorAction::Changed(inner.vars.clone()));
+                }
+                EventResult::Consumed
+            }
+            KeyCode::Char('d') | KeyCode::Delete | KeyCode::Backspace => {
+                if inner.remove_selected() {
+                    return EventResult::Action(EnvEditorAction::Changed(inner.vars.clone()));
+                }
+                EventResult::Consumed
+            }
+            KeyCode::Up | KeyCode::Char('k') => {
+                inner.selected_index = inner.selected_index.saturating_sub(1);
+                EventResult::Consumed
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                if inner.selected_index + 1 < len {
+                    inner.selected_index += 1;
+                }
+                EventResult::Consumed
+            }
+            KeyCode::Home => {
+                inner.selected_index = 0;
+                EventResult::Consumed
+            }
+            KeyCode::End => {
+                inner.selected_index = len.saturating_sub(1);
+                EventResult::Consumed
+            }
+            _ => EventResult::Consumed,
+        }
+    }
+
+    fn handle_mouse(&mut self, _event: MouseEvent, _area: Rect) -> EventResult<Self::Action> {
+        EventResult::NotHandled
+    }
+
+    fn collect_overlays(&mut self) -> Vec<OverlayRequest> {
+        let Some(ref inner) = self.state else {
+            return vec![];
+        };
+
+        let mut lines: Vec<OverlayLine> = inner
+            .rows()
+            .into_iter()
+            .map(|row| {
+                let var = inner.var_index(row).map(|i| inner.vars[i].clone());
+                match row {
+                    Row::Suggested(i) => {
+                        let suggestion = &inner.suggestions[i];
+                        let description = match (&var, &suggestion.inherited) {
+                            (Some(_), _) => suggestion.source.clone(),
+                            (None, Some(value)) => {
+                                format!("{} · inherited: {value}", suggestion.source)
+                            }
+                            (None, None) => format!("{} · not set", suggestion.source),
+                        };
+                        OverlayLine {
+                            name: suggestion.name.clone(),
+                            var,
+                            description,
+                        }
+                    }
+                    Row::Extra(i) => OverlayLine {
+                        name: inner.vars[i].name.clone(),
+                        var,
+                        description: String::new(),
+                    },
+                }
+            })
+            .collect();
+        let editing = inner.input.as_ref().map(|input| {
+            (
+                input.text_before_cursor().to_string(),
+                input.text_after_cursor().to_string(),
+            )
+        });
+        // A new variable is typed in a row of its own at the end
+        let adding = editing.is_some() && inner.adding;
+        if adding {
+            lines.push(OverlayLine {
+                name: String::new(),
+                var: None,
+                description: String::new(),
+            });
+        }
+        let editing_index = if adding {
+            lines.len() - 1
+        } else {
+            inner.selected_index
+        };
+
+        let editing_len = inner.input.as_ref().map(|i| i.text().chars().count() + 1);
+        let widest = lines
+            .iter()
+            .map(|line| line.label().chars().count() + line.description.chars().count() + 2)
+            .chain(editing_len)
+            .max()
+            .unwrap_or(0)
+            .max(40) as u16;
+        // "▶ " prefix (2) + padding (2) + borders (2)
+        let width = (widest + 6).min(self.viewport.width.saturating_sub(4));
+        let height = (lines.len().max(1) as u16 + 2).min(self.viewport.height.saturating_sub(5));
+
+        // Horizontally centered, just below the command preview
+        let anchor_x = self.viewport.x + self.viewport.width.saturating_sub(width) / 2;
+        let anchor = Rect::new(anchor_x, self.viewport.y + 3, 0, 0);
+
+        vec![OverlayRequest {
+            anchor,
+            size: (width, height),
+            content: Box::new(EnvEditorOverlay {
+                lines,
+                selected_index: if editing.is_some() {
+                    editing_index
+                } else {
+                    inner.selected_index
+                },
+                editing,
+                error: inner.error.clone(),
+            }),
+        }]
+    }
+}
+
+/// A row of the overlay, ready for rendering.
+struct OverlayLine {
+    name: String,
+    /// The override, if the variable has one.
+    var: Option<EnvVar>,
+    description: String,
+}
+
+impl OverlayLine {
+    fn label(&self) -> String {
+        match &self.var {
+            Some(EnvVar {
+                value: Some(value), ..
+            }) => format!("{}={value}", self.name),
+            Some(EnvVar { value: None, .. }) => format!("{} (unset)", self.name),
+            None => self.name.clone(),
+        }
+    }
+}
+
+/// Snapshot of environment editor data needed to render the overlay.
+struct EnvEditorOverlay {
+    lines: Vec<OverlayLine>,
+    selected_index: usize,
+    /// Text before and after the cursor of the variable being typed.
+    editing: Option<(String, String)>,
+    error: Option<String>,
+}
+
+impl OverlayContent for EnvEditorOverlay {
+    fn render(&self, area: Rect, buf: &mut Buffer, colors: &UiColors) {
+        Clear.render(area, buf);
+        let mut block = Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(colors.active_border))
+            .title(" Environment ")
+            .title_style(
+                Style::default()
+                    .fg(colors.active_border)
+                    .add_modifier(Modifier::BOLD),
+            );
+        if let Some(ref error) = self.error {
+            block = block.title_bottom(Line::from(Span::styled(
+                format!(" {error} "),
+                Style::default().fg(colors.required),
+            )));
+        }
+
+        let inner_width = area.width.saturating_sub(2) as usize;
+        let scroll = scroll_offset(self.selected_index, visible_rows(area));
+        let lines: Vec<Line> = if self.lines.is_empty() {
+            vec![Line::from(Span::styled(
+                "  (no variables — a to add)",
+                Style::default().fg(colors.help).italic(),
+            ))]
+        } else {
+            self.lines
+                .iter()
+                .enumerate()
+                .skip(scroll)
+                .map(|(i, line)| {
+                    let is_selected = i == self.selected_index;
+                    let bold = if is_selected {
+                        Modifier::BOLD
+                    } else {
+                        Modifier::empty()
+                    };
+                    let mut spans = Vec::new();
+                    if is_selected {
+                        spans.push(Span::styled(
+                            "▶ ",
+                            Style::default()
+                                .fg(colors.active_border)
+                                .add_modifier(Modifier::BOLD),
+                        ));
+                    } else {
+                        spans.push(Span::raw("  "));
+                    }
+                    if let (Some((before, after)), true) = (&self.editing, is_selected) {
+                        push_edit_cursor(&mut spans, before, after, colors);
+                        return Line::from(spans).style(Style::default().bg(colors.editing_bg));
+                    }
+                    match &line.var {
+                        Some(EnvVar {
+                            value: Some(value), ..
+                        }) => {
+                            spans.push(Span::styled(
+                                format!("{}=", line.name),
+                                Style::default().fg(colors.flag).add_modifier(bold),
+                            ));
+                            spans.push(Span::styled(
+                                value.clone(),
+                                Style::default().fg(colors.value).add_modifier(bold),
+                            ));
+                        }
+                        Some(EnvVar { value: None, .. }) => {
+                            spans.push(Span::styled(
+                                line.name.clone(),
+                                Style::default().fg(colors.flag).add_modifier(bold),
+                            ));
+                            spans.push(Span::styled(
+                                " (unset)",
+                                Style::default().fg(colors.required),
+                            ));
+                        }
+                        None => spans.push(Span::styled(
+                            line.name.clone(),
+                            Style::default().fg(colors.help).add_modifier(bold),
+                        )),
+                    }
+                    if !line.description.is_empty() {
+                        let used = 2 + line.label().chars().count();
+                        let desc_len = line.description.chars().count();
+                        let padding = inner_width.saturating_sub(used + desc_len + 1).max(1);
+                        spans.push(Span::raw(" ".repeat(padding)));
+                        spans.push(Span::styled(
+                            line.description.clone(),
+                            Style::default().fg(colors.help),
+                        ));
+                    }
+                    let line = Line::from(spans);
+                    if is_selected {
+                        line.style(Style::default().bg(colors.selected_bg))
+                    } else {
+                        line
+                    }
+                })
+                .collect()
+        };
+
+        Paragraph::new(lines).block(block).render(area, buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::test_keys::{key, type_text};
+
+    fn suggestions() -> Vec<EnvSuggestion> {
+        vec![
+            EnvSuggestion {
+                name: "API_TOKEN".to_string(),
+                source: "--token".to_string(),
+                inherited: Some("secret".to_string()),
+            },
+            EnvSuggestion {
+                name: "LOG_LEVEL".to_string(),
+                source: "--log-level".to_string(),
+                inherited: None,
+            },
+        ]
+    }
+
+    #[test]
+    fn test_empty_editor_starts_adding() {
+        let mut editor = EnvEditorComponent::new();
+        editor.open(Vec::new(), Vec::new());
+        assert!(editor.is_editing_entry());
+        type_text(&mut editor, "FOO=bar baz");
+        assert_eq!(
+            editor.handle_key(key(KeyCode::Enter)),
+            EventResult::Action(EnvEditorAction::Changed(vec![EnvVar::set(
+                "FOO", "bar baz"
+            )]))
+        );
+        assert!(
+            editor.is_open(),
+            "Editor stays open after adding a variable"
+        );
+    }
+
+    #[test]
+    fn test_edit_suggested_variable() {
+        let mut editor = EnvEditorComponent::new();
+        editor.open(Vec::new(), suggestions());
+        assert!(!editor.is_editing_entry());
+        // Pre-filled with the inherited value
+        editor.handle_key(key(KeyCode::Enter));
+        editor.handle_key(key(KeyCode::Char('2')));
+        assert_eq!(
+            editor.handle_key(key(KeyCode::Enter)),
+            EventResult::Action(EnvEditorAction::Changed(vec![EnvVar::set(
+                "API_TOKEN",
+                "secret2"
+            )]))
+        );
+        assert_eq!(editor.selected_index(), Some(0));
+
+        editor.handle_key(key(KeyCode::Down));
+        editor.handle_key(key(KeyCode::Char('e')));
+        type_text(&mut editor, "debug");
+        editor.handle_key(key(KeyCode::Enter));
+        assert_eq!(
+            editor.vars().unwrap(),
+            [
+                EnvVar::set("API_TOKEN", "secret2"),
+                EnvVar::set("LOG_LEVEL", "debug")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_invalid_entry_keeps_editing() {
+        let mut editor = EnvEditorComponent::new();
+        editor.open(Vec::new(), suggestions());
+        editor.handle_key(key(KeyCode::Char('a')));
+        type_text(&mut editor, "NOPE");
+        assert_eq!(
+            editor.handle_key(key(KeyCode::Enter)),
+            EventResult::Consumed
+        );
+        assert!(editor.is_editing_entry());
+        assert_eq!(editor.error(), Some("expected NAME=value"));
+
+        editor.handle_key(key(KeyCode::Esc));
+        assert!(!editor.is_editing_entry());
+        assert_eq!(editor.error(), None);
+        assert_eq!(editor.vars().unwrap(), []);
+    }
+
+    #[test]
+    fn test_unset_and_remove() {
+        let mut editor = EnvEditorComponent::new();
+        editor.open(vec![EnvVar::set("EXTRA", "1")], suggestions());
+        let result = editor.handle_key(key(KeyCode::Char('u')));
+        assert_eq!(
+            result,
+            EventResult::Action(EnvEditorAction::Changed(vec![
+                EnvVar::set("EXTRA", "1"),
+                EnvVar::unset("API_TOKEN"),
+            ]))
+        );
+        // Toggling again goes back to inheriting it
+        editor.handle_key(key(KeyCode::Char('u')));
+        assert_eq!(editor.vars().unwrap(), [EnvVar::set("EXTRA", "1")]);
+        assert_eq!(
+            editor.handle_key(key(KeyCode::Char('d'))),
+            EventResult::Consumed,
+            "A suggested variable without an override can't be removed"
+        );
+
+        editor.handle_key(key(KeyCode::End));
+        assert_eq!(editor.selected_index(), Some(2));
+        let result = editor.handle_key(key(KeyCode::Delete));
+        assert_eq!(
+            result,
+            EventResult::Action(EnvEditorAction::Changed(Vec::new()))
+        );
+        assert_eq!(
+            editor.selected_index(),
+            Some(1),
+            "Selection clamps to the last row"
+        );
+    }
+
+    #[test]
+    fn test_renaming_replaces_existing_override() {
+        let mut editor = EnvEditorComponent::new();
+        editor.open(
+            vec![EnvVar::set("A", "1"), EnvVar::set("B", "2")],
+            Vec::new(),
+        );
+        editor.handle_key(key(KeyCode::Enter));
+        editor.handle_key(KeyEvent::new(KeyCode::Char('u'), KeyModifiers::CONTROL));
+        type_text(&mut editor, "B=3");
+        editor.handle_key(key(KeyCode::Enter));
+        assert_eq!(editor.vars().unwrap(), [EnvVar::set("B", "3")]);
+        assert_eq!(editor.selected_index(), Some(0));
+    }
+
+    #[test]
+    fn test_close_and_click_outside() {
+        let mut editor = EnvEditorComponent::new();
+        editor.open(Vec::new(), suggestions());
+        assert_eq!(
+            editor.handle_key(key(KeyCode::Esc)),
+            EventResult::Action(EnvEditorAction::Closed)
+        );
+        assert!(!editor.is_open());
+        assert!(editor.collect_overlays().is_empty());
+
+        editor.open(Vec::new(), suggestions());
+        let rect = Some(Rect::new(10, 3, 50, 4));
+        assert_eq!(editor.click_at(20, 5, rect), None);
+        assert_eq!(editor.selected_index(), Some(1));
+        editor.handle_key(key(KeyCode::Char('a')));
+        type_text(&mut editor, "X=1");
+        assert_eq!(
+            editor.click_at(0, 0, rect),
+            Some(EnvEditorAction::Changed(vec![EnvVar::set("X", "1")])),
+            "Clicking outside saves the variable being typed"
+        );
+        assert!(!editor.is_open());
+    }
+}
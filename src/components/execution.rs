@@ -1,16 +1,20 @@
 //! Execution component — embedded terminal for running commands.
 //!
 //! This component owns the full lifecycle of a command execution:
-//! PTY I/O, VT100 rendering, keyboard forwarding, and close-on-exit.
+//! PTY I/O, VT100 rendering, keyboard forwarding, scrollback, transcript
+//! recording, signals and timeouts, and close-on-exit.
 
 use std::io::Read;
 use std::io::Write;
+use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex, RwLock};
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
-use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseEvent};
-use portable_pty::{CommandBuilder, MasterPty, NativePtySystem, PtySize, PtySystem};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseEvent, MouseEventKind};
+use portable_pty::{
+    CommandBuilder, ExitStatus, MasterPty, NativePtySystem, PtySize, PtySystem,
+};
 use ratatui::{
     buffer::Buffer,
     layout::{Constraint, Direction, Layout, Rect, Size},
@@ -18,16 +22,189 @@ use ratatui::{
     text::{Line, Span},
     widgets::{Block, Borders, Paragraph, Widget, Wrap},
 };
-use tui_term::widget::PseudoTerminal;
+use tui_term::widget::{Cursor, PseudoTerminal};
 
 use super::{Component, EventResult, RenderableComponent};
+use crate::config::{KeyBinding, KeyBindings};
+use crate::env::EnvVar;
+use crate::pty_input::{encode_key, encode_mouse, encode_paste, InputModes};
 use crate::theme::UiColors;
+use crate::transcript::Transcript;
+use crate::watch::Watcher;
+
+/// Lines scrolled per mouse wheel step.
+const WHEEL_SCROLL_LINES: usize = 3;
 
 /// Actions the execution component can emit to its parent.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum ExecutionAction {
     /// User closed the execution view (process had exited).
     Close,
+    /// User asked to save the transcript (process had exited).
+    SaveTranscript,
+    /// User sent the command to the background to return to the builder.
+    Background,
+    /// User asked to stop the command and run the builder's command again.
+    Rerun,
+    /// User asked to run the same command again (process had exited).
+    RunAgain,
+    /// User asked to change the command in the builder (process had exited).
+    Edit,
+    /// User toggled running the command again when files change.
+    ToggleWatch,
+}
+
+/// Signals for stopping a running command.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Signal {
+    /// Ask the process to exit.
+    Terminate,
+    /// Force the process to exit.
+    Kill,
+}
+
+impl Signal {
+    pub fn name(self) -> &'static str {
+        match self {
+            Signal::Terminate => "SIGTERM",
+            Signal::Kill => "SIGKILL",
+        }
+    }
+
+    #[cfg(unix)]
+    fn number(self) -> libc::c_int {
+        match self {
+            Signal::Terminate => libc::SIGTERM,
+            Signal::Kill => libc::SIGKILL,
+        }
+    }
+}
+
+/// A signal sent to the command, and the timeout that sent it, if any.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SentSignal {
+    pub signal: Signal,
+    pub timeout: Option<Duration>,
+}
+
+/// Sends signals to a spawned command.
+#[derive(Clone)]
+struct ProcessControl {
+    /// The child leads its own session, so this is also its process group.
+    #[cfg(unix)]
+    pid: Option<u32>,
+    #[cfg(not(unix))]
+    killer: Arc<Mutex<Box<dyn portable_pty::ChildKiller + Send + Sync>>>,
+    exited: Arc<AtomicBool>,
+    sent: Arc<Mutex<Option<SentSignal>>>,
+}
+
+impl ProcessControl {
+    /// Send `signal` to the process group, unless the process has exited.
+    fn send(&self, signal: Signal, timeout: Option<Duration>) {
+        if self.exited.load(Ordering::Relaxed) {
+            return;
+        }
+        if let Ok(mut sent) = self.sent.lock() {
+            *sent = Some(SentSignal { signal, timeout });
+        }
+        #[cfg(unix)]
+        if let Some(pid) = self.pid.and_then(|p| libc::pid_t::try_from(p).ok()) {
+            // SAFETY: kill() only takes integer arguments. The group is
+            // signalled so that children started by the command stop too.
+            unsafe {
+                if libc::kill(-pid, signal.number()) != 0 {
+                    libc::kill(pid, signal.number());
+                }
+            }
+        }
+        #[cfg(not(unix))]
+        if let Ok(mut killer) = self.killer.lock() {
+            // Windows has no signals; both terminate the process
+            let _ = killer.kill();
+        }
+    }
+}
+
+/// Wait up to `limit` for `done` to return true, returning whether it did.
+pub fn wait_until(done: impl Fn() -> bool, limit: Duration) -> bool {
+    let deadline = Instant::now() + limit;
+    while !done() {
+        let now = Instant::now();
+        if now >= deadline {
+            return false;
+        }
+        std::thread::sleep((deadline - now).min(Duration::from_millis(20)));
+    }
+    true
+}
+
+/// Send SIGTERM to the commands, then SIGKILL to those still running after
+/// the `grace` period.
+pub fn stop_executions(executions: &[&ExecutionComponent], grace: Duration) {
+    let running: Vec<&&ExecutionComponent> = executions.iter().filter(|e| !e.exited()).collect();
+    if running.is_empty() {
+        return;
+    }
+    let all_exited = || running.iter().all(|e| e.exited());
+    for execution in &running {
+        execution.signal(Signal::Terminate);
+    }
+    if wait_until(all_exited, grace) {
+        return;
+    }
+    for execution in &running {
+        execution.signal(Signal::Kill);
+    }
+    // Give the exit statuses a moment to be collected for the history
+    wait_until(all_exited, Duration::from_secs(1));
+}
+
+/// Describe an exit status, naming the signal that ended the process
+/// (e.g. "Terminated by SIGTERM" rather than "Terminated by Terminated").
+fn describe_exit_status(status: &ExitStatus) -> String {
+    match status.signal().and_then(signal_name) {
+        Some(name) => format!("Terminated by {name}"),
+        None => status.to_string(),
+    }
+}
+
+/// The name of a signal from its `strsignal` description, which is what
+/// portable-pty reports.
+#[cfg(unix)]
+fn signal_name(description: &str) -> Option<&'static str> {
+    const SIGNALS: &[(libc::c_int, &str)] = &[
+        (libc::SIGHUP, "SIGHUP"),
+        (libc::SIGINT, "SIGINT"),
+        (libc::SIGQUIT, "SIGQUIT"),
+        (libc::SIGILL, "SIGILL"),
+        (libc::SIGABRT, "SIGABRT"),
+        (libc::SIGFPE, "SIGFPE"),
+        (libc::SIGKILL, "SIGKILL"),
+        (libc::SIGSEGV, "SIGSEGV"),
+        (libc::SIGPIPE, "SIGPIPE"),
+        (libc::SIGALRM, "SIGALRM"),
+        (libc::SIGTERM, "SIGTERM"),
+        (libc::SIGUSR1, "SIGUSR1"),
+        (libc::SIGUSR2, "SIGUSR2"),
+    ];
+    SIGNALS.iter().find_map(|&(number, name)| {
+        // SAFETY: strsignal returns a valid C string (or null), which is
+        // copied before the next call can overwrite it.
+        let text = unsafe {
+            let ptr = libc::strsignal(number);
+            if ptr.is_null() {
+                return None;
+            }
+            std::ffi::CStr::from_ptr(ptr).to_string_lossy().into_owned()
+        };
+        (text == description).then_some(name)
+    })
+}
+
+#[cfg(not(unix))]
+fn signal_name(_description: &str) -> Option<&'static str> {
+    None
 }
 
 /// State for a running (or finished) command execution.
@@ -46,46 +223,217 @@ pub struct ExecutionState {
     pub exit_status: Arc<Mutex<Option<String>>>,
 }
 
+/// What a spawned command was started with, for running it again.
+#[derive(Debug, Clone)]
+struct Launch {
+    parts: Vec<String>,
+    env: Vec<EnvVar>,
+    cwd: Option<PathBuf>,
+    scrollback: usize,
+}
+
 /// Embedded terminal component for command execution.
 pub struct ExecutionComponent {
     state: ExecutionState,
+    /// How the command was started, when it was spawned by this component.
+    launch: Option<Launch>,
+    /// Durations of the earlier runs, when the command was run again.
+    earlier_runs: Vec<Duration>,
+    /// Timeout and SIGKILL grace period applied to every run.
+    timeout: Option<(Duration, Duration)>,
+    /// Runs the command again when files change, in watch mode.
+    watch: Option<Watcher>,
+    /// Whether keys scroll the output instead of going to the running
+    /// process. Scrolling is always available once the process has exited.
+    scroll_mode: bool,
+    /// Key pressed before the keys below while the process runs.
+    prefix_key: KeyBinding,
+    /// Whether the prefix key was just pressed.
+    prefix_pending: bool,
+    /// Key that toggles scroll mode while the process runs.
+    scroll_key: KeyBinding,
+    /// Key that sends the command to the background.
+    background_key: KeyBinding,
+    /// Key that restarts the command with the builder's current state.
+    rerun_key: KeyBinding,
+    /// Key that toggles watch mode.
+    watch_key: KeyBinding,
+    /// Keys that send SIGTERM and SIGKILL to the running command.
+    terminate_key: KeyBinding,
+    kill_key: KeyBinding,
+    /// Signals the process, when it was spawned by this component.
+    process: Option<ProcessControl>,
+    /// Output recorded for saving as a transcript.
+    transcript: Arc<Mutex<Transcript>>,
+    /// Where the transcript was saved, if it has been.
+    transcript_path: Option<PathBuf>,
+    /// Result of the last action, shown in the status bar.
+    message: Option<String>,
+    /// Working directory shown in the header, when it isn't the launch one.
+    cwd_label: Option<String>,
+    /// Where the output was last rendered, for mouse reporting.
+    output_area: Rect,
 }
 
 impl ExecutionComponent {
     pub fn new(state: ExecutionState) -> Self {
-        Self { state }
+        let size = state
+            .parser
+            .read()
+            .map(|p| p.screen().size())
+            .unwrap_or_default();
+        let transcript = Transcript::new(
+            state.command_display.clone(),
+            Vec::new(),
+            (size.1, size.0),
+        );
+        Self {
+            state,
+            launch: None,
+            earlier_runs: Vec::new(),
+            timeout: None,
+            watch: None,
+            scroll_mode: false,
+            prefix_key: KeyBindings::default().prefix,
+            prefix_pending: false,
+            scroll_key: KeyBindings::default().scroll_mode,
+            background_key: KeyBindings::default().background,
+            rerun_key: KeyBindings::default().rerun,
+            watch_key: KeyBindings::default().watch,
+            terminate_key: KeyBindings::default().terminate,
+            kill_key: KeyBindings::default().kill,
+            process: None,
+            transcript: Arc::new(Mutex::new(transcript)),
+            transcript_path: None,
+            message: None,
+            cwd_label: None,
+            output_area: Rect::default(),
+        }
     }
 
+    /// Show the directory the command runs in below it in the header.
+    pub fn with_cwd(mut self, label: Option<String>) -> Self {
+        self.cwd_label = label;
+        self
+    }
+
+    /// Use the configured keys for the prefix, scroll mode, backgrounding,
+    /// re-running, watch mode and signals.
+    pub fn with_keys(mut self, keys: &KeyBindings) -> Self {
+        self.prefix_key = keys.prefix;
+        self.scroll_key = keys.scroll_mode;
+        self.background_key = keys.background;
+        self.rerun_key = keys.rerun;
+        self.watch_key = keys.watch;
+        self.terminate_key = keys.terminate;
+        self.kill_key = keys.kill;
+        self
+    }
+
+    /// Stop the command if it runs longer than `timeout`: it is sent
+    /// SIGTERM, then SIGKILL if it is still running `kill_after` later.
+    pub fn with_timeout(mut self, timeout: Option<Duration>, kill_after: Duration) -> Self {
+        self.timeout = timeout.map(|timeout| (timeout, kill_after));
+        self.watch_timeout();
+        self
+    }
+
+    /// Start the thread enforcing the timeout on the current run.
+    fn watch_timeout(&self) {
+        if let (Some((timeout, kill_after)), Some(process)) = (self.timeout, self.process.clone()) {
+            let exited = self.state.exited.clone();
+            std::thread::spawn(move || {
+                let exited = || exited.load(Ordering::Relaxed);
+                if wait_until(exited, timeout) {
+                    return;
+                }
+                process.send(Signal::Terminate, Some(timeout));
+                if !wait_until(exited, kill_after) {
+                    process.send(Signal::Kill, Some(timeout));
+                }
+            });
+        }
+    }
+
+    /// Spawn the command in a PTY of `size` with the environment overrides
+    /// applied, keeping up to `scrollback` lines that scroll off the top of
+    /// the screen. It runs in `cwd`, or TuiSage's own working directory.
     pub fn spawn(
         command_display: String,
         parts: &[String],
-        terminal_size: Size,
+        env: &[EnvVar],
+        cwd: Option<&Path>,
+        size: Size,
+        scrollback: usize,
     ) -> color_eyre::Result<Self> {
         if parts.is_empty() {
             return Err(color_eyre::eyre::eyre!("No command to execute"));
         }
+        let launch = Launch {
+            parts: parts.to_vec(),
+            env: env.to_vec(),
+            cwd: cwd.map(Path::to_path_buf),
+            scrollback,
+        };
+        let parser = Arc::new(RwLock::new(vt100::Parser::new(
+            size.height,
+            size.width,
+            scrollback,
+        )));
+        let (state, process, transcript) = Self::start(command_display, &launch, size, parser)?;
+        let mut component = Self::new(state);
+        component.transcript = transcript;
+        component.process = Some(process);
+        component.launch = Some(launch);
+        Ok(component)
+    }
 
+    /// Start a run of the command in a new PTY of `size` whose output goes
+    /// to `parser`.
+    fn start(
+        command_display: String,
+        launch: &Launch,
+        size: Size,
+        parser: Arc<RwLock<vt100::Parser>>,
+    ) -> color_eyre::Result<(ExecutionState, ProcessControl, Arc<Mutex<Transcript>>)> {
+        let parts = &launch.parts;
         let mut cmd = CommandBuilder::new(&parts[0]);
         for arg in &parts[1..] {
             cmd.arg(arg);
         }
-        if let Ok(cwd) = std::env::current_dir() {
-            cmd.cwd(cwd);
+        match launch.cwd {
+            Some(ref dir) => cmd.cwd(dir),
+            None => {
+                if let Ok(cwd) = std::env::current_dir() {
+                    cmd.cwd(cwd);
+                }
+            }
+        }
+        for var in &launch.env {
+            match var.value {
+                Some(ref value) => cmd.env(&var.name, value),
+                None => cmd.env_remove(&var.name),
+            }
         }
 
-        let pty_size = Self::pty_size_for_terminal(terminal_size);
+        let pty_size = PtySize {
+            rows: size.height,
+            cols: size.width,
+            pixel_width: 0,
+            pixel_height: 0,
+        };
         let pty_system = NativePtySystem::default();
         let pair = pty_system
             .openpty(pty_size)
             .map_err(|e| color_eyre::eyre::eyre!("Failed to open PTY: {}", e))?;
 
-        let parser = Arc::new(RwLock::new(vt100::Parser::new(
-            pty_size.rows,
-            pty_size.cols,
-            0,
-        )));
         let exited = Arc::new(AtomicBool::new(false));
         let exit_status: Arc<Mutex<Option<String>>> = Arc::new(Mutex::new(None));
+        let transcript = Arc::new(Mutex::new(Transcript::new(
+            command_display.clone(),
+            parts.to_vec(),
+            (pty_size.cols, pty_size.rows),
+        )));
 
         let child_result = pair.slave.spawn_command(cmd);
         drop(pair.slave);
@@ -93,22 +441,29 @@ impl ExecutionComponent {
         let mut child = child_result.map_err(|e| {
             color_eyre::eyre::eyre!("Failed to spawn command '{}': {}", parts[0], e)
         })?;
+        let process = ProcessControl {
+            #[cfg(unix)]
+            pid: child.process_id(),
+            #[cfg(not(unix))]
+            killer: Arc::new(Mutex::new(child.clone_killer())),
+            exited: exited.clone(),
+            sent: Arc::new(Mutex::new(None)),
+        };
 
         {
             let exited = exited.clone();
             let exit_status = exit_status.clone();
+            let transcript = transcript.clone();
             std::thread::spawn(move || {
-                match child.wait() {
-                    Ok(status) => {
-                        if let Ok(mut s) = exit_status.lock() {
-                            *s = Some(format!("{}", status));
-                        }
-                    }
-                    Err(e) => {
-                        if let Ok(mut s) = exit_status.lock() {
-                            *s = Some(format!("error: {}", e));
-                        }
-                    }
+                let status = match child.wait() {
+                    Ok(status) => describe_exit_status(&status),
+                    Err(e) => format!("error: {}", e),
+                };
+                if let Ok(mut t) = transcript.lock() {
+                    t.finish(Some(status.clone()));
+                }
+                if let Ok(mut s) = exit_status.lock() {
+                    *s = Some(status);
                 }
                 exited.store(true, Ordering::Relaxed);
             });
@@ -121,12 +476,16 @@ impl ExecutionComponent {
 
         {
             let parser = parser.clone();
+            let transcript = transcript.clone();
             std::thread::spawn(move || {
                 let mut buf = [0u8; 8192];
                 loop {
                     match reader.read(&mut buf) {
                         Ok(0) => break,
                         Ok(size) => {
+                            if let Ok(mut t) = transcript.lock() {
+                                t.record(&buf[..size]);
+                            }
                             if let Ok(mut p) = parser.write() {
                                 p.process(&buf[..size]);
                             }
@@ -165,30 +524,169 @@ impl ExecutionComponent {
             });
         }
 
-        Ok(Self::new(ExecutionState {
+        let state = ExecutionState {
             command_display,
             parser,
             pty_writer,
             pty_master,
             exited,
             exit_status,
-        }))
+        };
+        Ok((state, process, transcript))
+    }
+
+    /// Run the same command again in a fresh PTY of the current size, after
+    /// it exited. The output is cleared, or with `append` kept above a
+    /// separator line describing the finished run.
+    pub fn run_again(&mut self, append: bool) -> color_eyre::Result<()> {
+        let Some(launch) = self.launch.clone() else {
+            return Err(color_eyre::eyre::eyre!("This command can't be run again"));
+        };
+        let (rows, cols) = self
+            .state
+            .parser
+            .read()
+            .map(|p| p.screen().size())
+            .unwrap_or((24, 80));
+        let parser = if append {
+            let separator = format!(
+                "\r\n\x1b[0m\x1b[2m── run {} · {} · {} ──\x1b[0m\r\n",
+                self.run_number(),
+                crate::jobs::short_exit_status(self.exit_summary().as_str()),
+                crate::jobs::format_elapsed(self.elapsed())
+            );
+            if let Ok(mut parser) = self.state.parser.write() {
+                parser.screen_mut().set_scrollback(0);
+                parser.process(separator.as_bytes());
+            }
+            self.state.parser.clone()
+        } else {
+            Arc::new(RwLock::new(vt100::Parser::new(rows, cols, launch.scrollback)))
+        };
+        let size = Size {
+            width: cols,
+            height: rows,
+        };
+        let (state, process, transcript) =
+            Self::start(self.state.command_display.clone(), &launch, size, parser)?;
+        self.earlier_runs.push(self.elapsed());
+        self.state = state;
+        self.process = Some(process);
+        self.transcript = transcript;
+        self.transcript_path = None;
+        self.message = None;
+        self.scroll_mode = false;
+        self.watch_timeout();
+        if let Some(ref mut watch) = self.watch {
+            watch.ran(Instant::now());
+        }
+        Ok(())
+    }
+
+    /// The directory the command runs in, when it was spawned here.
+    pub fn run_dir(&self) -> Option<PathBuf> {
+        let launch = self.launch.as_ref()?;
+        launch.cwd.clone().or_else(|| std::env::current_dir().ok())
+    }
+
+    /// Start or stop watch mode.
+    pub fn set_watch(&mut self, watch: Option<Watcher>) {
+        self.watch = watch;
+        self.message = None;
+    }
+
+    pub fn is_watching(&self) -> bool {
+        self.watch.is_some()
+    }
+
+    /// Whether watch mode wants the command to run again at `now`.
+    pub fn watch_due(&self, now: Instant) -> bool {
+        self.launch.is_some() && self.watch.as_ref().is_some_and(|w| w.due(now))
+    }
+
+    /// Show the result of an action in the status bar.
+    pub fn report(&mut self, message: String) {
+        self.message = Some(message);
+    }
+
+    /// The text on the live screen.
+    #[cfg(test)]
+    pub fn screen_text(&self) -> String {
+        self.state
+            .parser
+            .read()
+            .map(|p| p.screen().contents())
+            .unwrap_or_default()
+    }
+
+    /// Which run of the command this is, counting from 1.
+    pub fn run_number(&self) -> usize {
+        self.earlier_runs.len() + 1
+    }
+
+    /// Run counter and run times for the status bar, e.g.
+    /// `"run 3 · 4s · earlier 12s, 9s"`.
+    fn run_summary(&self) -> String {
+        let mut summary = format!(
+            "run {} · {}",
+            self.run_number(),
+            crate::jobs::format_elapsed(self.elapsed())
+        );
+        if !self.earlier_runs.is_empty() {
+            let earlier: Vec<String> = self
+                .earlier_runs
+                .iter()
+                .map(|d| crate::jobs::format_elapsed(*d))
+                .collect();
+            summary.push_str(&format!(" · earlier {}", earlier.join(", ")));
+        }
+        if self.watch.is_some() {
+            summary.push_str(" · watching");
+        }
+        summary
     }
 
     pub fn resize_to_terminal(&self, terminal_size: Size) {
-        let pty_size = Self::pty_size_for_terminal(terminal_size);
-        self.resize_pty(pty_size.rows, pty_size.cols);
+        let size = Self::output_size(terminal_size);
+        self.resize_pty(size.height, size.width);
     }
 
-    fn pty_size_for_terminal(terminal_size: Size) -> PtySize {
-        PtySize {
-            rows: terminal_size.height.saturating_sub(4).max(4),
-            cols: terminal_size.width.max(20),
-            pixel_width: 0,
-            pixel_height: 0,
+    /// Size of the output in the full-screen execution view.
+    pub fn output_size(terminal_size: Size) -> Size {
+        Size {
+            width: terminal_size.width.max(20),
+            height: terminal_size.height.saturating_sub(4).max(4),
         }
     }
 
+    /// Resize the PTY when the output is drawn in an area of another size,
+    /// e.g. after switching between the full-screen and split layouts.
+    fn fit(&self, area: Rect) {
+        let size = self
+            .state
+            .parser
+            .read()
+            .map(|p| p.screen().size())
+            .unwrap_or_default();
+        if area.width > 0 && area.height > 0 && size != (area.height, area.width) {
+            self.resize_pty(area.height, area.width);
+        }
+    }
+
+    /// The command string shown at the top.
+    pub fn command_display(&self) -> &str {
+        &self.state.command_display
+    }
+
+    /// Time since the command started, up to when it exited.
+    pub fn elapsed(&self) -> Duration {
+        let Ok(transcript) = self.transcript.lock() else {
+            return Duration::ZERO;
+        };
+        let end = transcript.ended.unwrap_or_else(std::time::SystemTime::now);
+        end.duration_since(transcript.started).unwrap_or_default()
+    }
+
     /// Whether the child process has exited.
     pub fn exited(&self) -> bool {
         self.state.exited.load(Ordering::Relaxed)
@@ -203,6 +701,83 @@ impl ExecutionComponent {
             .and_then(|s| s.clone())
     }
 
+    /// Send a signal to the command's process group, if it is running.
+    pub fn signal(&self, signal: Signal) {
+        if let Some(ref process) = self.process {
+            process.send(signal, None);
+        }
+    }
+
+    /// Send SIGTERM to the command, then SIGKILL from a background thread
+    /// if it is still running after the `grace` period. Returns right away.
+    pub fn stop(&self, grace: Duration) {
+        let Some(process) = self.process.clone().filter(|_| !self.exited()) else {
+            return;
+        };
+        process.send(Signal::Terminate, None);
+        let exited = self.state.exited.clone();
+        std::thread::spawn(move || {
+            if !wait_until(|| exited.load(Ordering::Relaxed), grace) {
+                process.send(Signal::Kill, None);
+            }
+        });
+    }
+
+    /// The last signal sent to the command.
+    pub fn signal_sent(&self) -> Option<SentSignal> {
+        self.process
+            .as_ref()
+            .and_then(|p| p.sent.lock().ok().and_then(|s| *s))
+    }
+
+    /// The exit status, with the reason when the command was stopped,
+    /// e.g. "Terminated by SIGTERM, timed out after 30s".
+    pub fn exit_summary(&self) -> String {
+        let status = self
+            .exit_status()
+            .filter(|s| !s.is_empty())
+            .unwrap_or_else(|| "unknown".to_string());
+        match self.signal_sent() {
+            Some(SentSignal {
+                timeout: Some(timeout),
+                ..
+            }) => format!(
+                "{status}, timed out after {}",
+                crate::config::HumanDuration(timeout)
+            ),
+            Some(sent) if !status.contains(sent.signal.name()) => {
+                format!("{status} after {}", sent.signal.name())
+            }
+            _ => status,
+        }
+    }
+
+    /// A copy of the output recorded so far.
+    pub fn transcript(&self) -> Transcript {
+        self.transcript
+            .lock()
+            .map(|t| t.clone())
+            .unwrap_or_else(|e| e.into_inner().clone())
+    }
+
+    /// Where the transcript was saved, if it has been.
+    pub fn transcript_path(&self) -> Option<&Path> {
+        self.transcript_path.as_deref()
+    }
+
+    /// Save the transcript to `dir` and report the result in the status bar.
+    pub fn save_transcript(&mut self, dir: &Path) -> std::io::Result<PathBuf> {
+        let result = self.transcript().save(dir);
+        match result {
+            Ok(ref path) => {
+                self.message = Some(format!("saved transcript to {}", path.display()));
+                self.transcript_path = Some(path.clone());
+            }
+            Err(ref e) => self.message = Some(format!("failed to save transcript: {e}")),
+        }
+        result
+    }
+
     /// Write bytes to the PTY (forward keyboard input).
     pub fn write_to_pty(&self, data: &[u8]) {
         if let Ok(mut writer_guard) = self.state.pty_writer.lock() {
@@ -230,37 +805,250 @@ impl ExecutionComponent {
         }
     }
 
-    /// Forward a key event to the PTY as raw bytes.
+    /// Whether keys and the mouse wheel currently scroll the output.
+    pub fn is_scrolling(&self) -> bool {
+        self.scroll_mode || self.exited()
+    }
+
+    /// Enter or leave scroll mode. Leaving returns to the live screen.
+    pub fn toggle_scroll_mode(&mut self) {
+        self.scroll_mode = !self.scroll_mode;
+        if !self.scroll_mode {
+            self.scroll_to(0);
+        }
+    }
+
+    /// Current scroll position as (lines scrolled back, lines of scrollback).
+    pub fn scroll_position(&self) -> (usize, usize) {
+        let Ok(mut parser) = self.state.parser.write() else {
+            return (0, 0);
+        };
+        let screen = parser.screen_mut();
+        let offset = screen.scrollback();
+        // vt100 only exposes the scrollback length by clamping the offset
+        screen.set_scrollback(usize::MAX);
+        let total = screen.scrollback();
+        screen.set_scrollback(offset);
+        (offset, total)
+    }
+
+    /// Scroll to `offset` lines back from the live screen (clamped).
+    fn scroll_to(&self, offset: usize) {
+        if let Ok(mut parser) = self.state.parser.write() {
+            parser.screen_mut().set_scrollback(offset);
+        }
+    }
+
+    /// Scroll back (positive) or forward (negative) by `lines`.
+    fn scroll_by(&self, lines: isize) {
+        let (offset, _) = self.scroll_position();
+        self.scroll_to(offset.saturating_add_signed(lines));
+    }
+
+    fn page_lines(&self) -> isize {
+        self.state
+            .parser
+            .read()
+            .map(|p| p.screen().size().0.saturating_sub(1).max(1) as isize)
+            .unwrap_or(1)
+    }
+
+    /// Handle the view's own keys: backgrounding, watch mode, re-running,
+    /// and while the process runs, signals and scroll mode. `None` for
+    /// other keys.
+    fn handle_view_key(&mut self, key: KeyEvent) -> Option<EventResult<ExecutionAction>> {
+        let running = !self.exited();
+        if self.background_key.matches(&key) {
+            Some(EventResult::Action(ExecutionAction::Background))
+        } else if self.watch_key.matches(&key) && self.launch.is_some() {
+            Some(EventResult::Action(ExecutionAction::ToggleWatch))
+        } else if self.rerun_key.matches(&key) {
+            Some(EventResult::Action(ExecutionAction::Rerun))
+        } else if running && self.terminate_key.matches(&key) {
+            self.signal(Signal::Terminate);
+            Some(EventResult::Consumed)
+        } else if running && self.kill_key.matches(&key) {
+            self.signal(Signal::Kill);
+            Some(EventResult::Consumed)
+        } else if running && self.scroll_key.matches(&key) {
+            self.toggle_scroll_mode();
+            Some(EventResult::Consumed)
+        } else {
+            None
+        }
+    }
+
+    /// Label of a view key while the process runs, after the prefix.
+    fn prefixed(&self, key: KeyBinding) -> String {
+        format!("{} {}", self.prefix_key.label(), key.label())
+    }
+
+    /// What the keys do after the prefix.
+    fn prefix_hint(&self) -> String {
+        let mut keys = vec![
+            format!("{} background", self.background_key.label()),
+            format!("{} stop", self.terminate_key.label()),
+            format!("{} kill", self.kill_key.label()),
+            format!("{} scroll", self.scroll_key.label()),
+            format!("{} rerun", self.rerun_key.label()),
+        ];
+        if self.launch.is_some() {
+            keys.push(format!("{} watch", self.watch_key.label()));
+        }
+        let prefix = self.prefix_key.label();
+        keys.push(format!("{prefix} sends {prefix}"));
+        format!("{prefix} — {}", keys.join(" · "))
+    }
+
+    /// Handle a scrolling key. Returns false for other keys.
+    fn handle_scroll_key(&self, key: KeyEvent) -> bool {
+        match key.code {
+            KeyCode::Up | KeyCode::Char('k') => self.scroll_by(1),
+            KeyCode::Down | KeyCode::Char('j') => self.scroll_by(-1),
+            KeyCode::PageUp | KeyCode::Char('b') => self.scroll_by(self.page_lines()),
+            KeyCode::PageDown | KeyCode::Char(' ') => self.scroll_by(-self.page_lines()),
+            KeyCode::Home | KeyCode::Char('g') => self.scroll_to(usize::MAX),
+            KeyCode::End | KeyCode::Char('G') => self.scroll_to(0),
+            _ => return false,
+        }
+        true
+    }
+
+    /// The input modes the command has set on its terminal.
+    fn input_modes(&self) -> InputModes {
+        self.state
+            .parser
+            .read()
+            .map(|p| InputModes::from_screen(p.screen()))
+            .unwrap_or_default()
+    }
+
+    /// Forward a key event to the PTY, encoded as xterm would send it.
     fn forward_key_to_pty(&self, key: KeyEvent) {
-        let bytes: Option<Vec<u8>> = match key.code {
-            KeyCode::Char(c) => {
-                if key.modifiers.contains(KeyModifiers::CONTROL) {
-                    match c {
-                        'c' => return self.write_to_pty(b"\x03"),
-                        'd' => return self.write_to_pty(b"\x04"),
-                        _ => {}
-                    }
-                }
-                let mut buf = [0u8; 4];
-                let s = c.encode_utf8(&mut buf);
-                Some(s.as_bytes().to_vec())
+        if let Some(data) = encode_key(key, &self.input_modes()) {
+            self.write_to_pty(&data);
+        }
+    }
+
+    /// Forward pasted text to the running process, bracketed if it enabled
+    /// bracketed paste. Ignored while scrolling or after the process exited.
+    pub fn paste(&self, text: &str) {
+        if !self.is_scrolling() {
+            self.write_to_pty(&encode_paste(text, &self.input_modes()));
+        }
+    }
+
+    /// Forward a mouse event over the output to the process, if it enabled
+    /// mouse reporting. Returns whether the event was forwarded.
+    fn forward_mouse_to_pty(&self, event: MouseEvent) -> bool {
+        let area = self.output_area;
+        let inside = event.column >= area.x
+            && event.column < area.right()
+            && event.row >= area.y
+            && event.row < area.bottom();
+        if self.is_scrolling() || !inside {
+            return false;
+        }
+        let encoded = encode_mouse(
+            event,
+            event.column - area.x,
+            event.row - area.y,
+            &self.input_modes(),
+        );
+        match encoded {
+            Some(data) => {
+                self.write_to_pty(&data);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+impl ExecutionComponent {
+    /// Render the output in a bordered pane next to the builder, titled
+    /// with the command and its status. Keys reach the command only while
+    /// the pane is `focused`.
+    pub fn render_pane(&mut self, area: Rect, buf: &mut Buffer, colors: &UiColors, focused: bool) {
+        let border_color = if focused {
+            colors.active_border
+        } else {
+            colors.inactive_border
+        };
+        let exited = self.exited();
+        let status = if let (true, Some(watch)) = (exited, &self.watch) {
+            Span::styled(
+                format!(
+                    " {} · waiting for {} ",
+                    self.exit_summary(),
+                    watch.describe(Instant::now())
+                ),
+                Style::default().fg(colors.help),
+            )
+        } else if exited {
+            Span::styled(
+                format!(" {} ", self.exit_summary()),
+                Style::default().fg(colors.help),
+            )
+        } else if let Some(sent) = self.signal_sent() {
+            Span::styled(
+                format!(" stopping ({}) ", sent.signal.name()),
+                Style::default().fg(colors.required),
+            )
+        } else {
+            Span::styled(" ● running ", Style::default().fg(colors.command))
+        };
+        let (scroll_offset, scroll_total) = self.scroll_position();
+        let mut position = format!(" {} ", self.run_summary());
+        if self.is_scrolling() && scroll_offset > 0 {
+            position.push_str(&format!("· ↑{scroll_offset}/{scroll_total} "));
+        }
+        let hint = match (focused, exited) {
+            (false, _) => " Tab to focus ".to_string(),
+            (true, true) if self.launch.is_some() => {
+                " ⏎/Esc close · r run again · e edit · s save ".to_string()
             }
-            KeyCode::Enter => Some(b"\r".to_vec()),
-            KeyCode::Backspace => Some(b"\x7f".to_vec()),
-            KeyCode::Tab => Some(b"\t".to_vec()),
-            KeyCode::Esc => Some(b"\x1b".to_vec()),
-            KeyCode::Up => Some(b"\x1b[A".to_vec()),
-            KeyCode::Down => Some(b"\x1b[B".to_vec()),
-            KeyCode::Right => Some(b"\x1b[C".to_vec()),
-            KeyCode::Left => Some(b"\x1b[D".to_vec()),
-            KeyCode::Home => Some(b"\x1b[H".to_vec()),
-            KeyCode::End => Some(b"\x1b[F".to_vec()),
-            KeyCode::Delete => Some(b"\x1b[3~".to_vec()),
-            _ => None,
+            (true, true) => " ⏎/Esc close · e edit · s save ".to_string(),
+            (true, false) if self.scroll_mode => " Esc to return to the process ".to_string(),
+            (true, false) if self.prefix_pending => format!(" {} ", self.prefix_hint()),
+            (true, false) => format!(
+                " keys go to the process · Tab leaves · {} scroll · {} stop ",
+                self.prefixed(self.scroll_key),
+                self.prefixed(self.terminate_key)
+            ),
         };
 
-        if let Some(data) = bytes {
-            self.write_to_pty(&data);
+        let mut cwd = String::new();
+        if let Some(ref label) = self.cwd_label {
+            cwd = format!(" in {label} ");
+        }
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(border_color))
+            .title(Line::from(vec![
+                Span::styled(
+                    format!(" $ {} ", self.state.command_display),
+                    Style::default().fg(border_color).bold(),
+                ),
+                status,
+            ]))
+            .title(Line::from(position).right_aligned())
+            .title_bottom(Line::from(Span::styled(hint, Style::default().fg(colors.help))))
+            .title_bottom(Line::from(Span::styled(cwd, Style::default().fg(colors.help))).right_aligned());
+        let inner = block.inner(area);
+        Widget::render(block, area, buf);
+
+        self.output_area = inner;
+        self.fit(inner);
+        if let Ok(parser) = self.state.parser.read() {
+            let mut cursor = Cursor::default();
+            if !focused || scroll_offset > 0 {
+                cursor.hide();
+            }
+            let pseudo_term = PseudoTerminal::new(parser.screen())
+                .cursor(cursor)
+                .style(Style::default().fg(colors.preview_cmd).bg(colors.bg));
+            Widget::render(pseudo_term, inner, buf);
         }
     }
 }
@@ -269,21 +1057,83 @@ impl Component for ExecutionComponent {
     type Action = ExecutionAction;
 
     fn handle_key(&mut self, key: KeyEvent) -> EventResult<ExecutionAction> {
+        let after_prefix = std::mem::take(&mut self.prefix_pending);
         if self.exited() {
-            match key.code {
+            if let Some(result) = self.handle_view_key(key) {
+                return result;
+            }
+            return match key.code {
                 KeyCode::Esc | KeyCode::Enter | KeyCode::Char('q') => {
                     EventResult::Action(ExecutionAction::Close)
                 }
-                _ => EventResult::Consumed,
+                KeyCode::Char('s') => EventResult::Action(ExecutionAction::SaveTranscript),
+                KeyCode::Char('r') if self.launch.is_some() => {
+                    EventResult::Action(ExecutionAction::RunAgain)
+                }
+                KeyCode::Char('e') => EventResult::Action(ExecutionAction::Edit),
+                _ => {
+                    self.handle_scroll_key(key);
+                    EventResult::Consumed
+                }
+            };
+        }
+        if after_prefix {
+            if self.prefix_key.matches(&key) {
+                // Pressed twice, the prefix goes to the process
+                self.forward_key_to_pty(key);
+                return EventResult::Consumed;
+            }
+            // Any other key just cancels the prefix
+            return self.handle_view_key(key).unwrap_or(EventResult::Consumed);
+        }
+        if self.scroll_mode {
+            // No keys go to the process, so the prefix isn't needed
+            if let Some(result) = self.handle_view_key(key) {
+                return result;
             }
+            if matches!(key.code, KeyCode::Esc | KeyCode::Char('q')) {
+                self.toggle_scroll_mode();
+            } else {
+                self.handle_scroll_key(key);
+            }
+            EventResult::Consumed
+        } else if self.prefix_key.matches(&key) {
+            self.prefix_pending = true;
+            EventResult::Consumed
+        } else if key.code == KeyCode::PageUp && key.modifiers.contains(KeyModifiers::SHIFT) {
+            // Like most terminals, Shift+PageUp scrolls back
+            self.scroll_mode = true;
+            self.scroll_by(self.page_lines());
+            EventResult::Consumed
         } else {
             self.forward_key_to_pty(key);
             EventResult::Consumed
         }
     }
 
-    fn handle_mouse(&mut self, _event: MouseEvent, _area: Rect) -> EventResult<ExecutionAction> {
-        EventResult::NotHandled
+    fn handle_mouse(&mut self, event: MouseEvent, _area: Rect) -> EventResult<ExecutionAction> {
+        // Programs that enabled mouse reporting get the events themselves
+        if self.forward_mouse_to_pty(event) {
+            return EventResult::Consumed;
+        }
+        match event.kind {
+            MouseEventKind::ScrollUp => {
+                if !self.exited() {
+                    self.scroll_mode = true;
+                }
+                self.scroll_by(WHEEL_SCROLL_LINES as isize);
+                EventResult::Consumed
+            }
+            MouseEventKind::ScrollDown => {
+                self.scroll_by(-(WHEEL_SCROLL_LINES as isize));
+                // Scrolling back down to the live screen resumes input
+                if self.scroll_mode && self.scroll_position().0 == 0 {
+                    self.scroll_mode = false;
+                }
+                EventResult::Consumed
+            }
+            _ => EventResult::NotHandled,
+        }
     }
 }
 
@@ -299,11 +1149,17 @@ impl RenderableComponent for ExecutionComponent {
             .split(area);
 
         // --- Command display at top ---
-        let cmd_block = Block::default()
+        let mut cmd_block = Block::default()
             .borders(Borders::ALL)
             .border_style(Style::default().fg(colors.active_border))
             .title(" Command ")
             .title_style(Style::default().fg(colors.active_border).bold());
+        if let Some(ref label) = self.cwd_label {
+            cmd_block = cmd_block.title_bottom(Line::from(Span::styled(
+                format!(" in {label} "),
+                Style::default().fg(colors.help),
+            )));
+        }
 
         let cmd_spans = vec![
             Span::styled("$ ", Style::default().fg(colors.command)),
@@ -322,9 +1178,18 @@ impl RenderableComponent for ExecutionComponent {
         // --- Terminal output ---
         let term_block = Block::default().borders(Borders::NONE);
 
+        self.output_area = outer[1];
+        self.fit(outer[1]);
+        let (scroll_offset, scroll_total) = self.scroll_position();
         if let Ok(parser) = self.state.parser.read() {
+            let mut cursor = Cursor::default();
+            if scroll_offset > 0 {
+                // The cursor position refers to the live screen
+                cursor.hide();
+            }
             let pseudo_term = PseudoTerminal::new(parser.screen())
                 .block(term_block)
+                .cursor(cursor)
                 .style(Style::default().fg(colors.preview_cmd).bg(colors.bg));
             Widget::render(pseudo_term, outer[1], buf);
         } else {
@@ -336,18 +1201,54 @@ impl RenderableComponent for ExecutionComponent {
 
         // --- Status bar at bottom ---
         let exited = self.exited();
-        let status_text = if exited {
-            let exit_code = self.exit_status().unwrap_or_default();
+        let status_text = if let (true, Some(watch), None) = (exited, &self.watch, &self.message) {
             format!(
-                " Exited ({}) — press Esc/⏎/q to close ",
-                if exit_code.is_empty() {
-                    "unknown".to_string()
-                } else {
-                    exit_code
-                }
+                " Exited ({}) — waiting for {} ({} to stop watching, Esc/⏎/q to close) ",
+                self.exit_summary(),
+                watch.describe(Instant::now()),
+                self.watch_key.label()
+            )
+        } else if exited {
+            let again = if self.launch.is_some() {
+                format!("r to run again, {} to watch, ", self.watch_key.label())
+            } else {
+                String::new()
+            };
+            let hint = format!(
+                "press Esc/⏎/q to close, {again}e to edit, PgUp/PgDn to scroll, s to save the transcript"
+            );
+            format!(
+                " Exited ({}) — {} ",
+                self.exit_summary(),
+                self.message.as_deref().unwrap_or(&hint)
+            )
+        } else if let Some(sent) = self.signal_sent() {
+            let reason = match sent.timeout {
+                Some(timeout) => format!(
+                    "timed out after {}, ",
+                    crate::config::HumanDuration(timeout)
+                ),
+                None => String::new(),
+            };
+            format!(
+                " Stopping… ({reason}sent {}, {} to kill) ",
+                sent.signal.name(),
+                self.prefixed(self.kill_key)
+            )
+        } else if self.scroll_mode {
+            format!(
+                " Scrolling — PgUp/PgDn, ↑/↓, Home/End; Esc or {} to return to the process ",
+                self.scroll_key.label()
             )
+        } else if self.prefix_pending {
+            format!(" {} ", self.prefix_hint())
         } else {
-            " Running… (input is forwarded to the process) ".to_string()
+            format!(
+                " Running… (input is forwarded to the process, {} to scroll, {} to background, {} to stop) ",
+                self.prefixed(self.scroll_key),
+                self.prefixed(self.background_key),
+                self.prefixed(self.terminate_key)
+            )
         };
 
         let status_style = if exited {
@@ -360,7 +1261,29 @@ impl RenderableComponent for ExecutionComponent {
                 .add_modifier(RatModifier::BOLD | RatModifier::REVERSED)
         };
 
+        // Run counter, run times and scroll position at the right of the status bar
+        let mut position = format!(" {} ", self.run_summary());
+        if self.is_scrolling() && scroll_total > 0 {
+            if scroll_offset == 0 {
+                position.push_str(&format!("· bottom of {scroll_total} "));
+            } else {
+                position.push_str(&format!("· ↑{scroll_offset}/{scroll_total} "));
+            }
+        }
+        let status_areas = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([
+                Constraint::Min(0),
+                Constraint::Length(position.chars().count() as u16),
+            ])
+            .split(outer[2]);
+
         let status = Paragraph::new(status_text).style(status_style);
-        Widget::render(status, outer[2], buf);
+        Widget::render(status, status_areas[0], buf);
+        Widget::render(
+            Paragraph::new(position).style(status_style),
+            status_areas[1],
+            buf,
+        );
     }
 }
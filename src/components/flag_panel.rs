@@ -17,12 +17,15 @@ use ratatui::{
 
 use crate::app::FlagValue;
 use crate::theme::UiColors;
+use crate::validation::{Diagnostic, Target};
 
-use super::list_panel_base::{ChoiceEvent, EditEvent, FocusLostEvent, ListPanelBase, MouseResult};
+use super::list_panel_base::{
+    ChoiceEvent, EditEvent, FocusLostEvent, ListEvent, ListPanelBase, MouseResult,
+};
 use super::filterable::{FilterableItem, Filterable};
 use super::{
-    build_help_line, panel_block, panel_title, push_edit_cursor, push_highlighted_name,
-    push_selection_cursor, render_help_overlays, render_panel_scrollbar, selection_bg, Component,
+    build_help_line, panel_block, panel_title, push_diagnostics, push_edit_cursor,
+    push_highlighted_name, push_selection_cursor, render_help_overlays, render_panel_scrollbar, selection_bg, Component,
     EventResult, ItemContext, OverlayRequest, PanelState,
 };
 
@@ -48,6 +51,8 @@ pub enum FlagPanelAction {
     ValueChanged { index: usize, value: String },
     /// Inline edit finished (Enter/Esc committed the value).
     EditFinished { index: usize, value: String },
+    /// List editor changed the values of a repeatable flag at the given index.
+    ListChanged { index: usize, values: Vec<String> },
 }
 
 /// Panel-owned interpretation of Enter on a flag row.
@@ -66,6 +71,12 @@ pub enum FlagPanelEnterRequest {
         current_value: String,
         value_column: u16,
     },
+    ListEdit {
+        index: usize,
+        values: Vec<String>,
+        max: Option<usize>,
+        value_column: u16,
+    },
 }
 
 // ── FlagPanelComponent ──────────────────────────────────────────────
@@ -246,6 +257,36 @@ impl FlagPanelComponent {
         self.base.editing_text()
     }
 
+    // ── List editor (delegated) ─────────────────────────────────────
+
+    pub fn is_list_editing(&self) -> bool {
+        self.base.is_list_editing()
+    }
+
+    /// Whether an entry in the open list editor is being added or edited.
+    pub fn is_list_entry_editing(&self) -> bool {
+        self.base.is_list_entry_editing()
+    }
+
+    pub fn open_list_editor(
+        &mut self,
+        index: usize,
+        values: Vec<String>,
+        max: Option<usize>,
+        value_column: u16,
+    ) {
+        self.base.open_list_editor(index, values, max, value_column);
+    }
+
+    pub fn handle_list_editor_click(
+        &mut self,
+        col: u16,
+        row: u16,
+        overlay_rect: Option<Rect>,
+    ) -> EventResult<FlagPanelAction> {
+        Self::map_list_event(self.base.handle_list_editor_click(col, row, overlay_rect))
+    }
+
     fn build_enter_request(
         index: usize,
         flags: &[&usage::SpecFlag],
@@ -257,11 +298,20 @@ impl FlagPanelComponent {
             .find(|(name, _)| name == &flag.name)
             .map(|(_, value)| value);
 
-        let FlagValue::String(current_value) = value?.clone() else {
-            return Some(FlagPanelEnterRequest::Toggle);
+        let value_column = Self::value_column_for_flag(flag, value);
+        let current_value = match value?.clone() {
+            FlagValue::String(current_value) => current_value,
+            FlagValue::Multi(values) => {
+                return Some(FlagPanelEnterRequest::ListEdit {
+                    index,
+                    values,
+                    max: flag.var_max,
+                    value_column,
+                })
+            }
+            _ => return Some(FlagPanelEnterRequest::Toggle),
         };
 
-        let value_column = Self::value_column_for_flag(flag, value);
         if let Some(choices) = flag
             .arg
             .as_ref()
@@ -299,6 +349,7 @@ impl FlagPanelComponent {
         // indicator width: "✓ "=2, "○ "=2, "[n] "=varies, "[·] "=4, "[•] "=4
         let indicator_width = match value {
             Some(FlagValue::Count(n)) => format!("[{}] ", n).chars().count(),
+            Some(FlagValue::Multi(values)) => format!("[{}] ", values.len()).chars().count(),
             Some(FlagValue::String(_)) => 4,
             _ => 2,
         };
@@ -343,6 +394,15 @@ impl FlagPanelComponent {
         }
     }
 
+    fn map_list_event(event: ListEvent) -> EventResult<FlagPanelAction> {
+        match event {
+            ListEvent::Changed { index, values } => {
+                EventResult::Action(FlagPanelAction::ListChanged { index, values })
+            }
+            ListEvent::Consumed => EventResult::Consumed,
+        }
+    }
+
     // ── Rendering ───────────────────────────────────────────────────
 
     pub fn render_with_data(
@@ -402,6 +462,7 @@ impl FlagPanelComponent {
             flags: data.flags,
             flag_values: data.flag_values,
             flag_defaults: data.flag_defaults,
+            diagnostics: data.diagnostics,
             flag_index: self.base.list_state.selected_index,
             scroll_offset: self.base.list_state.scroll as usize,
             hovered_index: self.base.hovered_index,
@@ -414,22 +475,14 @@ impl FlagPanelComponent {
         };
         panel.render(area, buf);
 
-        // Update choice_select anchor now that we know the panel area
-        if self.base.choice_select.is_open() {
-            if let Some(cs_idx) = self.base.choice_select_index {
-                let scroll = self.base.list_state.scroll as usize;
-                let row_in_viewport = cs_idx.saturating_sub(scroll);
-                let anchor_y = area.y + 1 + row_in_viewport as u16;
-                let anchor_x = area.x + self.base.value_column.saturating_sub(1);
-                let anchor = Rect::new(
-                    anchor_x,
-                    anchor_y,
-                    area.width
-                        .saturating_sub(self.base.value_column.saturating_sub(1)),
-                    1,
-                );
-                self.base.choice_select.set_anchor(anchor);
-            }
+        // Update overlay anchors now that we know the panel area
+        if let Some(cs_idx) = self.base.choice_select_index {
+            let anchor = self.base.value_anchor(area, cs_idx);
+            self.base.choice_select.set_anchor(anchor);
+        }
+        if let Some(list_idx) = self.base.list_editor_index {
+            let anchor = self.base.value_anchor(area, list_idx);
+            self.base.list_editor.set_anchor(anchor);
         }
     }
 
@@ -463,12 +516,20 @@ impl Component for FlagPanelComponent {
             FocusLostEvent::EditFinished { index, value } => {
                 EventResult::Action(FlagPanelAction::EditFinished { index, value })
             }
+            FocusLostEvent::ListChanged { index, values } => {
+                EventResult::Action(FlagPanelAction::ListChanged { index, values })
+            }
             FocusLostEvent::Consumed => EventResult::Consumed,
             FocusLostEvent::NotHandled => EventResult::NotHandled,
         }
     }
 
     fn handle_key(&mut self, key: KeyEvent) -> EventResult<FlagPanelAction> {
+        // Delegate to the list editor when open
+        if self.base.is_list_editing() {
+            return Self::map_list_event(self.base.handle_list_editor_key(key));
+        }
+
         // Delegate to choice select when open
         if self.base.is_choosing() {
             match self.base.handle_choice_key(key) {
@@ -620,6 +681,8 @@ pub struct FlagRenderData<'a> {
     pub flags: &'a [&'a usage::SpecFlag],
     pub flag_values: &'a [(String, FlagValue)],
     pub flag_defaults: &'a [Option<String>],
+    /// Validation problems of the current command, shown next to their flags.
+    pub diagnostics: &'a [Diagnostic],
 }
 
 // ── FlagPanel Widget (rendering) ────────────────────────────────────
@@ -630,6 +693,7 @@ struct FlagPanel<'a> {
     flags: &'a [&'a usage::SpecFlag],
     flag_values: &'a [(String, FlagValue)],
     flag_defaults: &'a [Option<String>],
+    diagnostics: &'a [Diagnostic],
     flag_index: usize,
     scroll_offset: usize,
     hovered_index: Option<usize>,
@@ -706,8 +770,23 @@ impl Widget for FlagPanel<'_> {
                 }
 
                 // Value display for string flags
-                if let Some((_, FlagValue::String(s))) = value {
-                    self.render_string_value(&mut spans, s, flag, default_val, is_editing, i);
+                match value {
+                    Some((_, FlagValue::String(s))) => {
+                        self.render_string_value(&mut spans, s, flag, default_val, is_editing, i);
+                    }
+                    Some((_, FlagValue::Multi(values))) => {
+                        render_multi_value(&mut spans, values, flag, colors);
+                    }
+                    _ => {}
+                }
+
+                if !is_editing && self.choice_select_flag_index != Some(i) {
+                    let messages = self
+                        .diagnostics
+                        .iter()
+                        .filter(|d| matches!(&d.target, Target::Flag(name) if name == &flag.name))
+                        .map(|d| d.message.as_str());
+                    push_diagnostics(&mut spans, messages, colors);
                 }
 
                 // Collect help text for overlay
@@ -796,6 +875,31 @@ impl FlagPanel<'_> {
     }
 }
 
+/// Render the values of a repeatable flag, or its argument name when empty.
+fn render_multi_value(
+    spans: &mut Vec<Span<'static>>,
+    values: &[String],
+    flag: &usage::SpecFlag,
+    colors: &UiColors,
+) {
+    spans.push(Span::styled(" = ", Style::default().fg(colors.help)));
+    if values.is_empty() {
+        if let Some(ref arg) = flag.arg {
+            spans.push(Span::styled(
+                format!("<{}>…", arg.name),
+                Style::default().fg(colors.default_val),
+            ));
+        }
+        return;
+    }
+    for (i, value) in values.iter().enumerate() {
+        if i > 0 {
+            spans.push(Span::styled(", ", Style::default().fg(colors.help)));
+        }
+        spans.push(Span::styled(value.clone(), Style::default().fg(colors.value)));
+    }
+}
+
 /// Build the flag display string (e.g. "-v, --verbose").
 pub fn flag_display_string(flag: &usage::SpecFlag) -> String {
     let mut parts = Vec::new();
@@ -838,6 +942,13 @@ fn render_flag_indicator<'a>(value: Option<&FlagValue>, colors: &UiColors) -> Sp
                 Span::styled("[0] ".to_string(), Style::default().fg(colors.help))
             }
         }
+        Some(FlagValue::Multi(values)) => {
+            if values.is_empty() {
+                Span::styled("[0] ".to_string(), Style::default().fg(colors.help))
+            } else {
+                Span::styled(format!("[{}] ", values.len()), Style::default().fg(colors.count))
+            }
+        }
         Some(FlagValue::String(s)) => {
             if s.is_empty() {
                 Span::styled("[·] ", Style::default().fg(colors.help))
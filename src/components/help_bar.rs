@@ -20,10 +20,13 @@ pub struct Keybind<'a> {
 /// A widget that renders the context-sensitive help/status bar.
 ///
 /// Shows keyboard shortcuts for the current mode on the left and
-/// the active theme indicator on the right.
+/// the active theme indicator on the right. A transient status message,
+/// when set, replaces the shortcuts.
 pub struct HelpBar<'a> {
     /// Structured key/description pairs to display.
     pub keybinds: &'a [Keybind<'a>],
+    /// Status message shown instead of the keybinds.
+    pub message: Option<&'a str>,
     /// Theme display name for the right-aligned indicator.
     pub theme_display: &'a str,
     pub colors: &'a UiColors,
@@ -33,11 +36,18 @@ impl<'a> HelpBar<'a> {
     pub fn new(keybinds: &'a [Keybind<'a>], theme_display: &'a str, colors: &'a UiColors) -> Self {
         Self {
             keybinds,
+            message: None,
             theme_display,
             colors,
         }
     }
 
+    /// Show a status message instead of the keybinds.
+    pub fn with_message(mut self, message: Option<&'a str>) -> Self {
+        self.message = message;
+        self
+    }
+
     /// Returns the Rect where the theme indicator is rendered,
     /// for use in mouse click hit-testing.
     pub fn theme_indicator_rect(&self, area: Rect) -> Rect {
@@ -76,7 +86,14 @@ impl Widget for HelpBar<'_> {
         let theme_indicator = format!("T: [{}] ", self.theme_display);
         let theme_indicator_len = theme_indicator.len() as u16;
 
-        let (mut spans, keybinds_len) = self.styled_keybind_spans();
+        let (mut spans, keybinds_len) = match self.message {
+            Some(message) => {
+                let text = format!(" {message}");
+                let len = text.chars().count() as u16;
+                (vec![Span::styled(text, Style::default().fg(self.colors.value))], len)
+            }
+            None => self.styled_keybind_spans(),
+        };
         let padding_len = area.width.saturating_sub(keybinds_len + theme_indicator_len);
         let padding = " ".repeat(padding_len as usize);
 
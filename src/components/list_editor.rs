@@ -0,0 +1,604 @@
+//! List editor overlay component.
+//!
+//! Edits an ordered list of values — the repeated values of a `var=#true`
+//! flag or the values of a variadic argument — in a dropdown anchored below
+//! the row being edited. Entries can be
+//! added, edited, removed and reordered; every change is reported to the
+//! parent with the complete list.
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseEvent};
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph, Widget},
+};
+use ratatui_interact::components::InputState;
+
+use super::{
+    push_edit_cursor, scroll_offset, Component, EventResult, OverlayContent, OverlayRequest,
+};
+use crate::theme::UiColors;
+
+/// Maximum number of entries shown before the list scrolls.
+const MAX_VISIBLE: usize = 10;
+
+/// Actions emitted by the list editor for the parent to process.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ListEditorAction {
+    /// An entry was added, edited, removed or moved. Contains the full list.
+    Changed(Vec<String>),
+    /// The editor was closed.
+    Closed,
+}
+
+/// Self-contained list editor overlay.
+pub struct ListEditorComponent {
+    state: Option<ListEditorInner>,
+}
+
+struct ListEditorInner {
+    values: Vec<String>,
+    selected_index: usize,
+    /// Inline input while an entry is being added or edited.
+    input: Option<InputState>,
+    /// Whether the entry being edited was just added (dropped again on Esc).
+    adding: bool,
+    /// Maximum number of entries (`var_max`), if limited.
+    max: Option<usize>,
+    /// Anchor point for overlay positioning (set by parent panel).
+    anchor: Rect,
+}
+
+impl ListEditorInner {
+    /// Insert an empty entry after the selection and start editing it,
+    /// unless the list is already full.
+    fn start_adding(&mut self) {
+        if self.max.is_some_and(|max| self.values.len() >= max) {
+            return;
+        }
+        let at = if self.values.is_empty() {
+            0
+        } else {
+            self.selected_index + 1
+        };
+        self.values.insert(at, String::new());
+        self.selected_index = at;
+        self.input = Some(InputState::empty());
+        self.adding = true;
+    }
+
+    fn start_editing(&mut self) {
+        if self.values.is_empty() {
+            self.start_adding();
+            return;
+        }
+        self.input = Some(InputState::new(&self.values[self.selected_index]));
+        self.adding = false;
+    }
+
+    /// Store the edited text. A new entry left empty is dropped instead.
+    /// Returns whether the list changed.
+    fn commit_edit(&mut self) -> bool {
+        let Some(input) = self.input.take() else {
+            return false;
+        };
+        let text = input.text().to_string();
+        if self.adding && text.is_empty() {
+            self.drop_added();
+            return false;
+        }
+        let changed = self.adding || self.values[self.selected_index] != text;
+        self.values[self.selected_index] = text;
+        self.adding = false;
+        changed
+    }
+
+    fn cancel_edit(&mut self) {
+        if self.input.take().is_some() && self.adding {
+            self.drop_added();
+        }
+    }
+
+    fn drop_added(&mut self) {
+        self.values.remove(self.selected_index);
+        self.selected_index = self.selected_index.saturating_sub(1);
+        self.adding = false;
+    }
+
+    /// Swap the selected entry with the one above or below it.
+    fn move_selected(&mut self, up: bool) -> bool {
+        let from = self.selected_index;
+        let to = if up {
+            from.checked_sub(1)
+        } else {
+            Some(from + 1).filter(|&i| i < self.values.len())
+        };
+        let Some(to) = to else {
+            return false;
+        };
+        self.values.swap(from, to);
+        self.selected_index = to;
+        true
+    }
+
+    fn handle_editing_key(&mut self, key: KeyEvent) -> EventResult<ListEditorAction> {
+        match key.code {
+            KeyCode::Enter => {
+                if self.commit_edit() {
+                    return EventResult::Action(ListEditorAction::Changed(self.values.clone()));
+                }
+                return EventResult::Consumed;
+            }
+            KeyCode::Esc => {
+                self.cancel_edit();
+                return EventResult::Consumed;
+            }
+            _ => {}
+        }
+        let Some(ref mut input) = self.input else {
+            return EventResult::NotHandled;
+        };
+        match key.code {
+            KeyCode::Char('u') if key.modifiers.contains(KeyModifiers::CONTROL) => input.clear(),
+            KeyCode::Char('w') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                input.delete_word_backward();
+            }
+            KeyCode::Char(_) if key.modifiers.contains(KeyModifiers::CONTROL) => {}
+            KeyCode::Char(c) => input.insert_char(c),
+            KeyCode::Backspace => {
+                input.delete_char_backward();
+            }
+            KeyCode::Delete => {
+                input.delete_char_forward();
+            }
+            KeyCode::Left => input.move_left(),
+            KeyCode::Right => input.move_right(),
+            KeyCode::Home => input.move_home(),
+            KeyCode::End => input.move_end(),
+            _ => {}
+        }
+        EventResult::Consumed
+    }
+}
+
+impl ListEditorComponent {
+    pub fn new() -> Self {
+        Self { state: None }
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.state.is_some()
+    }
+
+    /// Open the editor with the current values, allowing at most `max`
+    /// entries. An empty list starts straight away with a new entry.
+    pub fn open(&mut self, values: Vec<String>, max: Option<usize>, anchor: Rect) {
+        let mut inner = ListEditorInner {
+            values,
+            selected_index: 0,
+            input: None,
+            adding: false,
+            max,
+            anchor,
+        };
+        if inner.values.is_empty() {
+            inner.start_adding();
+        }
+        self.state = Some(inner);
+    }
+
+    pub fn close(&mut self) {
+        self.state = None;
+    }
+
+    /// Commit any entry still being edited and close the editor.
+    /// Returns the final list if that edit changed it.
+    pub fn finish(&mut self) -> Option<Vec<String>> {
+        let mut inner = self.state.take()?;
+        inner.commit_edit().then_some(inner.values)
+    }
+
+    /// Whether an entry is being added or edited.
+    pub fn is_editing_entry(&self) -> bool {
+        self.state.as_ref().is_some_and(|s| s.input.is_some())
+    }
+
+    /// Update the anchor rect for overlay positioning.
+    /// Called during rendering when the actual panel area is known.
+    pub fn set_anchor(&mut self, anchor: Rect) {
+        if let Some(ref mut inner) = self.state {
+            inner.anchor = anchor;
+        }
+    }
+
+    #[cfg(test)]
+    pub fn values(&self) -> Option<&[String]> {
+        self.state.as_ref().map(|s| s.values.as_slice())
+    }
+
+    #[cfg(test)]
+    pub fn selected_index(&self) -> Option<usize> {
+        self.state.as_ref().map(|s| s.selected_index)
+    }
+
+    /// Handle a mouse click. Clicking an entry selects it; clicking outside
+    /// the overlay commits any pending edit and closes the editor.
+    pub fn click_at(
+        &mut self,
+        col: u16,
+        row: u16,
+        overlay_rect: Option<Rect>,
+    ) -> Option<ListEditorAction> {
+        let inner = self.state.as_mut()?;
+
+        if let Some(rect) = overlay_rect {
+            let inside = col >= rect.x
+                && col < rect.x + rect.width
+                && row >= rect.y
+                && row < rect.y + rect.height;
+            if inside {
+                if inner.input.is_none() {
+                    let scroll = scroll_offset(inner.selected_index, visible_rows(rect));
+                    let idx = (row - rect.y) as usize + scroll;
+                    if idx < inner.values.len() {
+                        inner.selected_index = idx;
+                    }
+                }
+                return None;
+            }
+        }
+
+        Some(match self.finish() {
+            Some(values) => ListEditorAction::Changed(values),
+            None => ListEditorAction::Closed,
+        })
+    }
+}
+
+/// Rows available for entries (the overlay has no top border).
+fn visible_rows(area: Rect) -> usize {
+    area.height.saturating_sub(1) as usize
+}
+
+impl Component for ListEditorComponent {
+    type Action = ListEditorAction;
+
+    fn handle_key(&mut self, key: KeyEvent) -> EventResult<Self::Action> {
+        let Some(ref mut inner) = self.state else {
+            return EventResult::NotHandled;
+        };
+
+        if inner.input.is_some() {
+            return inner.handle_editing_key(key);
+        }
+
+        let len = inner.values.len();
+        let shift = key.modifiers.contains(KeyModifiers::SHIFT);
+
+        // K/J or Shift+↑/↓ move the selected entry
+        let move_up = match key.code {
+            KeyCode::Char('K') => Some(true),
+            KeyCode::Char('J') => Some(false),
+            KeyCode::Up if shift => Some(true),
+            KeyCode::Down if shift => Some(false),
+            _ => None,
+        };
+        if let Some(up) = move_up {
+            if inner.move_selected(up) {
+                return EventResult::Action(ListEditorAction::Changed(inner.values.clone()));
+            }
+            return EventResult::Consumed;
+        }
+
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => {
+                self.close();
+                EventResult::Action(ListEditorAction::Closed)
+            }
+            KeyCode::Char('a') | KeyCode::Char('+') | KeyCode::Insert => {
+                inner.start_adding();
+                EventResult::Consumed
+            }
+            KeyCode::Enter | KeyCode::Char('e') => {
+                inner.start_editing();
+                EventResult::Consumed
+            }
+            KeyCode::Char('d') | KeyCode::Delete | KeyCode::Backspace if len > 0 => {
+                inner.values.remove(inner.selected_index);
+                inner.selected_index = inner.selected_index.min(len.saturating_sub(2));
+                EventResult::Action(ListEditorAction::Changed(inner.values.clone()))
+            }
+            KeyCode::Up | KeyCode::Char('k') => {
+                inner.selected_index = inner.selected_index.saturating_sub(1);
+                EventResult::Consumed
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                if inner.selected_index + 1 < len {
+                    inner.selected_index += 1;
+                }
+                EventResult::Consumed
+            }
+            KeyCode::Home => {
+                inner.selected_index = 0;
+                EventResult::Consumed
+            }
+            KeyCode::End => {
+                inner.selected_index = len.saturating_sub(1);
+                EventResult::Consumed
+            }
+            _ => EventResult::Consumed,
+        }
+    }
+
+    fn handle_mouse(&mut self, _event: MouseEvent, _area: Rect) -> EventResult<Self::Action> {
+        EventResult::NotHandled
+    }
+
+    fn collect_overlays(&mut self) -> Vec<OverlayRequest> {
+        let Some(ref inner) = self.state else {
+            return vec![];
+        };
+
+        let editing_len = inner.input.as_ref().map(|i| i.text().chars().count() + 1);
+        let widest = inner
+            .values
+            .iter()
+            .map(|v| v.chars().count())
+            .chain(editing_len)
+            .max()
+            .unwrap_or(0)
+            .max(16) as u16;
+        // "▶ " prefix (2) + padding (1) + side borders (2)
+        let width = widest + 5;
+        let height = inner.values.len().clamp(1, MAX_VISIBLE) as u16 + 1;
+
+        vec![OverlayRequest {
+            anchor: inner.anchor,
+            size: (width, height),
+            content: Box::new(ListEditorOverlay {
+                values: inner.values.clone(),
+                selected_index: inner.selected_index,
+                editing: inner.input.as_ref().map(|input| {
+                    (
+                        input.text_before_cursor().to_string(),
+                        input.text_after_cursor().to_string(),
+                    )
+                }),
+            }),
+        }]
+    }
+}
+
+/// Snapshot of list editor data needed to render the overlay.
+struct ListEditorOverlay {
+    values: Vec<String>,
+    selected_index: usize,
+    /// Text before and after the cursor of the entry being edited.
+    editing: Option<(String, String)>,
+}
+
+impl OverlayContent for ListEditorOverlay {
+    fn render(&self, area: Rect, buf: &mut Buffer, colors: &UiColors) {
+        Clear.render(area, buf);
+        let block = Block::default()
+            .borders(Borders::LEFT | Borders::RIGHT | Borders::BOTTOM)
+            .border_style(Style::default().fg(colors.active_border));
+
+        let scroll = scroll_offset(self.selected_index, visible_rows(area));
+        let lines: Vec<Line> = if self.values.is_empty() {
+            vec![Line::from(Span::styled(
+                "  (empty — a to add)",
+                Style::default().fg(colors.help).italic(),
+            ))]
+        } else {
+            self.values
+                .iter()
+                .enumerate()
+                .skip(scroll)
+                .map(|(i, value)| {
+                    let is_selected = i == self.selected_index;
+                    let mut spans = Vec::new();
+                    if is_selected {
+                        spans.push(Span::styled(
+                            "▶ ",
+                            Style::default()
+                                .fg(colors.active_border)
+                                .add_modifier(Modifier::BOLD),
+                        ));
+                    } else {
+                        spans.push(Span::raw("  "));
+                    }
+                    match &self.editing {
+                        Some((before, after)) if is_selected => {
+                            push_edit_cursor(&mut spans, before, after, colors);
+                            Line::from(spans).style(Style::default().bg(colors.editing_bg))
+                        }
+                        _ if is_selected => {
+                            spans.push(Span::styled(
+                                value.clone(),
+                                Style::default()
+                                    .fg(colors.value)
+                                    .add_modifier(Modifier::BOLD),
+                            ));
+                            Line::from(spans).style(Style::default().bg(colors.selected_bg))
+                        }
+                        _ => {
+                            spans.push(Span::styled(
+                                value.clone(),
+                                Style::default().fg(colors.value),
+                            ));
+                            Line::from(spans)
+                        }
+                    }
+                })
+                .collect()
+        };
+
+        Paragraph::new(lines).block(block).render(area, buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::test_keys::{key, type_text};
+
+    fn values(list: &[&str]) -> Vec<String> {
+        list.iter().map(|v| v.to_string()).collect()
+    }
+
+    #[test]
+    fn test_empty_list_starts_adding() {
+        let mut editor = ListEditorComponent::new();
+        editor.open(Vec::new(), None, Rect::ZERO);
+        assert!(editor.is_editing_entry());
+        type_text(&mut editor, "A=1");
+        let result = editor.handle_key(key(KeyCode::Enter));
+        assert_eq!(
+            result,
+            EventResult::Action(ListEditorAction::Changed(values(&["A=1"])))
+        );
+        assert!(!editor.is_editing_entry());
+        assert!(editor.is_open(), "Editor stays open after adding an entry");
+    }
+
+    #[test]
+    fn test_add_inserts_after_selection() {
+        let mut editor = ListEditorComponent::new();
+        editor.open(values(&["a", "c"]), None, Rect::ZERO);
+        editor.handle_key(key(KeyCode::Char('a')));
+        type_text(&mut editor, "b");
+        editor.handle_key(key(KeyCode::Enter));
+        assert_eq!(editor.values().unwrap(), values(&["a", "b", "c"]));
+        assert_eq!(editor.selected_index(), Some(1));
+    }
+
+    #[test]
+    fn test_esc_drops_new_entry_and_reverts_edit() {
+        let mut editor = ListEditorComponent::new();
+        editor.open(values(&["a"]), None, Rect::ZERO);
+        editor.handle_key(key(KeyCode::Char('a')));
+        type_text(&mut editor, "new");
+        assert_eq!(editor.handle_key(key(KeyCode::Esc)), EventResult::Consumed);
+        assert_eq!(editor.values().unwrap(), values(&["a"]));
+        assert_eq!(editor.selected_index(), Some(0));
+
+        editor.handle_key(key(KeyCode::Enter));
+        type_text(&mut editor, "bc");
+        editor.handle_key(key(KeyCode::Esc));
+        assert_eq!(editor.values().unwrap(), values(&["a"]));
+        assert!(editor.is_open(), "Esc while editing only cancels the edit");
+    }
+
+    #[test]
+    fn test_edit_entry() {
+        let mut editor = ListEditorComponent::new();
+        editor.open(values(&["a", "b"]), None, Rect::ZERO);
+        editor.handle_key(key(KeyCode::Down));
+        editor.handle_key(key(KeyCode::Enter));
+        type_text(&mut editor, "2");
+        let result = editor.handle_key(key(KeyCode::Enter));
+        assert_eq!(
+            result,
+            EventResult::Action(ListEditorAction::Changed(values(&["a", "b2"])))
+        );
+    }
+
+    #[test]
+    fn test_delete_entry() {
+        let mut editor = ListEditorComponent::new();
+        editor.open(values(&["a", "b"]), None, Rect::ZERO);
+        editor.handle_key(key(KeyCode::End));
+        let result = editor.handle_key(key(KeyCode::Char('d')));
+        assert_eq!(
+            result,
+            EventResult::Action(ListEditorAction::Changed(values(&["a"])))
+        );
+        assert_eq!(
+            editor.selected_index(),
+            Some(0),
+            "Selection clamps to the new last entry"
+        );
+        editor.handle_key(key(KeyCode::Delete));
+        assert_eq!(editor.values().unwrap(), Vec::<String>::new());
+        assert_eq!(
+            editor.handle_key(key(KeyCode::Char('d'))),
+            EventResult::Consumed
+        );
+    }
+
+    #[test]
+    fn test_reorder_entries() {
+        let mut editor = ListEditorComponent::new();
+        editor.open(values(&["a", "b", "c"]), None, Rect::ZERO);
+        let result = editor.handle_key(key(KeyCode::Char('J')));
+        assert_eq!(
+            result,
+            EventResult::Action(ListEditorAction::Changed(values(&["b", "a", "c"])))
+        );
+        assert_eq!(
+            editor.selected_index(),
+            Some(1),
+            "Selection follows the moved entry"
+        );
+
+        editor.handle_key(KeyEvent::new(KeyCode::Down, KeyModifiers::SHIFT));
+        assert_eq!(editor.values().unwrap(), values(&["b", "c", "a"]));
+        assert_eq!(
+            editor.handle_key(KeyEvent::new(KeyCode::Down, KeyModifiers::SHIFT)),
+            EventResult::Consumed,
+            "The last entry can't move further down"
+        );
+
+        editor.handle_key(key(KeyCode::Char('K')));
+        assert_eq!(editor.values().unwrap(), values(&["b", "a", "c"]));
+    }
+
+    #[test]
+    fn test_add_stops_at_max() {
+        let mut editor = ListEditorComponent::new();
+        editor.open(values(&["a", "b"]), Some(2), Rect::ZERO);
+        editor.handle_key(key(KeyCode::Char('a')));
+        assert!(!editor.is_editing_entry(), "A full list can't grow");
+        assert_eq!(editor.values().unwrap(), values(&["a", "b"]));
+    }
+
+    #[test]
+    fn test_esc_closes() {
+        let mut editor = ListEditorComponent::new();
+        editor.open(values(&["a"]), None, Rect::ZERO);
+        assert_eq!(
+            editor.handle_key(key(KeyCode::Esc)),
+            EventResult::Action(ListEditorAction::Closed)
+        );
+        assert!(!editor.is_open());
+        assert_eq!(
+            editor.handle_key(key(KeyCode::Down)),
+            EventResult::NotHandled
+        );
+        assert!(editor.collect_overlays().is_empty());
+    }
+
+    #[test]
+    fn test_finish_commits_pending_edit() {
+        let mut editor = ListEditorComponent::new();
+        editor.open(values(&["a"]), None, Rect::ZERO);
+        editor.handle_key(key(KeyCode::Char('a')));
+        type_text(&mut editor, "b");
+        assert_eq!(editor.finish(), Some(values(&["a", "b"])));
+        assert!(!editor.is_open());
+    }
+
+    #[test]
+    fn test_click_selects_or_closes() {
+        let mut editor = ListEditorComponent::new();
+        editor.open(values(&["a", "b", "c"]), None, Rect::ZERO);
+        let rect = Some(Rect::new(10, 5, 20, 4));
+        assert_eq!(editor.click_at(12, 7, rect), None);
+        assert_eq!(editor.selected_index(), Some(2));
+        assert_eq!(editor.click_at(0, 0, rect), Some(ListEditorAction::Closed));
+        assert!(!editor.is_open());
+    }
+}
@@ -14,6 +14,7 @@ use crate::app::MatchScores;
 
 use super::choice_select::{ChoiceSelectAction, ChoiceSelectComponent};
 use super::filterable::{compute_match_scores, FilterableItem};
+use super::list_editor::{ListEditorAction, ListEditorComponent};
 use super::{find_adjacent_match, find_first_match, Component, EventResult, OverlayRequest};
 
 // ── Internal event types ────────────────────────────────────────────
@@ -38,6 +39,14 @@ pub enum ChoiceEvent {
     Consumed,
 }
 
+/// Result from list editor key or click handling.
+pub enum ListEvent {
+    /// The edited list changed.
+    Changed { index: usize, values: Vec<String> },
+    /// Event consumed by the list editor.
+    Consumed,
+}
+
 /// Result from mouse event handling in a list panel.
 pub enum MouseResult {
     /// A choice select item was clicked — yields a ChoiceEvent.
@@ -56,6 +65,8 @@ pub enum MouseResult {
 pub enum FocusLostEvent {
     /// Inline editing finished and should be committed by the parent.
     EditFinished { index: usize, value: String },
+    /// The list editor closed with a pending entry edit committed.
+    ListChanged { index: usize, values: Vec<String> },
     /// Focus loss closed local UI state without any parent action.
     Consumed,
     /// The panel had no focus-local state to clean up.
@@ -96,6 +107,11 @@ pub struct ListPanelBase {
     pub choice_select_index: Option<usize>,
     /// X-offset from panel left to the value column.
     pub value_column: u16,
+
+    /// Embedded list editor for multi-value items.
+    pub list_editor: ListEditorComponent,
+    /// Which item index has the list editor open.
+    pub list_editor_index: Option<usize>,
 }
 
 impl ListPanelBase {
@@ -115,6 +131,8 @@ impl ListPanelBase {
             choice_select: ChoiceSelectComponent::new(),
             choice_select_index: None,
             value_column: 0,
+            list_editor: ListEditorComponent::new(),
+            list_editor_index: None,
         }
     }
 
@@ -198,6 +216,13 @@ impl ListPanelBase {
     pub fn handle_focus_lost(&mut self) -> FocusLostEvent {
         self.focused = false;
 
+        if let Some(index) = self.list_editor_index.take() {
+            return match self.list_editor.finish() {
+                Some(values) => FocusLostEvent::ListChanged { index, values },
+                None => FocusLostEvent::Consumed,
+            };
+        }
+
         if self.choice_select.is_open() {
             if self.editing {
                 let index = self
@@ -482,7 +507,23 @@ impl ListPanelBase {
     }
 
     pub fn collect_overlays(&mut self) -> Vec<OverlayRequest> {
-        self.choice_select.collect_overlays()
+        let mut overlays = self.choice_select.collect_overlays();
+        overlays.extend(self.list_editor.collect_overlays());
+        overlays
+    }
+
+    /// Anchor for an overlay dropped down from the value column of an item row.
+    pub fn value_anchor(&self, area: Rect, index: usize) -> Rect {
+        let scroll = self.list_state.scroll as usize;
+        let row_in_viewport = index.saturating_sub(scroll);
+        let anchor_y = area.y + 1 + row_in_viewport as u16;
+        let anchor_x = area.x + self.value_column.saturating_sub(1);
+        Rect::new(
+            anchor_x,
+            anchor_y,
+            area.width.saturating_sub(self.value_column.saturating_sub(1)),
+            1,
+        )
     }
 
     pub fn handle_choice_click(
@@ -511,6 +552,62 @@ impl ListPanelBase {
         MouseResult::Consumed
     }
 
+    // ── List editor ─────────────────────────────────────────────────
+
+    pub fn is_list_editing(&self) -> bool {
+        self.list_editor.is_open()
+    }
+
+    pub fn open_list_editor(
+        &mut self,
+        index: usize,
+        values: Vec<String>,
+        max: Option<usize>,
+        value_column: u16,
+    ) {
+        self.list_editor_index = Some(index);
+        self.value_column = value_column;
+        self.list_editor.open(values, max, Rect::default());
+    }
+
+    /// Whether an entry in the open list editor is being added or edited.
+    pub fn is_list_entry_editing(&self) -> bool {
+        self.list_editor.is_editing_entry()
+    }
+
+    fn map_list_editor_action(&mut self, action: ListEditorAction) -> ListEvent {
+        let Some(index) = self.list_editor_index else {
+            return ListEvent::Consumed;
+        };
+        if !self.list_editor.is_open() {
+            self.list_editor_index = None;
+        }
+        match action {
+            ListEditorAction::Changed(values) => ListEvent::Changed { index, values },
+            ListEditorAction::Closed => ListEvent::Consumed,
+        }
+    }
+
+    /// Handle a key event while the list editor is open.
+    pub fn handle_list_editor_key(&mut self, key: KeyEvent) -> ListEvent {
+        match self.list_editor.handle_key(key) {
+            EventResult::Action(action) => self.map_list_editor_action(action),
+            _ => ListEvent::Consumed,
+        }
+    }
+
+    pub fn handle_list_editor_click(
+        &mut self,
+        col: u16,
+        row: u16,
+        overlay_rect: Option<Rect>,
+    ) -> ListEvent {
+        match self.list_editor.click_at(col, row, overlay_rect) {
+            Some(action) => self.map_list_editor_action(action),
+            None => ListEvent::Consumed,
+        }
+    }
+
     // ── Mouse handling ──────────────────────────────────────────────
 
     /// Handle a mouse event for this list panel.
@@ -523,7 +620,7 @@ impl ListPanelBase {
 
         match event.kind {
             MouseEventKind::Down(MouseButton::Left) => {
-                if self.is_choosing() {
+                if self.is_choosing() || self.is_list_editing() {
                     return MouseResult::Consumed;
                 }
 
@@ -549,7 +646,9 @@ impl ListPanelBase {
                 MouseResult::NotHandled
             }
             MouseEventKind::ScrollUp => {
-                if self.is_choosing() {
+                if self.is_list_editing() {
+                    // Keep the row under the open editor selected
+                } else if self.is_choosing() {
                     self.scroll_choice_select_up();
                 } else {
                     self.move_up();
@@ -557,7 +656,9 @@ impl ListPanelBase {
                 MouseResult::Consumed
             }
             MouseEventKind::ScrollDown => {
-                if self.is_choosing() {
+                if self.is_list_editing() {
+                    // Keep the row under the open editor selected
+                } else if self.is_choosing() {
                     self.scroll_choice_select_down();
                 } else {
                     self.move_down();
@@ -15,17 +15,26 @@
 //! - [`arg_panel`] — Argument list panel with choice select
 //! - [`choice_select`] — Filtered choice selection overlay
 //! - [`theme_picker`] — Theme picker overlay
+//! - [`picker`] — Entry picker overlay (history, favorites)
+//! - [`prompt`] — Single-line text prompt overlay
+//! - [`env_editor`] — Environment variables overlay
+//! - [`dir_picker`] — Working directory overlay
 //! - [`execution`] — Embedded terminal for command execution
 
 pub mod arg_panel;
 pub mod choice_select;
 pub mod command_panel;
+pub mod dir_picker;
+pub mod env_editor;
 pub mod execution;
 pub mod filterable;
 pub mod flag_panel;
 pub mod help_bar;
+pub mod list_editor;
 pub mod list_panel_base;
+pub mod picker;
 pub mod preview;
+pub mod prompt;
 pub mod select_list;
 pub mod theme_picker;
 
@@ -401,6 +410,21 @@ pub fn render_help_overlays(
     }
 }
 
+/// Push the validation messages for an item, if any, after its value.
+pub fn push_diagnostics<'a>(
+    spans: &mut Vec<Span<'static>>,
+    messages: impl Iterator<Item = &'a str>,
+    colors: &UiColors,
+) {
+    let messages: Vec<&str> = messages.collect();
+    if !messages.is_empty() {
+        spans.push(Span::styled(
+            format!("  ✗ {}", messages.join("; ")),
+            Style::default().fg(colors.required),
+        ));
+    }
+}
+
 /// Push inline edit cursor spans (before_cursor + ▎ + after_cursor).
 pub fn push_edit_cursor(
     spans: &mut Vec<Span<'static>>,
@@ -522,6 +546,16 @@ pub fn render_panel_scrollbar(
     StatefulWidget::render(scrollbar, inner, buf, &mut scrollbar_state);
 }
 
+/// Scroll offset that keeps the selected entry of a list showing `visible`
+/// entries in view, for the overlays that scroll by their selection.
+pub(crate) fn scroll_offset(selected: usize, visible: usize) -> usize {
+    if visible > 0 && selected >= visible {
+        selected + 1 - visible
+    } else {
+        0
+    }
+}
+
 // ── Shared filter-navigation helpers ────────────────────────────────
 
 /// Find the next or previous matching item in a scored list, wrapping around.
@@ -573,3 +607,25 @@ pub(crate) fn find_first_match(
     }
     None
 }
+
+// ── Test helpers ────────────────────────────────────────────────────
+
+/// Key events shared by the component tests.
+#[cfg(test)]
+pub(crate) mod test_keys {
+    use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+    use super::Component;
+
+    /// A key press without modifiers.
+    pub fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    /// Type `text` into a component one character at a time.
+    pub fn type_text(component: &mut impl Component, text: &str) {
+        for c in text.chars() {
+            component.handle_key(key(KeyCode::Char(c)));
+        }
+    }
+}
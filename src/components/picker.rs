@@ -0,0 +1,371 @@
+//! Generic entry picker overlay component.
+//!
+//! Lists labelled entries with a right-aligned description, used for the
+//! command history and favorites overlays. Selecting an entry (or deleting
+//! one, when enabled) is reported to the parent by index.
+
+use crossterm::event::{KeyCode, KeyEvent, MouseEvent};
+use ratatui::{buffer::Buffer, layout::Rect, widgets::Widget};
+
+use super::select_list::SelectList;
+use super::{Component, EventResult, OverlayContent, OverlayRequest};
+use crate::theme::UiColors;
+
+/// Actions emitted by the picker for the parent to process.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PickerAction {
+    /// An entry was picked; the index refers to the list passed to `open`.
+    Selected(usize),
+    /// An entry was deleted; the index refers to the list as it was before
+    /// the deletion (the picker stays open).
+    Deleted(usize),
+    /// The picker was closed without picking an entry.
+    Cancelled,
+}
+
+/// One row in the picker.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PickerItem {
+    /// Main text of the row.
+    pub label: String,
+    /// Right-aligned secondary text.
+    pub description: String,
+}
+
+/// Self-contained picker overlay.
+pub struct PickerComponent {
+    /// Title shown in the overlay border.
+    title: &'static str,
+    /// Whether `d`/Delete removes the selected entry.
+    deletable: bool,
+    state: Option<PickerInner>,
+    /// The viewport area, set by the UI coordinator before collecting overlays.
+    viewport: Rect,
+    /// Mouse position from parent, used for hover highlighting.
+    mouse_position: Option<(u16, u16)>,
+}
+
+struct PickerInner {
+    items: Vec<PickerItem>,
+    selected_index: usize,
+}
+
+impl PickerComponent {
+    pub fn new(title: &'static str) -> Self {
+        Self {
+            title,
+            deletable: false,
+            state: None,
+            viewport: Rect::ZERO,
+            mouse_position: None,
+        }
+    }
+
+    /// Allow deleting entries with `d` or Delete.
+    pub fn with_delete(mut self) -> Self {
+        self.deletable = true;
+        self
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.state.is_some()
+    }
+
+    /// Open the picker with the given entries.
+    pub fn open(&mut self, items: Vec<PickerItem>) {
+        self.state = Some(PickerInner {
+            items,
+            selected_index: 0,
+        });
+    }
+
+    pub fn close(&mut self) {
+        self.state = None;
+    }
+
+    /// Set the viewport so collect_overlays can compute the anchor position.
+    pub fn set_viewport(&mut self, viewport: Rect) {
+        self.viewport = viewport;
+    }
+
+    /// Update the mouse position for hover highlighting in the overlay.
+    pub fn set_mouse_position(&mut self, pos: Option<(u16, u16)>) {
+        self.mouse_position = pos;
+    }
+
+    #[cfg(test)]
+    pub fn selected_index(&self) -> Option<usize> {
+        self.state.as_ref().map(|s| s.selected_index)
+    }
+
+    #[cfg(test)]
+    pub fn len(&self) -> usize {
+        self.state.as_ref().map(|s| s.items.len()).unwrap_or(0)
+    }
+
+    /// Handle a mouse click. Returns an action if the picker is open.
+    pub fn click_at(
+        &mut self,
+        col: u16,
+        row: u16,
+        overlay_rect: Option<Rect>,
+    ) -> Option<PickerAction> {
+        let inner = self.state.as_ref()?;
+
+        if let Some(rect) = overlay_rect {
+            if let Some(idx) = index_at(rect, col, row, inner.selected_index, inner.items.len()) {
+                self.close();
+                return Some(PickerAction::Selected(idx));
+            }
+        }
+
+        // Click outside — cancel
+        self.close();
+        Some(PickerAction::Cancelled)
+    }
+
+    fn overlay_size(&self, items: &[PickerItem]) -> (u16, u16) {
+        let widest = items
+            .iter()
+            .map(|item| item.label.chars().count() + item.description.chars().count() + 2)
+            .max()
+            .unwrap_or(20) as u16;
+        // "▶ " prefix (2) + padding (2) + borders (2)
+        let width = (widest + 6).min(self.viewport.width.saturating_sub(4));
+        let height = (items.len().max(1) as u16 + 2).min(self.viewport.height.saturating_sub(5));
+        (width, height)
+    }
+}
+
+/// Map a click/hover position to an item index, accounting for the scroll
+/// offset `SelectList` applies to keep the selection visible.
+fn index_at(area: Rect, col: u16, row: u16, selected: usize, len: usize) -> Option<usize> {
+    let inner_top = area.y + 1;
+    let inner_bottom = area.y + area.height.saturating_sub(1);
+    if col < area.x || col >= area.x + area.width || row < inner_top || row >= inner_bottom {
+        return None;
+    }
+    let visible = (inner_bottom - inner_top) as usize;
+    let scroll_offset = if visible > 0 && selected >= visible {
+        selected + 1 - visible
+    } else {
+        0
+    };
+    let idx = (row - inner_top) as usize + scroll_offset;
+    (idx < len).then_some(idx)
+}
+
+impl Component for PickerComponent {
+    type Action = PickerAction;
+
+    fn handle_key(&mut self, key: KeyEvent) -> EventResult<Self::Action> {
+        let Some(ref mut inner) = self.state else {
+            return EventResult::NotHandled;
+        };
+
+        let len = inner.items.len();
+
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => {
+                self.close();
+                EventResult::Action(PickerAction::Cancelled)
+            }
+            KeyCode::Enter => {
+                if len == 0 {
+                    self.close();
+                    return EventResult::Action(PickerAction::Cancelled);
+                }
+                let idx = inner.selected_index;
+                self.close();
+                EventResult::Action(PickerAction::Selected(idx))
+            }
+            KeyCode::Char('d') | KeyCode::Delete if self.deletable && len > 0 => {
+                let idx = inner.selected_index;
+                inner.items.remove(idx);
+                inner.selected_index = idx.min(inner.items.len().saturating_sub(1));
+                EventResult::Action(PickerAction::Deleted(idx))
+            }
+            KeyCode::Up | KeyCode::Char('k') if len > 0 => {
+                inner.selected_index = if inner.selected_index > 0 {
+                    inner.selected_index - 1
+                } else {
+                    len - 1
+                };
+                EventResult::Consumed
+            }
+            KeyCode::Down | KeyCode::Char('j') if len > 0 => {
+                inner.selected_index = if inner.selected_index + 1 < len {
+                    inner.selected_index + 1
+                } else {
+                    0
+                };
+                EventResult::Consumed
+            }
+            KeyCode::Home => {
+                inner.selected_index = 0;
+                EventResult::Consumed
+            }
+            KeyCode::End => {
+                inner.selected_index = len.saturating_sub(1);
+                EventResult::Consumed
+            }
+            _ => EventResult::Consumed,
+        }
+    }
+
+    fn handle_mouse(&mut self, _event: MouseEvent, _area: Rect) -> EventResult<Self::Action> {
+        EventResult::NotHandled
+    }
+
+    fn collect_overlays(&mut self) -> Vec<OverlayRequest> {
+        let Some(ref inner) = self.state else {
+            return vec![];
+        };
+
+        let (width, height) = self.overlay_size(&inner.items);
+
+        // Horizontally centered, just below the command preview
+        let anchor_x = self.viewport.x + self.viewport.width.saturating_sub(width) / 2;
+        let anchor = Rect::new(anchor_x, self.viewport.y + 3, 0, 0);
+
+        vec![OverlayRequest {
+            anchor,
+            size: (width, height),
+            content: Box::new(PickerOverlay {
+                title: self.title,
+                labels: inner.items.iter().map(|i| i.label.clone()).collect(),
+                descriptions: inner
+                    .items
+                    .iter()
+                    .map(|i| Some(i.description.clone()))
+                    .collect(),
+                selected_index: inner.selected_index,
+                mouse_position: self.mouse_position,
+            }),
+        }]
+    }
+}
+
+struct PickerOverlay {
+    title: &'static str,
+    labels: Vec<String>,
+    descriptions: Vec<Option<String>>,
+    selected_index: usize,
+    mouse_position: Option<(u16, u16)>,
+}
+
+impl OverlayContent for PickerOverlay {
+    fn render(&self, area: Rect, buf: &mut Buffer, colors: &UiColors) {
+        let hovered = self.mouse_position.and_then(|(col, row)| {
+            index_at(area, col, row, self.selected_index, self.labels.len())
+        });
+        let widget = SelectList::new(
+            format!(" {} ", self.title),
+            &self.labels,
+            Some(self.selected_index),
+            colors.preview_cmd,
+            colors.value,
+            colors,
+        )
+        .with_descriptions(&self.descriptions)
+        .with_cursor()
+        .with_hovered(hovered);
+        Widget::render(widget, area, buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::test_keys::key;
+
+    fn items(n: usize) -> Vec<PickerItem> {
+        (0..n)
+            .map(|i| PickerItem {
+                label: format!("mycli run task{i}"),
+                description: "✓ just now".to_string(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_open_navigate_and_select() {
+        let mut picker = PickerComponent::new("History");
+        assert!(!picker.is_open());
+        picker.open(items(3));
+        assert_eq!(picker.selected_index(), Some(0));
+
+        picker.handle_key(key(KeyCode::Down));
+        picker.handle_key(key(KeyCode::Char('j')));
+        assert_eq!(picker.selected_index(), Some(2));
+        picker.handle_key(key(KeyCode::Down));
+        assert_eq!(picker.selected_index(), Some(0), "Down wraps to the first entry");
+        picker.handle_key(key(KeyCode::Up));
+        assert_eq!(picker.selected_index(), Some(2), "Up wraps to the last entry");
+
+        let result = picker.handle_key(key(KeyCode::Enter));
+        assert_eq!(result, EventResult::Action(PickerAction::Selected(2)));
+        assert!(!picker.is_open());
+    }
+
+    #[test]
+    fn test_esc_cancels() {
+        let mut picker = PickerComponent::new("History");
+        picker.open(items(2));
+        let result = picker.handle_key(key(KeyCode::Esc));
+        assert_eq!(result, EventResult::Action(PickerAction::Cancelled));
+        assert!(!picker.is_open());
+    }
+
+    #[test]
+    fn test_enter_on_empty_list_cancels() {
+        let mut picker = PickerComponent::new("History");
+        picker.open(Vec::new());
+        let result = picker.handle_key(key(KeyCode::Enter));
+        assert_eq!(result, EventResult::Action(PickerAction::Cancelled));
+    }
+
+    #[test]
+    fn test_not_handled_when_closed() {
+        let mut picker = PickerComponent::new("History");
+        assert_eq!(picker.handle_key(key(KeyCode::Down)), EventResult::NotHandled);
+        assert!(picker.collect_overlays().is_empty());
+    }
+
+    #[test]
+    fn test_delete_only_when_enabled() {
+        let mut picker = PickerComponent::new("History");
+        picker.open(items(2));
+        assert_eq!(picker.handle_key(key(KeyCode::Char('d'))), EventResult::Consumed);
+        assert_eq!(picker.len(), 2);
+
+        let mut picker = PickerComponent::new("Favorites").with_delete();
+        picker.open(items(3));
+        picker.handle_key(key(KeyCode::End));
+        let result = picker.handle_key(key(KeyCode::Char('d')));
+        assert_eq!(result, EventResult::Action(PickerAction::Deleted(2)));
+        assert!(picker.is_open(), "Picker stays open after deleting");
+        assert_eq!(picker.len(), 2);
+        assert_eq!(picker.selected_index(), Some(1), "Selection clamps to the new last entry");
+    }
+
+    #[test]
+    fn test_click_accounts_for_scroll_offset() {
+        let mut picker = PickerComponent::new("History");
+        picker.open(items(20));
+        picker.handle_key(key(KeyCode::End)); // select 19
+        // 5 visible rows (height 7 minus borders) → scroll offset 15
+        let rect = Rect::new(10, 3, 40, 7);
+        let result = picker.click_at(20, 4, Some(rect));
+        assert_eq!(result, Some(PickerAction::Selected(15)));
+    }
+
+    #[test]
+    fn test_click_outside_cancels() {
+        let mut picker = PickerComponent::new("History");
+        picker.open(items(2));
+        let result = picker.click_at(0, 0, Some(Rect::new(10, 3, 40, 4)));
+        assert_eq!(result, Some(PickerAction::Cancelled));
+        assert!(!picker.is_open());
+    }
+}
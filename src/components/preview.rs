@@ -10,47 +10,115 @@ use ratatui::{
     widgets::{Block, Borders, Padding, Paragraph, Widget, Wrap},
 };
 
+use crate::env::{EnvVar, PrefixPart};
+use crate::quoting::Dialect;
 use crate::theme::UiColors;
 
 /// A widget that renders the assembled command preview with colorized tokens.
 ///
-/// Displays the command string in a bordered block, with syntax-aware coloring
-/// for the binary name, subcommands, flags, and positional arguments.
+/// Displays the command parts quoted for the configured shell in a bordered
+/// block, with syntax-aware coloring for the binary name, subcommands, flags,
+/// and positional arguments.
 pub struct CommandPreview<'a> {
-    /// The full built command string.
-    pub command: &'a str,
-    /// Binary name from the spec.
+    /// The command as separate (unquoted) arguments.
+    pub parts: &'a [String],
+    /// Shell syntax used to quote each part for display.
+    pub dialect: Dialect,
+    /// Binary name from the spec (may be several words, e.g. "mise run").
     pub bin: &'a str,
     /// Subcommand names in the current command path.
     pub subcommands: &'a [String],
+    /// Environment variable overrides, shown before the command.
+    pub env: &'a [EnvVar],
+    /// Working directory the command runs in, shown in the bottom border.
+    pub cwd: Option<&'a str>,
     /// Whether the preview panel currently has focus.
     pub is_focused: bool,
+    /// Number of validation problems, shown in the border when non-zero.
+    pub problems: usize,
+    /// Numbers of running and finished background jobs, shown at the right
+    /// of the border.
+    pub jobs: (usize, usize),
     pub colors: &'a UiColors,
 }
 
 impl<'a> CommandPreview<'a> {
     pub fn new(
-        command: &'a str,
+        parts: &'a [String],
+        dialect: Dialect,
         bin: &'a str,
         subcommands: &'a [String],
         is_focused: bool,
         colors: &'a UiColors,
     ) -> Self {
         Self {
-            command,
+            parts,
+            dialect,
             bin,
             subcommands,
+            env: &[],
+            cwd: None,
             is_focused,
+            problems: 0,
+            jobs: (0, 0),
             colors,
         }
     }
 
-    /// Colorize the command string by categorizing each token.
+    pub fn with_problems(mut self, problems: usize) -> Self {
+        self.problems = problems;
+        self
+    }
+
+    pub fn with_env(mut self, env: &'a [EnvVar]) -> Self {
+        self.env = env;
+        self
+    }
+
+    pub fn with_cwd(mut self, cwd: Option<&'a str>) -> Self {
+        self.cwd = cwd;
+        self
+    }
+
+    pub fn with_jobs(mut self, running: usize, finished: usize) -> Self {
+        self.jobs = (running, finished);
+        self
+    }
+
+    /// Colorize the environment prefix in the shell's syntax, or explain
+    /// why the shell can't set the overrides.
+    fn colorize_env(&self, bold: Modifier) -> Vec<Span<'static>> {
+        let parts = match crate::env::prefix(self.env, self.dialect) {
+            Ok(parts) => parts,
+            Err(e) => {
+                return vec![Span::styled(
+                    format!("✗ {e} "),
+                    Style::default().fg(self.colors.required).add_modifier(bold),
+                )]
+            }
+        };
+        parts
+            .into_iter()
+            .map(|part| {
+                let style = match part {
+                    PrefixPart::Command(_) => Style::default().fg(self.colors.preview_cmd),
+                    PrefixPart::Name(_) => Style::default().fg(self.colors.flag),
+                    PrefixPart::Value(_) => Style::default().fg(self.colors.value),
+                    PrefixPart::Plain(_) => return Span::raw(part.text().to_string()),
+                };
+                Span::styled(part.text().to_string(), style.add_modifier(bold))
+            })
+            .collect()
+    }
+
+    /// Colorize the command by categorizing each part, then quoting it.
     fn colorize(&self, bold: Modifier) -> Vec<Span<'static>> {
         let subcommand_names: HashSet<&str> =
             self.subcommands.iter().map(|s| s.as_str()).collect();
 
-        let tokens: Vec<&str> = self.command.split_whitespace().collect();
+        let tokens: Vec<&str> = self.parts.iter().map(|s| s.as_str()).collect();
+        let words = self.dialect.words(self.parts);
+        let bin_words = self.bin.split_whitespace().count();
         let mut spans = Vec::new();
         let mut i = 0;
         let mut expect_flag_value = false;
@@ -61,23 +129,24 @@ impl<'a> CommandPreview<'a> {
             }
 
             let token = tokens[i];
+            let word = words[i].clone();
 
-            if i == 0 && token == self.bin {
+            if i < bin_words {
                 spans.push(Span::styled(
-                    token.to_string(),
+                    word,
                     Style::default()
                         .fg(self.colors.preview_cmd)
                         .add_modifier(bold | Modifier::BOLD),
                 ));
             } else if expect_flag_value {
                 spans.push(Span::styled(
-                    token.to_string(),
+                    word,
                     Style::default().fg(self.colors.value).add_modifier(bold),
                 ));
                 expect_flag_value = false;
             } else if token.starts_with('-') {
                 spans.push(Span::styled(
-                    token.to_string(),
+                    word,
                     Style::default().fg(self.colors.flag).add_modifier(bold),
                 ));
                 if let Some(&next) = tokens.get(i + 1) {
@@ -87,12 +156,12 @@ impl<'a> CommandPreview<'a> {
                 }
             } else if subcommand_names.contains(token) {
                 spans.push(Span::styled(
-                    token.to_string(),
+                    word,
                     Style::default().fg(self.colors.command).add_modifier(bold),
                 ));
             } else {
                 spans.push(Span::styled(
-                    token.to_string(),
+                    word,
                     Style::default().fg(self.colors.arg).add_modifier(bold),
                 ));
             }
@@ -112,12 +181,43 @@ impl Widget for CommandPreview<'_> {
             self.colors.inactive_border
         };
 
-        let block = Block::default()
+        let mut title = vec![Span::raw(" Command ")];
+        if self.problems > 0 {
+            let noun = if self.problems == 1 { "problem" } else { "problems" };
+            title.push(Span::styled(
+                format!("✗ {} {noun} ", self.problems),
+                Style::default().fg(self.colors.required),
+            ));
+        }
+
+        let (running, finished) = self.jobs;
+        let mut jobs_title = Vec::new();
+        if running > 0 {
+            jobs_title.push(Span::styled(
+                format!(" ● {running} running "),
+                Style::default().fg(self.colors.command),
+            ));
+        }
+        if finished > 0 {
+            jobs_title.push(Span::styled(
+                format!(" {finished} finished "),
+                Style::default().fg(self.colors.value),
+            ));
+        }
+
+        let mut block = Block::default()
             .borders(Borders::ALL)
             .border_style(Style::default().fg(border_color))
-            .title(" Command ")
+            .title(Line::from(title))
+            .title(Line::from(jobs_title).right_aligned())
             .title_style(Style::default().fg(border_color).bold())
             .padding(Padding::horizontal(1));
+        if let Some(cwd) = self.cwd {
+            block = block.title_bottom(Line::from(Span::styled(
+                format!(" in {cwd} "),
+                Style::default().fg(self.colors.help),
+            )));
+        }
 
         let prefix = if self.is_focused { "▶ " } else { "$ " };
         let bold = if self.is_focused {
@@ -127,6 +227,7 @@ impl Widget for CommandPreview<'_> {
         };
 
         let mut spans = vec![Span::styled(prefix, Style::default().fg(self.colors.command))];
+        spans.extend(self.colorize_env(bold));
         spans.extend(self.colorize(bold));
 
         let paragraph = Paragraph::new(Line::from(spans))
@@ -0,0 +1,254 @@
+//! Single-line text prompt overlay component.
+//!
+//! Asks the user for a line of text (e.g. a favorite's name) in a small
+//! bordered box centered below the command preview.
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseEvent};
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph, Widget},
+};
+use ratatui_interact::components::InputState;
+
+use super::{push_edit_cursor, Component, EventResult, OverlayContent, OverlayRequest};
+use crate::theme::UiColors;
+
+/// Actions emitted by the prompt for the parent to process.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PromptAction {
+    /// The user pressed Enter with the given text.
+    Submitted(String),
+    /// The prompt was dismissed.
+    Cancelled,
+}
+
+/// Self-contained text prompt overlay.
+pub struct PromptComponent {
+    state: Option<PromptInner>,
+    /// The viewport area, set by the UI coordinator before collecting overlays.
+    viewport: Rect,
+}
+
+struct PromptInner {
+    title: String,
+    input: InputState,
+}
+
+impl PromptComponent {
+    pub fn new() -> Self {
+        Self {
+            state: None,
+            viewport: Rect::ZERO,
+        }
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.state.is_some()
+    }
+
+    /// Open the prompt with a title and pre-filled text.
+    pub fn open(&mut self, title: impl Into<String>, initial: &str) {
+        self.state = Some(PromptInner {
+            title: title.into(),
+            input: InputState::new(initial),
+        });
+    }
+
+    pub fn close(&mut self) {
+        self.state = None;
+    }
+
+    /// Set the viewport so collect_overlays can compute the anchor position.
+    pub fn set_viewport(&mut self, viewport: Rect) {
+        self.viewport = viewport;
+    }
+
+    /// Current input text (empty when closed).
+    #[cfg(test)]
+    pub fn text(&self) -> &str {
+        self.state.as_ref().map(|s| s.input.text()).unwrap_or("")
+    }
+
+    /// Handle a mouse click: clicks outside the prompt cancel it.
+    pub fn click_at(&mut self, col: u16, row: u16, overlay_rect: Option<Rect>) -> Option<PromptAction> {
+        self.state.as_ref()?;
+        let inside = overlay_rect.is_some_and(|r| {
+            col >= r.x && col < r.x + r.width && row >= r.y && row < r.y + r.height
+        });
+        if inside {
+            return None;
+        }
+        self.close();
+        Some(PromptAction::Cancelled)
+    }
+}
+
+impl Component for PromptComponent {
+    type Action = PromptAction;
+
+    fn handle_key(&mut self, key: KeyEvent) -> EventResult<Self::Action> {
+        let Some(ref mut inner) = self.state else {
+            return EventResult::NotHandled;
+        };
+        let input = &mut inner.input;
+
+        match key.code {
+            KeyCode::Esc => {
+                self.close();
+                EventResult::Action(PromptAction::Cancelled)
+            }
+            KeyCode::Enter => {
+                let text = input.text().to_string();
+                self.close();
+                EventResult::Action(PromptAction::Submitted(text))
+            }
+            KeyCode::Char('u') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                input.clear();
+                EventResult::Consumed
+            }
+            KeyCode::Char('w') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                input.delete_word_backward();
+                EventResult::Consumed
+            }
+            KeyCode::Char(_) if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                EventResult::Consumed
+            }
+            KeyCode::Char(c) => {
+                input.insert_char(c);
+                EventResult::Consumed
+            }
+            KeyCode::Backspace => {
+                input.delete_char_backward();
+                EventResult::Consumed
+            }
+            KeyCode::Delete => {
+                input.delete_char_forward();
+                EventResult::Consumed
+            }
+            KeyCode::Left => {
+                input.move_left();
+                EventResult::Consumed
+            }
+            KeyCode::Right => {
+                input.move_right();
+                EventResult::Consumed
+            }
+            KeyCode::Home => {
+                input.move_home();
+                EventResult::Consumed
+            }
+            KeyCode::End => {
+                input.move_end();
+                EventResult::Consumed
+            }
+            _ => EventResult::Consumed,
+        }
+    }
+
+    fn handle_mouse(&mut self, _event: MouseEvent, _area: Rect) -> EventResult<Self::Action> {
+        EventResult::NotHandled
+    }
+
+    fn collect_overlays(&mut self) -> Vec<OverlayRequest> {
+        let Some(ref inner) = self.state else {
+            return vec![];
+        };
+
+        let content_width = inner.title.chars().count().max(inner.input.text().chars().count()) as u16;
+        let width = (content_width + 6)
+            .max(50)
+            .min(self.viewport.width.saturating_sub(4));
+        let anchor_x = self.viewport.x + self.viewport.width.saturating_sub(width) / 2;
+        let anchor = Rect::new(anchor_x, self.viewport.y + 3, 0, 0);
+
+        vec![OverlayRequest {
+            anchor,
+            size: (width, 3),
+            content: Box::new(PromptOverlay {
+                title: inner.title.clone(),
+                before_cursor: inner.input.text_before_cursor().to_string(),
+                after_cursor: inner.input.text_after_cursor().to_string(),
+            }),
+        }]
+    }
+}
+
+struct PromptOverlay {
+    title: String,
+    before_cursor: String,
+    after_cursor: String,
+}
+
+impl OverlayContent for PromptOverlay {
+    fn render(&self, area: Rect, buf: &mut Buffer, colors: &UiColors) {
+        Clear.render(area, buf);
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(colors.active_border))
+            .title(format!(" {} ", self.title))
+            .title_style(
+                Style::default()
+                    .fg(colors.active_border)
+                    .add_modifier(Modifier::BOLD),
+            );
+        let mut spans = vec![Span::raw(" ")];
+        push_edit_cursor(&mut spans, &self.before_cursor, &self.after_cursor, colors);
+        Paragraph::new(Line::from(spans))
+            .block(block)
+            .style(Style::default().bg(colors.editing_bg))
+            .render(area, buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::test_keys::key;
+
+    #[test]
+    fn test_typing_and_submit() {
+        let mut prompt = PromptComponent::new();
+        prompt.open("Save favorite", "deploy");
+        prompt.handle_key(key(KeyCode::Char('-')));
+        prompt.handle_key(key(KeyCode::Char('x')));
+        prompt.handle_key(key(KeyCode::Backspace));
+        assert_eq!(prompt.text(), "deploy-");
+
+        let result = prompt.handle_key(key(KeyCode::Enter));
+        assert_eq!(result, EventResult::Action(PromptAction::Submitted("deploy-".to_string())));
+        assert!(!prompt.is_open());
+    }
+
+    #[test]
+    fn test_esc_cancels() {
+        let mut prompt = PromptComponent::new();
+        prompt.open("Save favorite", "");
+        assert_eq!(
+            prompt.handle_key(key(KeyCode::Esc)),
+            EventResult::Action(PromptAction::Cancelled)
+        );
+        assert!(!prompt.is_open());
+    }
+
+    #[test]
+    fn test_ctrl_u_clears() {
+        let mut prompt = PromptComponent::new();
+        prompt.open("Save favorite", "some text");
+        prompt.handle_key(KeyEvent::new(KeyCode::Char('u'), KeyModifiers::CONTROL));
+        assert_eq!(prompt.text(), "");
+    }
+
+    #[test]
+    fn test_click_outside_cancels() {
+        let mut prompt = PromptComponent::new();
+        prompt.open("Save favorite", "");
+        let rect = Some(Rect::new(10, 3, 50, 3));
+        assert_eq!(prompt.click_at(20, 4, rect), None);
+        assert!(prompt.is_open());
+        assert_eq!(prompt.click_at(0, 0, rect), Some(PromptAction::Cancelled));
+        assert!(!prompt.is_open());
+    }
+}
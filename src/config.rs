@@ -0,0 +1,829 @@
+//! User configuration file.
+//!
+//! Settings are read from `<config dir>/config.toml`:
+//!
+//! ```toml
+//! theme = "tokyo-night"
+//!
+//! [behavior]
+//! preview_enter = "print"   # or "execute" (default)
+//! shell = "fish"            # posix, fish, powershell or cmd
+//! scrollback = 10000        # lines kept from command output
+//! transcript_dir = "/tmp/tuisage"  # save a transcript of every run
+//! timeout = "10m"           # stop commands that run longer
+//! resolve_paths = true      # make relative path args absolute
+//! layout = "right"          # show output beside the builder (or "below")
+//! rerun_output = "clear"    # clear the output when running a command again
+//!
+//! [watch]
+//! include = ["*.rs"]        # re-run watched commands when these change
+//! exclude = ["target/**"]
+//!
+//! [cache]
+//! watch = ["mise.toml", "tasks.toml"]  # spec command output is stale when these change
+//!
+//! [keys]
+//! execute = "ctrl+r"
+//! history = "H"
+//!
+//! [specs."mise tasks ls --usage"]
+//! cmd = "mise run"
+//! ```
+//!
+//! The theme is written back when it is confirmed in the theme picker; only
+//! the `theme` key is touched so comments and formatting are preserved.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::time::Duration;
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui_themes::ThemeName;
+use serde::Deserialize;
+
+use crate::quoting::Dialect;
+
+/// Settings loaded from the config file.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// File the config was loaded from. `None` keeps changes in memory only.
+    #[serde(skip)]
+    path: Option<PathBuf>,
+    /// Theme name (kebab-case slug or display name).
+    pub theme: Option<String>,
+    pub behavior: Behavior,
+    /// Watch mode settings.
+    pub watch: WatchConfig,
+    /// Spec command output cache settings.
+    pub cache: CacheConfig,
+    pub keys: KeyBindings,
+    /// Per-spec settings, keyed by the spec command, spec file path or binary name.
+    pub specs: HashMap<String, SpecConfig>,
+}
+
+/// Behavior toggles.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct Behavior {
+    /// What Enter on the command preview does.
+    pub preview_enter: PreviewEnter,
+    /// Shell syntax for quoting the displayed and printed command.
+    pub shell: Dialect,
+    /// Lines of command output kept after they scroll off the screen.
+    pub scrollback: usize,
+    /// Directory to save a transcript of every executed command to.
+    pub transcript_dir: Option<PathBuf>,
+    /// Stop commands that run longer than this.
+    pub timeout: Option<HumanDuration>,
+    /// How long a command stopped by the timeout, or when quitting, has to
+    /// exit after SIGTERM before it is sent SIGKILL.
+    pub kill_after: HumanDuration,
+    /// Make relative paths in argument values absolute against the
+    /// directory the command runs in.
+    pub resolve_paths: bool,
+    /// Where the output of an executed command is shown.
+    pub layout: OutputLayout,
+    /// What happens to the output when a command is run again.
+    pub rerun_output: RerunOutput,
+}
+
+impl Default for Behavior {
+    fn default() -> Self {
+        Self {
+            preview_enter: PreviewEnter::default(),
+            shell: Dialect::default(),
+            scrollback: 10_000,
+            transcript_dir: None,
+            timeout: None,
+            kill_after: HumanDuration(Duration::from_secs(5)),
+            resolve_paths: false,
+            layout: OutputLayout::default(),
+            rerun_output: RerunOutput::default(),
+        }
+    }
+}
+
+/// A duration written as a number with a unit, e.g. `"500ms"`, `"30s"`,
+/// `"5m"` or `"1h"`. A bare number is in seconds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "String")]
+pub struct HumanDuration(pub Duration);
+
+impl FromStr for HumanDuration {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let split = s
+            .find(|c: char| !c.is_ascii_digit() && c != '.')
+            .unwrap_or(s.len());
+        let (number, unit) = s.split_at(split);
+        let number: f64 = number
+            .parse()
+            .map_err(|_| format!("invalid duration '{s}', e.g. \"30s\" or \"5m\""))?;
+        let seconds = match unit.trim() {
+            "ms" => number / 1000.0,
+            "" | "s" => number,
+            "m" => number * 60.0,
+            "h" => number * 3600.0,
+            other => return Err(format!("unknown unit '{other}' in duration '{s}'")),
+        };
+        Duration::try_from_secs_f64(seconds)
+            .map(Self)
+            .map_err(|e| format!("invalid duration '{s}': {e}"))
+    }
+}
+
+impl TryFrom<String> for HumanDuration {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl fmt::Display for HumanDuration {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let millis = self.0.as_millis();
+        match millis {
+            m if m % 3_600_000 == 0 && m > 0 => write!(f, "{}h", m / 3_600_000),
+            m if m % 60_000 == 0 && m > 0 => write!(f, "{}m", m / 60_000),
+            m if m % 1000 == 0 => write!(f, "{}s", m / 1000),
+            m => write!(f, "{m}ms"),
+        }
+    }
+}
+
+/// Action for Enter (or a click) on the command preview.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PreviewEnter {
+    /// Run the command in the embedded terminal.
+    #[default]
+    Execute,
+    /// Exit and print the command to stdout.
+    Print,
+}
+
+/// Where the output of an executed command is shown.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputLayout {
+    /// Full screen, in place of the builder until it is closed.
+    #[default]
+    Full,
+    /// In a pane to the right of the builder panels.
+    Right,
+    /// In a pane below the builder panels.
+    Below,
+}
+
+impl OutputLayout {
+    /// The layout the layout key switches to.
+    pub fn next(self) -> Self {
+        match self {
+            OutputLayout::Full => OutputLayout::Right,
+            OutputLayout::Right => OutputLayout::Below,
+            OutputLayout::Below => OutputLayout::Full,
+        }
+    }
+
+    /// Whether the builder stays visible next to the output.
+    pub fn is_split(self) -> bool {
+        self != OutputLayout::Full
+    }
+}
+
+/// What happens to the previous output when a command is run again from
+/// the execution view.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RerunOutput {
+    /// Keep it, followed by a separator line describing the finished run.
+    #[default]
+    Append,
+    /// Start from an empty screen.
+    Clear,
+}
+
+/// What watch mode waits for before running a command again.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct WatchConfig {
+    /// Directory to watch, relative to the one the command runs in.
+    pub dir: Option<PathBuf>,
+    /// Globs for the files whose changes count, relative to the watched
+    /// directory. Every file counts when empty.
+    pub include: Vec<String>,
+    /// Globs for files whose changes are ignored.
+    pub exclude: Vec<String>,
+    /// How long files have to stay unchanged before the command runs.
+    pub debounce: HumanDuration,
+    /// Run the command at this interval instead of watching files.
+    pub interval: Option<HumanDuration>,
+}
+
+impl Default for WatchConfig {
+    fn default() -> Self {
+        Self {
+            dir: None,
+            include: Vec::new(),
+            exclude: vec![
+                ".git/**".to_string(),
+                "target/**".to_string(),
+                "node_modules/**".to_string(),
+            ],
+            debounce: HumanDuration(Duration::from_millis(300)),
+            interval: None,
+        }
+    }
+}
+
+/// When the cached output of a spec command is used.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct CacheConfig {
+    /// Files (or directories) whose changes make the cached output stale,
+    /// relative to the directory TuiSage runs in.
+    pub watch: Vec<String>,
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        Self {
+            watch: [
+                "mise.toml",
+                ".mise.toml",
+                "mise.local.toml",
+                ".mise/config.toml",
+                ".mise/tasks",
+                "mise-tasks",
+            ]
+            .map(String::from)
+            .to_vec(),
+        }
+    }
+}
+
+/// Settings for one spec.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct SpecConfig {
+    /// Default base command, used when `--cmd` is not given.
+    pub cmd: Option<String>,
+}
+
+/// Key bindings for the global actions.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct KeyBindings {
+    pub quit: KeyBinding,
+    /// Checked before any panel handles the key, so it should use a modifier.
+    pub execute: KeyBinding,
+    /// Print the command and exit (on the command preview).
+    pub accept: KeyBinding,
+    pub theme_picker: KeyBinding,
+    pub next_theme: KeyBinding,
+    pub prev_theme: KeyBinding,
+    pub history: KeyBinding,
+    pub favorites: KeyBinding,
+    pub save_favorite: KeyBinding,
+    /// Prompt for a command line to load into the builder.
+    pub paste_command: KeyBinding,
+    /// Read the spec again from its file or command.
+    pub reload: KeyBinding,
+    /// Open the environment variables for the next run.
+    pub env: KeyBinding,
+    /// Open the working directory for the next run.
+    pub cwd: KeyBinding,
+    /// Pressed before the execution view's own keys while a command runs,
+    /// so that all other keys reach the command. Pressed twice, it reaches
+    /// the command too.
+    pub prefix: KeyBinding,
+    /// Toggle scrolling the output of a running command.
+    pub scroll_mode: KeyBinding,
+    /// Send the running command to the background (in the execution view).
+    pub background: KeyBinding,
+    /// Send SIGTERM to the running command (in the execution view).
+    pub terminate: KeyBinding,
+    /// Send SIGKILL to the running command (in the execution view).
+    pub kill: KeyBinding,
+    /// Open the list of running and finished commands.
+    pub jobs: KeyBinding,
+    /// Switch between full-screen output and the split layouts.
+    pub layout: KeyBinding,
+    /// Stop the latest run and start the current command in its place.
+    /// Works in the output too, so it should use a modifier.
+    pub rerun: KeyBinding,
+    /// Toggle running the command again when files change (in the
+    /// execution view).
+    pub watch: KeyBinding,
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        Self {
+            quit: KeyBinding::char('q'),
+            execute: KeyBinding::new(KeyCode::Char('r'), KeyModifiers::CONTROL),
+            accept: KeyBinding::char('p'),
+            theme_picker: KeyBinding::char('T'),
+            next_theme: KeyBinding::char(']'),
+            prev_theme: KeyBinding::char('['),
+            history: KeyBinding::char('H'),
+            favorites: KeyBinding::char('F'),
+            save_favorite: KeyBinding::char('S'),
+            paste_command: KeyBinding::char('P'),
+            reload: KeyBinding::char('R'),
+            env: KeyBinding::char('E'),
+            cwd: KeyBinding::char('D'),
+            prefix: KeyBinding::new(KeyCode::Char('g'), KeyModifiers::CONTROL),
+            scroll_mode: KeyBinding::new(KeyCode::Char('s'), KeyModifiers::ALT),
+            background: KeyBinding::new(KeyCode::Char('z'), KeyModifiers::CONTROL),
+            terminate: KeyBinding::new(KeyCode::Char('t'), KeyModifiers::ALT),
+            kill: KeyBinding::new(KeyCode::Char('k'), KeyModifiers::ALT),
+            jobs: KeyBinding::char('J'),
+            layout: KeyBinding::char('L'),
+            rerun: KeyBinding::new(KeyCode::Char('r'), KeyModifiers::ALT),
+            watch: KeyBinding::new(KeyCode::Char('w'), KeyModifiers::ALT),
+        }
+    }
+}
+
+impl KeyBindings {
+    /// Label of an execution view key pressed after the prefix, e.g.
+    /// `"^g ^z"`.
+    pub fn prefixed(&self, key: KeyBinding) -> String {
+        format!("{} {}", self.prefix.label(), key.label())
+    }
+}
+
+/// A key with modifiers, written as e.g. `"q"`, `"H"`, `"ctrl+r"` or `"alt+enter"`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "String")]
+pub struct KeyBinding {
+    pub code: KeyCode,
+    /// Shift is folded into characters and only kept for named keys.
+    pub modifiers: KeyModifiers,
+}
+
+impl KeyBinding {
+    pub fn new(code: KeyCode, modifiers: KeyModifiers) -> Self {
+        Self { code, modifiers }
+    }
+
+    fn char(c: char) -> Self {
+        Self::new(KeyCode::Char(c), KeyModifiers::NONE)
+    }
+
+    /// Whether a key event triggers this binding.
+    ///
+    /// Shift is ignored for characters since terminals report it
+    /// inconsistently; the character itself is already upper- or lowercase.
+    pub fn matches(&self, key: &KeyEvent) -> bool {
+        let relevant = match key.code {
+            KeyCode::Char(_) => KeyModifiers::CONTROL | KeyModifiers::ALT,
+            _ => KeyModifiers::CONTROL | KeyModifiers::ALT | KeyModifiers::SHIFT,
+        };
+        let code = match key.code {
+            KeyCode::Char(c) if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                KeyCode::Char(c.to_ascii_lowercase())
+            }
+            code => code,
+        };
+        code == self.code && key.modifiers & relevant == self.modifiers
+    }
+
+    /// Short label for the help bar (e.g. `"^r"`, `"H"`, `"⏎"`).
+    pub fn label(&self) -> String {
+        let key = match self.code {
+            KeyCode::Char(' ') => "Space".to_string(),
+            KeyCode::Char(c) => c.to_string(),
+            KeyCode::Enter => "⏎".to_string(),
+            KeyCode::Tab => "⇥".to_string(),
+            code => key_name(code),
+        };
+        let mut label = String::new();
+        if self.modifiers.contains(KeyModifiers::ALT) {
+            label.push_str("M-");
+        }
+        if self.modifiers.contains(KeyModifiers::CONTROL) {
+            label.push('^');
+        }
+        if self.modifiers.contains(KeyModifiers::SHIFT) {
+            label.push_str("S-");
+        }
+        label + &key
+    }
+}
+
+const NAMED_KEYS: &[(&str, KeyCode)] = &[
+    ("enter", KeyCode::Enter),
+    ("esc", KeyCode::Esc),
+    ("tab", KeyCode::Tab),
+    ("backtab", KeyCode::BackTab),
+    ("space", KeyCode::Char(' ')),
+    ("backspace", KeyCode::Backspace),
+    ("delete", KeyCode::Delete),
+    ("insert", KeyCode::Insert),
+    ("up", KeyCode::Up),
+    ("down", KeyCode::Down),
+    ("left", KeyCode::Left),
+    ("right", KeyCode::Right),
+    ("home", KeyCode::Home),
+    ("end", KeyCode::End),
+    ("pageup", KeyCode::PageUp),
+    ("pagedown", KeyCode::PageDown),
+    ("plus", KeyCode::Char('+')),
+];
+
+fn key_name(code: KeyCode) -> String {
+    if let KeyCode::F(n) = code {
+        return format!("f{n}");
+    }
+    NAMED_KEYS
+        .iter()
+        .find(|(_, c)| *c == code)
+        .map(|(name, _)| name.to_string())
+        .unwrap_or_else(|| match code {
+            KeyCode::Char(c) => c.to_string(),
+            other => format!("{other:?}").to_lowercase(),
+        })
+}
+
+impl FromStr for KeyBinding {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts: Vec<&str> = s.split('+').collect();
+        let key = parts
+            .pop()
+            .filter(|k| !k.is_empty())
+            .ok_or_else(|| format!("invalid key '{s}' (write '+' as 'plus')"))?;
+
+        let mut modifiers = KeyModifiers::NONE;
+        for part in parts {
+            modifiers |= match part.to_lowercase().as_str() {
+                "ctrl" | "control" => KeyModifiers::CONTROL,
+                "alt" | "meta" => KeyModifiers::ALT,
+                "shift" => KeyModifiers::SHIFT,
+                other => return Err(format!("unknown modifier '{other}' in key '{s}'")),
+            };
+        }
+
+        let mut chars = key.chars();
+        let code = match (chars.next(), chars.next()) {
+            (Some(c), None) => KeyCode::Char(c),
+            _ => {
+                let lower = key.to_lowercase();
+                if let Some(n) = lower.strip_prefix('f').and_then(|n| n.parse::<u8>().ok()) {
+                    KeyCode::F(n)
+                } else {
+                    NAMED_KEYS
+                        .iter()
+                        .find(|(name, _)| *name == lower)
+                        .map(|(_, code)| *code)
+                        .ok_or_else(|| format!("unknown key '{key}' in '{s}'"))?
+                }
+            }
+        };
+
+        // Fold shift into characters; terminals report ctrl+letter in lowercase.
+        let code = match code {
+            KeyCode::Char(c) if modifiers.contains(KeyModifiers::CONTROL) => {
+                modifiers.remove(KeyModifiers::SHIFT);
+                KeyCode::Char(c.to_ascii_lowercase())
+            }
+            KeyCode::Char(c) if modifiers.contains(KeyModifiers::SHIFT) => {
+                modifiers.remove(KeyModifiers::SHIFT);
+                KeyCode::Char(c.to_ascii_uppercase())
+            }
+            code => code,
+        };
+        Ok(Self::new(code, modifiers))
+    }
+}
+
+impl TryFrom<String> for KeyBinding {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl fmt::Display for KeyBinding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.modifiers.contains(KeyModifiers::CONTROL) {
+            f.write_str("ctrl+")?;
+        }
+        if self.modifiers.contains(KeyModifiers::ALT) {
+            f.write_str("alt+")?;
+        }
+        if self.modifiers.contains(KeyModifiers::SHIFT) {
+            f.write_str("shift+")?;
+        }
+        match self.code {
+            KeyCode::Char(c) if c != ' ' && c != '+' => write!(f, "{c}"),
+            code => f.write_str(&key_name(code)),
+        }
+    }
+}
+
+impl Config {
+    /// Default config file location in the config directory.
+    pub fn default_path() -> Option<PathBuf> {
+        crate::paths::config_dir().map(|dir| dir.join("config.toml"))
+    }
+
+    /// Load the config from a file. A missing file yields the defaults;
+    /// a file that cannot be parsed is an error.
+    pub fn load(path: PathBuf) -> color_eyre::Result<Self> {
+        let mut config = match std::fs::read_to_string(&path) {
+            Ok(text) => Self::parse(&text).map_err(|e| {
+                color_eyre::eyre::eyre!("Failed to parse config '{}': {}", path.display(), e)
+            })?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Self::default(),
+            Err(e) => {
+                return Err(color_eyre::eyre::eyre!(
+                    "Failed to read config '{}': {}",
+                    path.display(),
+                    e
+                ))
+            }
+        };
+        config.path = Some(path);
+        Ok(config)
+    }
+
+    /// Parse config file contents.
+    pub fn parse(text: &str) -> Result<Self, toml_edit::de::Error> {
+        toml_edit::de::from_str(text)
+    }
+
+    /// The configured theme, if set and known.
+    pub fn theme_name(&self) -> Option<ThemeName> {
+        self.theme.as_deref().and_then(|t| t.parse().ok())
+    }
+
+    /// Settings for a spec, looked up by its source (spec command or file
+    /// path) first and then by its binary name.
+    pub fn spec(&self, source: &str, bin: &str) -> Option<&SpecConfig> {
+        self.specs.get(source).or_else(|| self.specs.get(bin))
+    }
+
+    /// Remember the theme and write it to the config file.
+    pub fn save_theme(&mut self, theme: ThemeName) -> std::io::Result<()> {
+        self.theme = Some(theme.slug().to_string());
+        let Some(ref path) = self.path else {
+            return Ok(());
+        };
+        let mut doc = match std::fs::read_to_string(path) {
+            Ok(text) => text
+                .parse::<toml_edit::DocumentMut>()
+                .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => toml_edit::DocumentMut::new(),
+            Err(e) => return Err(e),
+        };
+        doc["theme"] = toml_edit::value(theme.slug());
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, doc.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(code: KeyCode, modifiers: KeyModifiers) -> KeyEvent {
+        KeyEvent::new(code, modifiers)
+    }
+
+    #[test]
+    fn test_parse_full_config() {
+        let config = Config::parse(
+            r#"
+            theme = "nord"
+
+            [behavior]
+            preview_enter = "print"
+            shell = "powershell"
+            scrollback = 500
+            timeout = "90s"
+            resolve_paths = true
+            layout = "below"
+            rerun_output = "clear"
+
+            [watch]
+            include = ["src/**/*.rs"]
+            interval = "1m"
+
+            [cache]
+            watch = ["tasks.toml"]
+
+            [keys]
+            execute = "ctrl+x"
+            history = "alt+h"
+
+            [specs."mise tasks ls --usage"]
+            cmd = "mise run"
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(config.theme_name(), Some(ThemeName::Nord));
+        assert_eq!(config.behavior.preview_enter, PreviewEnter::Print);
+        assert_eq!(config.behavior.shell, Dialect::PowerShell);
+        assert_eq!(config.behavior.scrollback, 500);
+        assert!(config.behavior.resolve_paths);
+        assert_eq!(config.behavior.layout, OutputLayout::Below);
+        assert_eq!(config.behavior.rerun_output, RerunOutput::Clear);
+        assert_eq!(config.watch.include, ["src/**/*.rs"]);
+        assert_eq!(config.watch.exclude, WatchConfig::default().exclude);
+        assert_eq!(config.cache.watch, ["tasks.toml"]);
+        assert_eq!(
+            config.watch.interval,
+            Some(HumanDuration(Duration::from_secs(60)))
+        );
+        assert_eq!(
+            config.behavior.timeout,
+            Some(HumanDuration(Duration::from_secs(90)))
+        );
+        assert_eq!(config.keys.execute.to_string(), "ctrl+x");
+        assert_eq!(config.keys.history.to_string(), "alt+h");
+        assert_eq!(
+            config.keys.quit,
+            KeyBindings::default().quit,
+            "unset keys keep defaults"
+        );
+        assert_eq!(
+            config
+                .spec("mise tasks ls --usage", "mise")
+                .unwrap()
+                .cmd
+                .as_deref(),
+            Some("mise run")
+        );
+    }
+
+    #[test]
+    fn test_empty_config_uses_defaults() {
+        let config = Config::parse("").unwrap();
+        assert_eq!(config, Config::default());
+        assert_eq!(config.behavior.preview_enter, PreviewEnter::Execute);
+        assert_eq!(config.behavior.scrollback, 10_000);
+        assert_eq!(config.behavior.timeout, None);
+        assert_eq!(config.behavior.kill_after.to_string(), "5s");
+        assert_eq!(config.theme_name(), None);
+    }
+
+    #[test]
+    fn test_human_duration_parse_and_display() {
+        for (input, millis, expected) in [
+            ("500ms", 500, "500ms"),
+            ("30s", 30_000, "30s"),
+            ("45", 45_000, "45s"),
+            ("1.5m", 90_000, "90s"),
+            ("5m", 300_000, "5m"),
+            ("2h", 7_200_000, "2h"),
+        ] {
+            let duration: HumanDuration = input.parse().unwrap();
+            assert_eq!(duration.0, Duration::from_millis(millis), "parsing {input}");
+            assert_eq!(duration.to_string(), expected);
+        }
+        assert!("".parse::<HumanDuration>().is_err());
+        assert!("5 days".parse::<HumanDuration>().is_err());
+        assert!(Config::parse("[behavior]\ntimeout = \"soon\"").is_err());
+    }
+
+    #[test]
+    fn test_out_of_range_duration_is_an_error() {
+        let err = "99999999999999999999999h"
+            .parse::<HumanDuration>()
+            .unwrap_err();
+        assert!(
+            err.starts_with("invalid duration '99999999999999999999999h'"),
+            "{err}"
+        );
+        let config = format!("[behavior]\nkill_after = \"{}s\"", "9".repeat(40));
+        assert!(Config::parse(&config).is_err());
+    }
+
+    #[test]
+    fn test_invalid_key_is_an_error() {
+        let err = Config::parse("[keys]\nquit = \"hyper+q\"").unwrap_err();
+        assert!(
+            err.to_string().contains("unknown modifier 'hyper'"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn test_spec_lookup_falls_back_to_bin() {
+        let config = Config::parse("[specs.mycli]\ncmd = \"mycli --verbose\"").unwrap();
+        assert_eq!(
+            config
+                .spec("./mycli.usage.kdl", "mycli")
+                .unwrap()
+                .cmd
+                .as_deref(),
+            Some("mycli --verbose")
+        );
+        assert!(config.spec("./other.kdl", "other").is_none());
+    }
+
+    #[test]
+    fn test_key_binding_parse_and_display() {
+        for (input, expected) in [
+            ("q", "q"),
+            ("H", "H"),
+            ("shift+h", "H"),
+            ("ctrl+R", "ctrl+r"),
+            ("Ctrl+Alt+Enter", "ctrl+alt+enter"),
+            ("f5", "f5"),
+            ("space", "space"),
+            ("plus", "plus"),
+        ] {
+            let binding: KeyBinding = input.parse().unwrap();
+            assert_eq!(binding.to_string(), expected, "parsing {input}");
+            assert_eq!(binding.to_string().parse::<KeyBinding>().unwrap(), binding);
+        }
+        assert!("".parse::<KeyBinding>().is_err());
+        assert!("ctrl+".parse::<KeyBinding>().is_err());
+        assert!("nosuchkey".parse::<KeyBinding>().is_err());
+    }
+
+    #[test]
+    fn test_key_binding_matches() {
+        let ctrl_r: KeyBinding = "ctrl+r".parse().unwrap();
+        assert!(ctrl_r.matches(&key(KeyCode::Char('r'), KeyModifiers::CONTROL)));
+        assert!(ctrl_r.matches(&key(
+            KeyCode::Char('R'),
+            KeyModifiers::CONTROL | KeyModifiers::SHIFT
+        )));
+        assert!(!ctrl_r.matches(&key(KeyCode::Char('r'), KeyModifiers::NONE)));
+
+        let upper_h: KeyBinding = "H".parse().unwrap();
+        assert!(upper_h.matches(&key(KeyCode::Char('H'), KeyModifiers::SHIFT)));
+        assert!(upper_h.matches(&key(KeyCode::Char('H'), KeyModifiers::NONE)));
+        assert!(!upper_h.matches(&key(KeyCode::Char('h'), KeyModifiers::NONE)));
+
+        let shift_tab: KeyBinding = "shift+tab".parse().unwrap();
+        assert!(shift_tab.matches(&key(KeyCode::Tab, KeyModifiers::SHIFT)));
+        assert!(!shift_tab.matches(&key(KeyCode::Tab, KeyModifiers::NONE)));
+    }
+
+    #[test]
+    fn test_key_binding_label() {
+        assert_eq!(KeyBindings::default().execute.label(), "^r");
+        assert_eq!(KeyBindings::default().history.label(), "H");
+        assert_eq!("alt+enter".parse::<KeyBinding>().unwrap().label(), "M-⏎");
+    }
+
+    #[test]
+    fn test_save_theme_preserves_other_content() {
+        let dir = std::env::temp_dir().join(format!("tuisage-test-config-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let path = dir.join("config.toml");
+
+        // Missing file: defaults, then created on save
+        let mut config = Config::load(path.clone()).unwrap();
+        config.save_theme(ThemeName::Nord).unwrap();
+        assert_eq!(
+            Config::load(path.clone()).unwrap().theme_name(),
+            Some(ThemeName::Nord)
+        );
+
+        std::fs::write(
+            &path,
+            "# my settings\ntheme = \"nord\"\n\n[behavior]\npreview_enter = \"print\" # for eval\n",
+        )
+        .unwrap();
+        let mut config = Config::load(path.clone()).unwrap();
+        config.save_theme(ThemeName::TokyoNight).unwrap();
+
+        let text = std::fs::read_to_string(&path).unwrap();
+        assert!(text.contains("# my settings"));
+        assert!(text.contains("theme = \"tokyo-night\""));
+        assert!(text.contains("preview_enter = \"print\" # for eval"));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_load_reports_parse_errors_with_path() {
+        let dir =
+            std::env::temp_dir().join(format!("tuisage-test-config-bad-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("config.toml");
+        std::fs::write(&path, "theme = ").unwrap();
+
+        let err = Config::load(path).unwrap_err();
+        assert!(err.to_string().contains("config.toml"), "{err}");
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,231 @@
+//! Environment variables set or unset for a run.
+//!
+//! Each command path has its own list of overrides, edited in the
+//! environment overlay. They are applied to the PTY child when the command
+//! is executed and shown before the command in the syntax of the configured
+//! shell, e.g. `NAME=value` assignments with `env -u NAME` for variables that
+//! are removed.
+
+use serde::{Deserialize, Serialize};
+
+use crate::quoting::{quote_powershell_single, Dialect};
+
+/// An environment variable override.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EnvVar {
+    pub name: String,
+    /// Value to set, or `None` to remove the variable from the environment.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value: Option<String>,
+}
+
+impl EnvVar {
+    pub fn set(name: impl Into<String>, value: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            value: Some(value.into()),
+        }
+    }
+
+    pub fn unset(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            value: None,
+        }
+    }
+
+    /// Parse `NAME=value` as typed in the environment overlay.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let (name, value) = text
+            .split_once('=')
+            .ok_or_else(|| "expected NAME=value".to_string())?;
+        let name = name.trim();
+        if !is_valid_name(name) {
+            return Err(format!("'{name}' is not a valid variable name"));
+        }
+        Ok(Self::set(name, value))
+    }
+}
+
+/// Whether `name` can be used as a variable name in a shell assignment:
+/// letters, digits and underscores, not starting with a digit.
+pub fn is_valid_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// A piece of the text that applies the overrides, kept apart so the
+/// preview can color it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PrefixPart {
+    /// A command such as `env` or `set`.
+    Command(String),
+    /// A variable name with the syntax around it, e.g. `-u NAME`, `NAME=`
+    /// or `$env:NAME=`.
+    Name(String),
+    /// A value, quoted as needed.
+    Value(String),
+    /// Spaces and separators such as `; ` or `" && `.
+    Plain(String),
+}
+
+impl PrefixPart {
+    pub fn text(&self) -> &str {
+        match self {
+            Self::Command(text) | Self::Name(text) | Self::Value(text) | Self::Plain(text) => text,
+        }
+    }
+}
+
+/// What to put before the command so `dialect` runs it with the overrides:
+///
+/// - POSIX shells and fish: `env -u NAME` for removed variables, then
+///   `NAME=value` assignments.
+/// - PowerShell: `$env:NAME='value'; ` statements, `$null` removing a
+///   variable. They change the session's environment, not just the command's.
+/// - cmd: `set "NAME=value" && ` commands, with an empty value removing the
+///   variable. Values cmd can't write literally (with `"`, `%`, `!` or line
+///   breaks) and empty ones are an error.
+pub fn prefix(vars: &[EnvVar], dialect: Dialect) -> Result<Vec<PrefixPart>, String> {
+    use PrefixPart::{Command, Name, Plain, Value};
+    let mut parts = Vec::new();
+    match dialect {
+        Dialect::Posix | Dialect::Fish => {
+            let unset: Vec<&EnvVar> = vars.iter().filter(|v| v.value.is_none()).collect();
+            if !unset.is_empty() {
+                parts.push(Command("env".to_string()));
+                for var in unset {
+                    parts.push(Plain(" ".to_string()));
+                    parts.push(Name(format!("-u {}", var.name)));
+                }
+                parts.push(Plain(" ".to_string()));
+            }
+            for var in vars {
+                if let Some(ref value) = var.value {
+                    parts.push(Name(format!("{}=", var.name)));
+                    parts.push(Value(dialect.quote(value)));
+                    parts.push(Plain(" ".to_string()));
+                }
+            }
+        }
+        Dialect::PowerShell => {
+            for var in vars {
+                parts.push(Name(format!("$env:{}=", var.name)));
+                parts.push(Value(match var.value {
+                    // A bare word would be run as a command
+                    Some(ref value) => format!("'{}'", quote_powershell_single(value)),
+                    None => "$null".to_string(),
+                }));
+                parts.push(Plain("; ".to_string()));
+            }
+        }
+        Dialect::Cmd => {
+            for var in vars {
+                let value = var.value.as_deref().unwrap_or_default();
+                if var.value.as_deref() == Some("") {
+                    return Err(format!("cmd can't set {} to an empty value", var.name));
+                }
+                if value.contains(['"', '%', '!', '\n', '\r']) {
+                    return Err(format!("cmd can't write the value of {}", var.name));
+                }
+                parts.push(Command("set".to_string()));
+                parts.push(Plain(" \"".to_string()));
+                parts.push(Name(format!("{}=", var.name)));
+                if !value.is_empty() {
+                    parts.push(Value(value.to_string()));
+                }
+                parts.push(Plain("\" && ".to_string()));
+            }
+        }
+    }
+    Ok(parts)
+}
+
+/// Prepend the overrides to a command line quoted for `dialect`.
+pub fn with_prefix(vars: &[EnvVar], dialect: Dialect, command: String) -> Result<String, String> {
+    let parts = prefix(vars, dialect)?;
+    let mut line: String = parts.iter().map(PrefixPart::text).collect();
+    line.push_str(&command);
+    Ok(line)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            EnvVar::parse("TOKEN=abc=1"),
+            Ok(EnvVar::set("TOKEN", "abc=1"))
+        );
+        assert_eq!(EnvVar::parse(" DEBUG="), Ok(EnvVar::set("DEBUG", "")));
+        assert_eq!(
+            EnvVar::parse("TOKEN"),
+            Err("expected NAME=value".to_string())
+        );
+        assert_eq!(
+            EnvVar::parse("1X=a"),
+            Err("'1X' is not a valid variable name".to_string())
+        );
+    }
+
+    #[test]
+    fn test_prefix() {
+        let vars = vec![
+            EnvVar::set("LOG", "debug"),
+            EnvVar::unset("TOKEN"),
+            EnvVar::set("MSG", "it's"),
+        ];
+        let line = |dialect| with_prefix(&vars, dialect, "mycli run".to_string());
+        assert_eq!(
+            line(Dialect::Posix).unwrap(),
+            r"env -u TOKEN LOG=debug MSG='it'\''s' mycli run"
+        );
+        assert_eq!(
+            line(Dialect::Fish).unwrap(),
+            r"env -u TOKEN LOG=debug MSG='it\'s' mycli run"
+        );
+        assert_eq!(
+            line(Dialect::PowerShell).unwrap(),
+            "$env:LOG='debug'; $env:TOKEN=$null; $env:MSG='it''s'; mycli run"
+        );
+        assert_eq!(
+            line(Dialect::Cmd).unwrap(),
+            r#"set "LOG=debug" && set "TOKEN=" && set "MSG=it's" && mycli run"#
+        );
+        for dialect in [
+            Dialect::Posix,
+            Dialect::Fish,
+            Dialect::PowerShell,
+            Dialect::Cmd,
+        ] {
+            assert_eq!(
+                with_prefix(&[], dialect, "mycli".to_string()).unwrap(),
+                "mycli"
+            );
+        }
+    }
+
+    #[test]
+    fn test_cmd_prefix_rejects_unwritable_values() {
+        let line = |var| with_prefix(&[var], Dialect::Cmd, "mycli".to_string());
+        assert_eq!(
+            line(EnvVar::set("A", "x & y | z")).unwrap(),
+            r#"set "A=x & y | z" && mycli"#
+        );
+        assert_eq!(
+            line(EnvVar::set("A", "say \"hi\"")),
+            Err("cmd can't write the value of A".to_string())
+        );
+        assert!(line(EnvVar::set("A", "%PATH%")).is_err());
+        assert!(line(EnvVar::set("A", "a\nb")).is_err());
+        assert_eq!(
+            line(EnvVar::set("A", "")),
+            Err("cmd can't set A to an empty value".to_string())
+        );
+    }
+}
@@ -0,0 +1,153 @@
+//! Named favorites (saved command presets).
+//!
+//! Favorites are stored per spec in `<data dir>/favorites/<spec>.json`, each
+//! holding the builder state of a fully configured command under a name.
+//! They can be loaded from the favorites picker or with `--preset <name>`.
+
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use crate::app::BuilderSnapshot;
+
+/// A saved command preset.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Favorite {
+    pub name: String,
+    /// Command string at the time the favorite was saved (for display).
+    pub command: String,
+    /// Builder state restored when the favorite is loaded.
+    pub state: BuilderSnapshot,
+}
+
+/// The favorites of one spec, optionally backed by a file.
+pub struct Favorites {
+    /// File the favorites are persisted to. `None` keeps them in memory only.
+    path: Option<PathBuf>,
+    favorites: Vec<Favorite>,
+}
+
+impl Favorites {
+    /// Favorites that are never written to disk.
+    pub fn in_memory() -> Self {
+        Self {
+            path: None,
+            favorites: Vec::new(),
+        }
+    }
+
+    /// Default favorites file for a spec identity.
+    pub fn default_path(spec_id: &str) -> Option<PathBuf> {
+        let file_name: String = spec_id
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+        crate::paths::data_dir().map(|dir| dir.join("favorites").join(format!("{file_name}.json")))
+    }
+
+    /// Load favorites from a file. A missing or unreadable file yields no favorites.
+    pub fn load(path: PathBuf) -> Self {
+        let favorites = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|text| serde_json::from_str(&text).ok())
+            .unwrap_or_default();
+        Self {
+            path: Some(path),
+            favorites,
+        }
+    }
+
+    /// All favorites, in the order they were saved.
+    pub fn list(&self) -> &[Favorite] {
+        &self.favorites
+    }
+
+    /// Find a favorite by name.
+    pub fn find(&self, name: &str) -> Option<&Favorite> {
+        self.favorites.iter().find(|f| f.name == name)
+    }
+
+    /// Save a favorite, replacing any existing one with the same name.
+    pub fn save(&mut self, favorite: Favorite) -> std::io::Result<()> {
+        match self.favorites.iter_mut().find(|f| f.name == favorite.name) {
+            Some(existing) => *existing = favorite,
+            None => self.favorites.push(favorite),
+        }
+        self.persist()
+    }
+
+    /// Remove the favorite at `index`.
+    pub fn remove(&mut self, index: usize) -> std::io::Result<()> {
+        if index < self.favorites.len() {
+            self.favorites.remove(index);
+        }
+        self.persist()
+    }
+
+    fn persist(&self) -> std::io::Result<()> {
+        let Some(ref path) = self.path else {
+            return Ok(());
+        };
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let text = serde_json::to_string_pretty(&self.favorites)?;
+        std::fs::write(path, text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn favorite(name: &str, command: &str) -> Favorite {
+        Favorite {
+            name: name.to_string(),
+            command: command.to_string(),
+            state: BuilderSnapshot::default(),
+        }
+    }
+
+    #[test]
+    fn test_save_replaces_same_name() {
+        let mut favorites = Favorites::in_memory();
+        favorites.save(favorite("prod", "mycli deploy prod")).unwrap();
+        favorites.save(favorite("dev", "mycli deploy dev")).unwrap();
+        favorites.save(favorite("prod", "mycli deploy prod --tag latest")).unwrap();
+
+        assert_eq!(favorites.list().len(), 2);
+        assert_eq!(favorites.find("prod").unwrap().command, "mycli deploy prod --tag latest");
+        assert!(favorites.find("missing").is_none());
+    }
+
+    #[test]
+    fn test_persist_and_reload() {
+        let dir = std::env::temp_dir().join(format!("tuisage-test-favorites-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let path = dir.join("mycli.json");
+
+        let mut favorites = Favorites::load(path.clone());
+        assert!(favorites.list().is_empty());
+        favorites.save(favorite("a", "mycli init")).unwrap();
+        favorites.save(favorite("b", "mycli run build")).unwrap();
+        favorites.remove(0).unwrap();
+
+        let reloaded = Favorites::load(path);
+        let names: Vec<&str> = reloaded.list().iter().map(|f| f.name.as_str()).collect();
+        assert_eq!(names, vec!["b"]);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_default_path_sanitizes_spec_id() {
+        if let Some(path) = Favorites::default_path("mise run") {
+            assert_eq!(path.file_name().unwrap(), "mise_run.json");
+        }
+    }
+}
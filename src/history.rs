@@ -0,0 +1,217 @@
+//! Persistent command history.
+//!
+//! Every executed command is appended to a JSON Lines file in the data
+//! directory together with the builder state needed to restore it, so a
+//! previous command can be picked from the history overlay and re-run or
+//! tweaked.
+
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use crate::app::BuilderSnapshot;
+
+/// Maximum number of entries kept on disk. Older entries are dropped when
+/// the file grows past this size.
+const MAX_ENTRIES: usize = 1000;
+
+/// One executed command.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    /// Identity of the spec the command was built from (see `App::spec_id`).
+    pub spec: String,
+    /// Unix timestamp (seconds) when the command was started.
+    pub timestamp: u64,
+    /// Working directory the command ran in.
+    pub cwd: Option<PathBuf>,
+    /// Command string as displayed in the preview.
+    pub command: String,
+    /// Argument vector passed to the process.
+    pub argv: Vec<String>,
+    /// Exit status description (e.g. "Success"), if the process finished.
+    pub exit_status: Option<String>,
+    /// Builder state to restore when the entry is picked.
+    pub state: BuilderSnapshot,
+}
+
+impl HistoryEntry {
+    /// Whether the recorded exit status indicates success.
+    pub fn succeeded(&self) -> Option<bool> {
+        self.exit_status.as_deref().map(|s| s == "Success")
+    }
+}
+
+/// Command history, optionally backed by a file.
+pub struct History {
+    /// File the history is persisted to. `None` keeps history in memory only.
+    path: Option<PathBuf>,
+    /// Entries in chronological order (oldest first).
+    entries: Vec<HistoryEntry>,
+}
+
+impl History {
+    /// History that is never written to disk.
+    pub fn in_memory() -> Self {
+        Self {
+            path: None,
+            entries: Vec::new(),
+        }
+    }
+
+    /// Default history file location in the data directory.
+    pub fn default_path() -> Option<PathBuf> {
+        crate::paths::data_dir().map(|dir| dir.join("history.jsonl"))
+    }
+
+    /// Load history from a JSON Lines file. A missing file yields an empty
+    /// history; malformed lines are skipped.
+    pub fn load(path: PathBuf) -> Self {
+        let entries = std::fs::read_to_string(&path)
+            .map(|text| {
+                text.lines()
+                    .filter_map(|line| serde_json::from_str(line).ok())
+                    .collect()
+            })
+            .unwrap_or_default();
+        Self {
+            path: Some(path),
+            entries,
+        }
+    }
+
+    /// Append an entry and persist it.
+    pub fn record(&mut self, entry: HistoryEntry) -> std::io::Result<()> {
+        self.entries.push(entry);
+        let Some(ref path) = self.path else {
+            return Ok(());
+        };
+        if self.entries.len() > MAX_ENTRIES {
+            let excess = self.entries.len() - MAX_ENTRIES;
+            self.entries.drain(..excess);
+            write_all(path, &self.entries)
+        } else {
+            append(path, self.entries.last().unwrap())
+        }
+    }
+
+    /// Entries recorded for the given spec, most recent first.
+    pub fn entries_for(&self, spec: &str) -> Vec<&HistoryEntry> {
+        self.entries.iter().rev().filter(|e| e.spec == spec).collect()
+    }
+}
+
+fn append(path: &Path, entry: &HistoryEntry) -> std::io::Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    let line = serde_json::to_string(entry)?;
+    writeln!(file, "{line}")
+}
+
+fn write_all(path: &Path, entries: &[HistoryEntry]) -> std::io::Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut text = String::new();
+    for entry in entries {
+        text.push_str(&serde_json::to_string(entry)?);
+        text.push('\n');
+    }
+    std::fs::write(path, text)
+}
+
+/// Current time as Unix seconds.
+pub fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Format the age of a timestamp relative to `now` (e.g. "5m ago").
+pub fn format_age(timestamp: u64, now: u64) -> String {
+    let secs = now.saturating_sub(timestamp);
+    match secs {
+        0..=59 => "just now".to_string(),
+        60..=3599 => format!("{}m ago", secs / 60),
+        3600..=86_399 => format!("{}h ago", secs / 3600),
+        _ => format!("{}d ago", secs / 86_400),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(spec: &str, command: &str) -> HistoryEntry {
+        HistoryEntry {
+            spec: spec.to_string(),
+            timestamp: 100,
+            cwd: None,
+            command: command.to_string(),
+            argv: command.split(' ').map(String::from).collect(),
+            exit_status: Some("Success".to_string()),
+            state: BuilderSnapshot::default(),
+        }
+    }
+
+    fn temp_path(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("tuisage-test-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir.join("history.jsonl")
+    }
+
+    #[test]
+    fn test_entries_for_filters_by_spec_newest_first() {
+        let mut history = History::in_memory();
+        history.record(entry("mycli", "mycli init")).unwrap();
+        history.record(entry("other", "other run")).unwrap();
+        history.record(entry("mycli", "mycli deploy")).unwrap();
+
+        let commands: Vec<&str> = history
+            .entries_for("mycli")
+            .iter()
+            .map(|e| e.command.as_str())
+            .collect();
+        assert_eq!(commands, vec!["mycli deploy", "mycli init"]);
+    }
+
+    #[test]
+    fn test_record_and_reload_roundtrip() {
+        let path = temp_path("roundtrip");
+        let mut history = History::load(path.clone());
+        assert!(history.entries_for("mycli").is_empty());
+        history.record(entry("mycli", "mycli init")).unwrap();
+        history.record(entry("mycli", "mycli run build")).unwrap();
+
+        let reloaded = History::load(path.clone());
+        assert_eq!(reloaded.entries_for("mycli").len(), 2);
+        assert_eq!(reloaded.entries_for("mycli")[0].command, "mycli run build");
+        let _ = std::fs::remove_dir_all(path.parent().unwrap());
+    }
+
+    #[test]
+    fn test_load_skips_malformed_lines() {
+        let path = temp_path("malformed");
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        let good = serde_json::to_string(&entry("mycli", "mycli init")).unwrap();
+        std::fs::write(&path, format!("not json\n{good}\n")).unwrap();
+
+        let history = History::load(path.clone());
+        assert_eq!(history.entries_for("mycli").len(), 1);
+        let _ = std::fs::remove_dir_all(path.parent().unwrap());
+    }
+
+    #[test]
+    fn test_format_age() {
+        assert_eq!(format_age(1000, 1030), "just now");
+        assert_eq!(format_age(1000, 1000 + 5 * 60), "5m ago");
+        assert_eq!(format_age(1000, 1000 + 3 * 3600), "3h ago");
+        assert_eq!(format_age(1000, 1000 + 2 * 86_400), "2d ago");
+        assert_eq!(format_age(2000, 1000), "just now");
+    }
+}
@@ -0,0 +1,112 @@
+//! Commands started from the builder.
+//!
+//! Every executed command is a [`Job`]. The one shown in the execution view
+//! is in the foreground; the others keep running in the background while
+//! the next command is built, and are listed in the jobs overlay.
+
+use std::time::Duration;
+
+use crate::components::execution::ExecutionComponent;
+use crate::history::HistoryEntry;
+
+/// A running or finished command.
+pub struct Job {
+    /// Number shown in the jobs list, unique for the session.
+    pub id: usize,
+    pub execution: ExecutionComponent,
+    /// History entry, recorded when the job is closed so the exit status
+    /// can be included.
+    pub history: Option<HistoryEntry>,
+    /// Whether the job finishing has been reported in the status bar.
+    pub finish_reported: bool,
+    /// Whether the command was stopped to run again once it has exited.
+    pub restarting: bool,
+}
+
+impl Job {
+    pub fn new(id: usize, execution: ExecutionComponent, history: Option<HistoryEntry>) -> Self {
+        Self {
+            id,
+            execution,
+            history,
+            finish_reported: false,
+            restarting: false,
+        }
+    }
+
+    pub fn is_running(&self) -> bool {
+        !self.execution.exited()
+    }
+
+    /// The displayed command.
+    pub fn command(&self) -> &str {
+        self.execution.command_display()
+    }
+
+    /// Status for the jobs list, e.g. `"● running 12s"` or `"✗ exit 1 · 3s"`.
+    pub fn status(&self) -> String {
+        let elapsed = format_elapsed(self.execution.elapsed());
+        let watching = if self.execution.is_watching() {
+            " · watching"
+        } else {
+            ""
+        };
+        if self.is_running() {
+            return format!("● running {elapsed}{watching}");
+        }
+        let status = self.execution.exit_status();
+        let mark = if status.as_deref() == Some("Success") {
+            "✓"
+        } else {
+            "✗"
+        };
+        format!(
+            "{mark} {} · {elapsed}{watching}",
+            short_exit_status(status.as_deref().unwrap_or("unknown"))
+        )
+    }
+}
+
+/// Shorten an exit status description ("Success", "Exited with code 1",
+/// "Terminated by SIGKILL") for display.
+pub fn short_exit_status(status: &str) -> String {
+    if status == "Success" {
+        "exit 0".to_string()
+    } else if let Some(code) = status.strip_prefix("Exited with code ") {
+        format!("exit {code}")
+    } else if let Some(signal) = status.strip_prefix("Terminated by ") {
+        signal.to_string()
+    } else {
+        status.to_lowercase()
+    }
+}
+
+/// Format a duration compactly, e.g. `"45s"`, `"3m05s"` or `"2h10m"`.
+pub fn format_elapsed(elapsed: Duration) -> String {
+    let secs = elapsed.as_secs();
+    match secs {
+        0..=59 => format!("{secs}s"),
+        60..=3599 => format!("{}m{:02}s", secs / 60, secs % 60),
+        _ => format!("{}h{:02}m", secs / 3600, secs % 3600 / 60),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_short_exit_status() {
+        assert_eq!(short_exit_status("Success"), "exit 0");
+        assert_eq!(short_exit_status("Exited with code 2"), "exit 2");
+        assert_eq!(short_exit_status("Terminated by SIGKILL"), "SIGKILL");
+        assert_eq!(short_exit_status("error: no child"), "error: no child");
+    }
+
+    #[test]
+    fn test_format_elapsed() {
+        assert_eq!(format_elapsed(Duration::from_millis(900)), "0s");
+        assert_eq!(format_elapsed(Duration::from_secs(185)), "3m05s");
+        assert_eq!(format_elapsed(Duration::from_secs(7800)), "2h10m");
+    }
+}
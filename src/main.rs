@@ -1,26 +1,44 @@
+// Newer clippy flags match arms with a nested `if`/`match` that the code
+// deliberately keeps apart
+#![allow(clippy::collapsible_match)]
+
 use std::path::PathBuf;
-use std::process::Command as ProcessCommand;
 use std::time::Duration;
 
-use clap::{CommandFactory, Parser};
+use clap::{CommandFactory, Parser, Subcommand};
 
 mod app;
 mod command_builder;
+mod command_parser;
+mod config;
 mod components;
+mod env;
+mod favorites;
+mod history;
+mod jobs;
+mod paths;
+mod pty_input;
+mod quoting;
+mod shell_init;
+mod spec_cache;
+mod spec_source;
 mod theme;
+mod transcript;
 mod ui;
+mod validation;
+mod watch;
 
 use app::App;
 
 /// TUI application for interactively building CLI commands from usage specs
 #[derive(Parser, Debug)]
-#[command(author, version, about, long_about = None)]
+#[command(author, version, about, long_about = None, disable_help_subcommand = true)]
 struct Args {
     /// Base command to build (e.g., "mise run")
     #[arg(long)]
     cmd: Option<String>,
 
-    /// Path to a usage spec file
+    /// Path to a usage spec file, a script with a `#USAGE` header, or `-` for stdin
     #[arg(long)]
     spec_file: Option<PathBuf>,
 
@@ -28,15 +46,77 @@ struct Args {
     #[arg(long)]
     usage: bool,
 
-    /// Command to run to get the usage spec (e.g., "mycli --usage")
+    /// Print the command to stdout instead of running it (for `eval "$(...)"`)
+    #[arg(long)]
+    print: bool,
+
+    /// Shell syntax for quoting the displayed and printed command
+    #[arg(long, value_name = "SHELL")]
+    shell: Option<quoting::Dialect>,
+
+    /// Start with the named favorite loaded
+    #[arg(long, value_name = "NAME")]
+    preset: Option<String>,
+
+    /// Start with this command line loaded (e.g. "mycli deploy prod"); also
+    /// given as words after `--`
+    #[arg(long, value_name = "COMMAND")]
+    initial: Option<String>,
+
+    /// Save a transcript of every executed command to this directory
+    #[arg(long, value_name = "DIR")]
+    transcript_dir: Option<PathBuf>,
+
+    /// Stop executed commands that run longer than this (e.g. "30s", "5m")
+    #[arg(long, value_name = "DURATION")]
+    timeout: Option<config::HumanDuration>,
+
+    /// Run the spec command without using or saving its cached output
+    #[arg(long, conflicts_with = "refresh")]
+    no_cache: bool,
+
+    /// Run the spec command instead of using its cached output, and cache the new output
+    #[arg(long)]
+    refresh: bool,
+
+    /// Command to run to get the usage spec (e.g., "mycli --usage"), or `-` to read it from stdin
     #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
     spec_cmd: Vec<String>,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Print a shell key binding (Ctrl-G) that edits the command line in TuiSage
+    ShellInit {
+        #[arg(value_enum)]
+        shell: shell_init::Shell,
+    },
+}
+
+/// Split the words of the command to start with from the trailing
+/// arguments: all of them with `--spec-file` (clap drops a leading `--`),
+/// or those after the first `--` that follows a spec command.
+fn split_initial_parts(spec_cmd: &mut Vec<String>, has_spec_file: bool) -> Vec<String> {
+    if has_spec_file {
+        return std::mem::take(spec_cmd);
+    }
+    match spec_cmd.iter().position(|word| word == "--") {
+        Some(i) => {
+            let initial = spec_cmd.split_off(i + 1);
+            spec_cmd.pop();
+            initial
+        }
+        None => Vec::new(),
+    }
 }
 
 fn main() -> color_eyre::Result<()> {
     color_eyre::install()?;
 
-    let args = Args::parse();
+    let mut args = Args::parse();
 
     // Handle --usage flag to output usage spec
     if args.usage {
@@ -50,6 +130,40 @@ fn main() -> color_eyre::Result<()> {
         return Ok(());
     }
 
+    if let Some(Command::ShellInit { shell }) = args.command {
+        print!("{}", shell_init::script(shell));
+        return Ok(());
+    }
+
+    // A lone `-` reads the spec from stdin, like `--spec-file -`
+    if args.spec_cmd == [spec_source::STDIN] && args.spec_file.is_none() {
+        args.spec_cmd.clear();
+        args.spec_file = Some(PathBuf::from(spec_source::STDIN));
+    }
+
+    let initial_parts = split_initial_parts(&mut args.spec_cmd, args.spec_file.is_some());
+    if !initial_parts.is_empty() && args.initial.is_some() {
+        return Err(color_eyre::eyre::eyre!(
+            "Cannot specify both --initial and a command after `--`. Use --help for usage information."
+        ));
+    }
+
+    // Without a spec source, the initial command's program provides it
+    if args.spec_cmd.is_empty() && args.spec_file.is_none() {
+        let words = match args.initial {
+            Some(ref line) => quoting::split_words(line)
+                .map_err(|e| color_eyre::eyre::eyre!("Can't parse the command: {}", e))?,
+            None => initial_parts.clone(),
+        };
+        if let Some(program) = command_parser::program(&words) {
+            if program.contains('/') || program.contains(std::path::MAIN_SEPARATOR) {
+                args.spec_file = Some(PathBuf::from(program));
+            } else {
+                args.spec_cmd = vec![program.to_string(), "--usage".to_string()];
+            }
+        }
+    }
+
     // Determine the usage spec source
     let has_spec_cmd = !args.spec_cmd.is_empty();
     let has_spec_file = args.spec_file.is_some();
@@ -62,91 +176,155 @@ fn main() -> color_eyre::Result<()> {
 
     if !has_spec_cmd && !has_spec_file {
         return Err(color_eyre::eyre::eyre!(
-            "Must specify either a spec command or --spec-file. Use --help for usage information."
+            "Must specify either a spec command, --spec-file or --initial. Use --help for usage information."
         ));
     }
 
-    let mut spec = if has_spec_cmd {
-        // Join the arguments into a single command string and run it
-        let spec_cmd = args.spec_cmd.join(" ");
-        let output = run_spec_command(&spec_cmd)?;
-        output.parse::<usage::Spec>().map_err(|e| {
-            color_eyre::eyre::eyre!(
-                "Failed to parse usage spec from command '{}': {}",
-                spec_cmd,
-                e
-            )
-        })?
-    } else if let Some(ref spec_file) = args.spec_file {
-        usage::Spec::parse_file(spec_file).map_err(|e| {
-            color_eyre::eyre::eyre!(
-                "Failed to parse usage spec '{}': {}",
-                spec_file.display(),
-                e
-            )
-        })?
-    } else {
-        unreachable!()
+    let config = match config::Config::default_path() {
+        Some(path) => config::Config::load(path)?,
+        None => config::Config::default(),
+    };
+
+    let source = match args.spec_file {
+        Some(path) => spec_source::SpecSource::File(path),
+        // Join the arguments into a single command string
+        None => spec_source::SpecSource::Command(args.spec_cmd.join(" ")),
+    };
+
+    // A spec command's cached output is shown right away and checked in the
+    // background once the UI is up
+    let cache = match source {
+        spec_source::SpecSource::Command(ref cmd) if !args.no_cache => {
+            spec_cache::SpecCache::open(cmd, &config.cache.watch)
+        }
+        _ => None,
+    };
+    let cached = cache
+        .as_ref()
+        .filter(|_| !args.refresh)
+        .and_then(spec_cache::SpecCache::load);
+    let (mut spec, cached_output) = match (cached, &cache) {
+        (Some((output, spec)), _) => (spec, Some(output)),
+        (None, Some(cache)) => (cache.fetch()?.1, None),
+        (None, None) => (source.read()?, None),
     };
 
-    // Override the bin name if --cmd is provided
-    if let Some(ref cmd) = args.cmd {
+    // Override the bin name if --cmd is provided, or configured for this spec
+    let cmd = args.cmd.clone().or_else(|| {
+        config
+            .spec(&source.name(), &spec.bin)
+            .and_then(|s| s.cmd.clone())
+    });
+    if let Some(ref cmd) = cmd {
         spec.bin = cmd.clone();
     }
+    if spec.bin.is_empty() && spec.name.is_empty() {
+        return Err(color_eyre::eyre::eyre!(
+            "The usage spec has no `bin` to run. Use --cmd to set the command."
+        ));
+    }
 
-    // Enable mouse capture before initializing the terminal
-    crossterm::execute!(std::io::stderr(), crossterm::event::EnableMouseCapture)?;
+    let mut app = App::with_theme(spec, config.theme_name().unwrap_or_default());
+    app.config = config;
+    app.set_spec_source(source, cmd);
+    if let Some(cache) = cache {
+        app.set_spec_cache(cache, cached_output);
+    }
+    if args.print {
+        app.config.behavior.preview_enter = config::PreviewEnter::Print;
+    }
+    if let Some(shell) = args.shell {
+        app.config.behavior.shell = shell;
+    }
+    if let Some(dir) = args.transcript_dir {
+        app.config.behavior.transcript_dir = Some(dir);
+    }
+    if let Some(timeout) = args.timeout {
+        app.config.behavior.timeout = Some(timeout);
+    }
+    if let Some(path) = history::History::default_path() {
+        app.history = history::History::load(path);
+    }
+    if let Some(path) = favorites::Favorites::default_path(&app.spec_id()) {
+        app.favorites = favorites::Favorites::load(path);
+    }
+    if let Some(ref name) = args.preset {
+        app.apply_preset(name)?;
+    }
+    if let Some(ref line) = args.initial {
+        app.load_command_line(line)?;
+    } else if !initial_parts.is_empty() {
+        app.load_command_parts(&initial_parts);
+    }
 
-    let mut terminal = ratatui::init();
-    let mut app = App::new(spec);
+    let mut terminal = init_terminal()?;
     let result = run_event_loop(&mut terminal, &mut app);
+    restore_terminal()?;
+    app.finish_jobs();
 
-    // Restore terminal and disable mouse capture
-    ratatui::restore();
-    crossterm::execute!(std::io::stderr(), crossterm::event::DisableMouseCapture)?;
-
-    result
+    // Printed only after the terminal is restored, so it lands on the
+    // normal screen (or in the capturing shell).
+    if let Some(command) = result? {
+        println!("{command}");
+    }
+    Ok(())
 }
 
-/// Run a shell command and return its stdout as a string.
-fn run_spec_command(cmd: &str) -> color_eyre::Result<String> {
-    let output = if cfg!(target_os = "windows") {
-        ProcessCommand::new("cmd")
-            .args(["/C", cmd])
-            .output()
-            .map_err(|e| color_eyre::eyre::eyre!("Failed to run spec command '{}': {}", cmd, e))?
-    } else {
-        ProcessCommand::new("sh")
-            .args(["-c", cmd])
-            .output()
-            .map_err(|e| color_eyre::eyre::eyre!("Failed to run spec command '{}': {}", cmd, e))?
-    };
+/// Terminal drawing to `ui_output()`, leaving stdout free for the printed command.
+type Terminal = ratatui::Terminal<ratatui::backend::CrosstermBackend<Box<dyn std::io::Write>>>;
 
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        return Err(color_eyre::eyre::eyre!(
-            "Spec command '{}' failed with status {}{}",
-            cmd,
-            output.status,
-            if stderr.is_empty() {
-                String::new()
-            } else {
-                format!(": {}", stderr.trim())
-            }
-        ));
+/// Where the UI is drawn: stderr, or the terminal itself when stderr is
+/// redirected (e.g. by a shell widget capturing the printed command).
+fn ui_output() -> Box<dyn std::io::Write> {
+    use std::io::IsTerminal;
+
+    if !std::io::stderr().is_terminal() {
+        #[cfg(unix)]
+        if let Ok(tty) = std::fs::OpenOptions::new().write(true).open("/dev/tty") {
+            return Box::new(tty);
+        }
     }
+    Box::new(std::io::stderr())
+}
 
-    String::from_utf8(output.stdout).map_err(|e| {
-        color_eyre::eyre::eyre!(
-            "Spec command '{}' produced invalid UTF-8 output: {}",
-            cmd,
-            e
-        )
-    })
+/// Enter raw mode and the alternate screen on stderr, with mouse capture.
+///
+/// Like `ratatui::init()`, but stdout may be captured by `$(...)`, so the UI
+/// is drawn on stderr instead.
+fn init_terminal() -> color_eyre::Result<Terminal> {
+    let hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        let _ = restore_terminal();
+        hook(info);
+    }));
+
+    crossterm::terminal::enable_raw_mode()?;
+    let mut output = ui_output();
+    crossterm::execute!(
+        output,
+        crossterm::terminal::EnterAlternateScreen,
+        crossterm::event::EnableMouseCapture,
+        crossterm::event::EnableBracketedPaste
+    )?;
+    let backend = ratatui::backend::CrosstermBackend::new(output);
+    Ok(ratatui::Terminal::new(backend)?)
+}
+
+/// Undo `init_terminal()`.
+fn restore_terminal() -> color_eyre::Result<()> {
+    crossterm::terminal::disable_raw_mode()?;
+    crossterm::execute!(
+        ui_output(),
+        crossterm::event::DisableMouseCapture,
+        crossterm::event::DisableBracketedPaste,
+        crossterm::terminal::LeaveAlternateScreen,
+        crossterm::cursor::Show
+    )?;
+    Ok(())
 }
 
 fn current_terminal_size(
-    terminal: &mut ratatui::DefaultTerminal,
+    terminal: &mut Terminal,
 ) -> color_eyre::Result<ratatui::layout::Size> {
     let size = terminal.size()?;
     Ok(ratatui::layout::Size {
@@ -156,23 +334,41 @@ fn current_terminal_size(
 }
 
 fn execute_current_command(
-    terminal: &mut ratatui::DefaultTerminal,
+    terminal: &mut Terminal,
     app: &mut App,
 ) -> color_eyre::Result<()> {
     let terminal_size = current_terminal_size(terminal)?;
+    // Shown in the status bar: stderr is where the UI is drawn
     if let Err(e) = app.spawn_execution(terminal_size) {
-        eprintln!("Failed to execute command: {}", e);
+        app.status_message = Some(format!("Failed to execute command: {e}"));
     }
     Ok(())
 }
 
-fn run_event_loop(
-    terminal: &mut ratatui::DefaultTerminal,
+fn rerun_current_command(
+    terminal: &mut Terminal,
     app: &mut App,
 ) -> color_eyre::Result<()> {
+    let terminal_size = current_terminal_size(terminal)?;
+    if let Err(e) = app.rerun(terminal_size) {
+        // The replaced job is gone, so go back to the builder to show why
+        app.close_execution();
+        app.status_message = Some(format!("Failed to execute command: {e}"));
+    }
+    Ok(())
+}
+
+/// Run the UI until the user quits. Returns the command to print, if the
+/// user chose to print it rather than run it.
+fn run_event_loop(
+    terminal: &mut Terminal,
+    app: &mut App,
+) -> color_eyre::Result<Option<String>> {
     use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
 
     loop {
+        app.poll_watches();
+        app.poll_spec_reload();
         terminal.draw(|frame| ui::render(frame, app))?;
 
         // Use polling when in execution mode so we can refresh the terminal output
@@ -186,8 +382,17 @@ fn run_event_loop(
 
                         // Ctrl-C during execution: forward to PTY (handled in app)
                         // But if the process has exited, just close
-                        app.handle_key(key);
+                        if app.handle_key(key) == app::Action::Rerun {
+                            rerun_current_command(terminal, app)?;
+                        }
+                    }
+                    Event::Mouse(mouse) => {
+                        let action = app.handle_mouse(mouse);
+                        if action == app::Action::Rerun {
+                            rerun_current_command(terminal, app)?;
+                        }
                     }
+                    Event::Paste(text) => app.handle_paste(&text),
                     Event::Resize(width, height) => {
                         app.resize_execution_to_terminal(ratatui::layout::Size {
                             width,
@@ -201,29 +406,65 @@ fn run_event_loop(
             continue;
         }
 
-        // Normal builder mode: blocking event read
+        // Normal builder mode: blocking event read, or polling while the
+        // output pane is shown, background jobs run or watch files, or the
+        // spec file is watched or refreshed, so they stay current
+        app.poll_jobs();
+        let refresh = if app.has_output_pane() {
+            Some(Duration::from_millis(16))
+        } else if app.running_jobs() > 0
+            || app.watching_jobs() > 0
+            || app.is_watching_spec()
+            || app.is_refreshing_spec()
+        {
+            Some(Duration::from_millis(500))
+        } else {
+            None
+        };
+        if let Some(refresh) = refresh {
+            if !event::poll(refresh)? {
+                continue;
+            }
+        }
         match event::read()? {
             Event::Key(key) => {
                 if key.kind != KeyEventKind::Press {
                     continue;
                 }
 
-                // Global quit shortcuts
-                if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c') {
-                    return Ok(());
+                // Global quit shortcuts (Ctrl-C in the output pane goes to the command)
+                if key.modifiers.contains(KeyModifiers::CONTROL)
+                    && key.code == KeyCode::Char('c')
+                    && app.focus() != app::Focus::Output
+                {
+                    if app.quit() == app::Action::Quit {
+                        return Ok(None);
+                    }
+                    continue;
                 }
 
                 match app.handle_key(key) {
                     app::Action::None => {}
-                    app::Action::Quit => return Ok(()),
+                    app::Action::Quit => return Ok(None),
                     app::Action::Execute => execute_current_command(terminal, app)?,
+                    app::Action::Rerun => rerun_current_command(terminal, app)?,
+                    app::Action::Print => match app.build_shell_command() {
+                        Ok(command) => return Ok(Some(command)),
+                        Err(e) => app.status_message = Some(format!("Can't print the command: {e}")),
+                    },
                 }
             }
             Event::Mouse(mouse) => match app.handle_mouse(mouse) {
                 app::Action::None => {}
-                app::Action::Quit => return Ok(()),
+                app::Action::Quit => return Ok(None),
                 app::Action::Execute => execute_current_command(terminal, app)?,
+                app::Action::Rerun => rerun_current_command(terminal, app)?,
+                app::Action::Print => match app.build_shell_command() {
+                    Ok(command) => return Ok(Some(command)),
+                    Err(e) => app.status_message = Some(format!("Can't print the command: {e}")),
+                },
             },
+            Event::Paste(text) => app.handle_paste(&text),
             Event::Resize(_, _) => {
                 // Terminal will be redrawn on next loop iteration
             }
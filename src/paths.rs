@@ -0,0 +1,126 @@
+//! Locations of TuiSage's on-disk state.
+//!
+//! Follows the XDG base directory layout on every platform so that files end
+//! up in predictable places (e.g. `~/.local/share/tuisage`).
+
+use std::path::{Path, PathBuf};
+
+/// Directory for persistent data such as command history.
+///
+/// Uses `$XDG_DATA_HOME/tuisage`, falling back to `~/.local/share/tuisage`.
+pub fn data_dir() -> Option<PathBuf> {
+    xdg_dir("XDG_DATA_HOME", ".local/share")
+}
+
+/// Directory for user configuration.
+///
+/// Uses `$XDG_CONFIG_HOME/tuisage`, falling back to `~/.config/tuisage`.
+pub fn config_dir() -> Option<PathBuf> {
+    xdg_dir("XDG_CONFIG_HOME", ".config")
+}
+
+/// Directory for cached data that can be recreated, such as the output of
+/// spec commands.
+///
+/// Uses `$XDG_CACHE_HOME/tuisage`, falling back to `~/.cache/tuisage`.
+pub fn cache_dir() -> Option<PathBuf> {
+    xdg_dir("XDG_CACHE_HOME", ".cache")
+}
+
+/// Resolve `$<var>/tuisage`, or `~/<fallback>/tuisage` when the variable is
+/// unset or not an absolute path (as required by the XDG spec).
+fn xdg_dir(var: &str, fallback: &str) -> Option<PathBuf> {
+    let base = std::env::var_os(var)
+        .map(PathBuf::from)
+        .filter(|p| p.is_absolute())
+        .or_else(|| home_dir().map(|home| home.join(fallback)))?;
+    Some(base.join("tuisage"))
+}
+
+/// Short form of a directory for display: relative to TuiSage's working
+/// directory when inside it, `~/…` under the home directory, or absolute.
+pub fn display_dir(dir: &Path) -> String {
+    let base = std::env::current_dir().ok();
+    shorten_dir(dir, base.as_deref(), home_dir().as_deref())
+}
+
+fn shorten_dir(dir: &Path, base: Option<&Path>, home: Option<&Path>) -> String {
+    if let Some(rel) = base.and_then(|base| dir.strip_prefix(base).ok()) {
+        if rel.as_os_str().is_empty() {
+            return ".".to_string();
+        }
+        return rel.display().to_string();
+    }
+    if let Some(rel) = home.and_then(|home| dir.strip_prefix(home).ok()) {
+        return Path::new("~").join(rel).display().to_string();
+    }
+    dir.display().to_string()
+}
+
+/// Expand a leading `~` in a typed path to the home directory.
+pub fn expand_home(text: &str) -> PathBuf {
+    let rest = match text.strip_prefix('~') {
+        Some(rest) if rest.is_empty() || rest.starts_with('/') => rest.trim_start_matches('/'),
+        _ => return PathBuf::from(text),
+    };
+    match home_dir() {
+        Some(home) => home.join(rest),
+        None => PathBuf::from(text),
+    }
+}
+
+/// The absolute path an argument value names, when it is explicitly
+/// relative: `.`, `..`, or starting with `./` or `../`. Other words are left
+/// alone, even when a file of that name exists in `dir`.
+pub fn resolve_relative(value: &str, dir: &Path) -> Option<PathBuf> {
+    let explicit = [".", ".."].contains(&value)
+        || ["./", "../"].iter().any(|prefix| value.starts_with(prefix));
+    if !explicit {
+        return None;
+    }
+    Some(match value.strip_prefix("./").unwrap_or(value) {
+        "." | "" => dir.to_path_buf(),
+        rest => dir.join(rest),
+    })
+}
+
+pub fn home_dir() -> Option<PathBuf> {
+    std::env::var_os("HOME")
+        .or_else(|| std::env::var_os("USERPROFILE"))
+        .filter(|h| !h.is_empty())
+        .map(PathBuf::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shorten_dir() {
+        let base = Path::new("/repo");
+        let home = Path::new("/home/me");
+        let short = |dir: &str| shorten_dir(Path::new(dir), Some(base), Some(home));
+        assert_eq!(short("/repo"), ".");
+        assert_eq!(short("/repo/packages/api"), "packages/api");
+        assert_eq!(short("/home/me/src"), "~/src");
+        assert_eq!(short("/tmp"), "/tmp");
+    }
+
+    #[test]
+    fn test_resolve_relative() {
+        let dir = std::env::temp_dir();
+        let resolve = |value: &str| resolve_relative(value, &dir);
+        assert_eq!(resolve("./src"), Some(dir.join("src")));
+        assert_eq!(resolve("../lib"), Some(dir.join("../lib")));
+        assert_eq!(resolve("."), Some(dir.clone()));
+        assert_eq!(resolve("--verbose"), None);
+        assert_eq!(resolve("/etc"), None);
+        assert_eq!(resolve("no-such-file-here"), None);
+
+        // Words that name an existing file are still words
+        let name = format!("tuisage-test-resolve-{}", std::process::id());
+        std::fs::write(dir.join(&name), "").unwrap();
+        assert_eq!(resolve(&name), None);
+        let _ = std::fs::remove_file(dir.join(&name));
+    }
+}
@@ -0,0 +1,390 @@
+//! Encoding keys, pastes and mouse events as terminal input.
+//!
+//! The execution view forwards input to the command the way xterm would
+//! send it, following the modes the command has set on its terminal (as
+//! tracked by the vt100 parser): application cursor and keypad keys,
+//! bracketed paste, and mouse reporting.
+
+use crossterm::event::{
+    KeyCode, KeyEvent, KeyEventState, KeyModifiers, MouseButton, MouseEvent, MouseEventKind,
+};
+use vt100::{MouseProtocolEncoding, MouseProtocolMode};
+
+const ESC: u8 = 0x1b;
+
+/// Terminal modes that change how input is encoded.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct InputModes {
+    /// Cursor keys send `ESC O A` rather than `ESC [ A` (DECCKM).
+    pub application_cursor: bool,
+    /// Keypad keys send `ESC O` sequences rather than their characters (DECKPAM).
+    pub application_keypad: bool,
+    /// Pastes are wrapped in `ESC [200~` … `ESC [201~`.
+    pub bracketed_paste: bool,
+    /// Which mouse events the command wants reported.
+    pub mouse_mode: MouseProtocolMode,
+    pub mouse_encoding: MouseProtocolEncoding,
+}
+
+impl InputModes {
+    /// The modes currently set on a terminal screen.
+    pub fn from_screen(screen: &vt100::Screen) -> Self {
+        Self {
+            application_cursor: screen.application_cursor(),
+            application_keypad: screen.application_keypad(),
+            bracketed_paste: screen.bracketed_paste(),
+            mouse_mode: screen.mouse_protocol_mode(),
+            mouse_encoding: screen.mouse_protocol_encoding(),
+        }
+    }
+}
+
+/// The xterm modifier parameter: 1 plus 1 for Shift, 2 for Alt and 4 for
+/// Ctrl. `None` without modifiers.
+fn modifier_param(modifiers: KeyModifiers) -> Option<u8> {
+    let mut param = 0;
+    if modifiers.contains(KeyModifiers::SHIFT) {
+        param += 1;
+    }
+    if modifiers.contains(KeyModifiers::ALT) {
+        param += 2;
+    }
+    if modifiers.contains(KeyModifiers::CONTROL) {
+        param += 4;
+    }
+    (param > 0).then_some(param + 1)
+}
+
+/// The control character for Ctrl plus `c`, if there is one.
+fn control_char(c: char) -> Option<u8> {
+    match c {
+        'a'..='z' | 'A'..='Z' => Some(c.to_ascii_lowercase() as u8 & 0x1f),
+        '@' | ' ' | '2' => Some(0x00),
+        '[' | '3' => Some(0x1b),
+        '\\' | '4' => Some(0x1c),
+        ']' | '5' => Some(0x1d),
+        '^' | '6' => Some(0x1e),
+        '_' | '-' | '7' => Some(0x1f),
+        '?' | '8' => Some(0x7f),
+        _ => None,
+    }
+}
+
+/// `ESC [ X` for a cursor key, `ESC O X` in application cursor mode, or
+/// `ESC [ 1 ; m X` with modifiers.
+fn cursor_key(final_byte: char, modifiers: KeyModifiers, modes: &InputModes) -> Vec<u8> {
+    match modifier_param(modifiers) {
+        Some(m) => format!("\x1b[1;{m}{final_byte}").into_bytes(),
+        None if modes.application_cursor => format!("\x1bO{final_byte}").into_bytes(),
+        None => format!("\x1b[{final_byte}").into_bytes(),
+    }
+}
+
+/// `ESC [ n ~`, or `ESC [ n ; m ~` with modifiers.
+fn tilde_key(number: u8, modifiers: KeyModifiers) -> Vec<u8> {
+    match modifier_param(modifiers) {
+        Some(m) => format!("\x1b[{number};{m}~").into_bytes(),
+        None => format!("\x1b[{number}~").into_bytes(),
+    }
+}
+
+/// F1–F4 are `ESC O P`–`ESC O S`, or `ESC [ 1 ; m P` with modifiers.
+fn function_key(n: u8, modifiers: KeyModifiers) -> Option<Vec<u8>> {
+    let number = match n {
+        1..=4 => {
+            let final_byte = (b'P' + n - 1) as char;
+            return Some(match modifier_param(modifiers) {
+                Some(m) => format!("\x1b[1;{m}{final_byte}").into_bytes(),
+                None => format!("\x1bO{final_byte}").into_bytes(),
+            });
+        }
+        5 => 15,
+        6..=10 => n + 11,
+        11..=12 => n + 12,
+        _ => return None,
+    };
+    Some(tilde_key(number, modifiers))
+}
+
+/// The application keypad sequence for a key on the numeric keypad.
+fn keypad_key(code: KeyCode) -> Option<Vec<u8>> {
+    let final_byte = match code {
+        KeyCode::Char(c @ '0'..='9') => (b'p' + (c as u8 - b'0')) as char,
+        KeyCode::Char('.') => 'n',
+        KeyCode::Char('+') => 'k',
+        KeyCode::Char('-') => 'm',
+        KeyCode::Char('*') => 'j',
+        KeyCode::Char('/') => 'o',
+        KeyCode::Char('=') => 'X',
+        KeyCode::Enter => 'M',
+        _ => return None,
+    };
+    Some(format!("\x1bO{final_byte}").into_bytes())
+}
+
+/// Prefix `bytes` with ESC when Alt is held.
+fn with_alt(modifiers: KeyModifiers, mut bytes: Vec<u8>) -> Vec<u8> {
+    if modifiers.contains(KeyModifiers::ALT) {
+        bytes.insert(0, ESC);
+    }
+    bytes
+}
+
+/// Encode a key press as the bytes xterm would send. Returns `None` for keys
+/// that have no encoding (e.g. media keys).
+pub fn encode_key(key: KeyEvent, modes: &InputModes) -> Option<Vec<u8>> {
+    let modifiers = key.modifiers;
+    // Keypad keys are only distinguished with the kitty keyboard protocol
+    if modes.application_keypad && key.state.contains(KeyEventState::KEYPAD) {
+        if let Some(bytes) = keypad_key(key.code) {
+            return Some(bytes);
+        }
+    }
+    let bytes = match key.code {
+        KeyCode::Char(c) => {
+            let bytes = match control_char(c) {
+                Some(byte) if modifiers.contains(KeyModifiers::CONTROL) => vec![byte],
+                _ => c.to_string().into_bytes(),
+            };
+            with_alt(modifiers, bytes)
+        }
+        KeyCode::Enter => with_alt(modifiers, b"\r".to_vec()),
+        KeyCode::Tab if modifiers.contains(KeyModifiers::SHIFT) => b"\x1b[Z".to_vec(),
+        KeyCode::Tab => with_alt(modifiers, b"\t".to_vec()),
+        KeyCode::BackTab => b"\x1b[Z".to_vec(),
+        KeyCode::Backspace if modifiers.contains(KeyModifiers::CONTROL) => {
+            with_alt(modifiers, vec![0x08])
+        }
+        KeyCode::Backspace => with_alt(modifiers, vec![0x7f]),
+        KeyCode::Esc => with_alt(modifiers, vec![ESC]),
+        KeyCode::Up => cursor_key('A', modifiers, modes),
+        KeyCode::Down => cursor_key('B', modifiers, modes),
+        KeyCode::Right => cursor_key('C', modifiers, modes),
+        KeyCode::Left => cursor_key('D', modifiers, modes),
+        KeyCode::Home => cursor_key('H', modifiers, modes),
+        KeyCode::End => cursor_key('F', modifiers, modes),
+        KeyCode::Insert => tilde_key(2, modifiers),
+        KeyCode::Delete => tilde_key(3, modifiers),
+        KeyCode::PageUp => tilde_key(5, modifiers),
+        KeyCode::PageDown => tilde_key(6, modifiers),
+        KeyCode::F(n) => function_key(n, modifiers)?,
+        _ => return None,
+    };
+    Some(bytes)
+}
+
+/// Encode pasted text. Line breaks are sent as carriage returns, like a
+/// typed Enter, and the text is bracketed when the command asked for it.
+pub fn encode_paste(text: &str, modes: &InputModes) -> Vec<u8> {
+    let text = text.replace("\r\n", "\r").replace('\n', "\r");
+    if !modes.bracketed_paste {
+        return text.into_bytes();
+    }
+    // A pasted end marker would end the paste early
+    let text = text.replace("\x1b[201~", "");
+    format!("\x1b[200~{text}\x1b[201~").into_bytes()
+}
+
+/// Encode a mouse event at `(column, row)`, zero-based within the terminal,
+/// if the command's mouse mode reports it.
+pub fn encode_mouse(
+    event: MouseEvent,
+    column: u16,
+    row: u16,
+    modes: &InputModes,
+) -> Option<Vec<u8>> {
+    use MouseProtocolMode as Mode;
+
+    if modes.mouse_mode == Mode::None {
+        return None;
+    }
+    let button_code = |button: MouseButton| match button {
+        MouseButton::Left => 0,
+        MouseButton::Middle => 1,
+        MouseButton::Right => 2,
+    };
+    let (mut code, release) = match event.kind {
+        MouseEventKind::Down(button) => (button_code(button), false),
+        MouseEventKind::Up(button) if modes.mouse_mode != Mode::Press => {
+            (button_code(button), true)
+        }
+        MouseEventKind::Drag(button)
+            if matches!(modes.mouse_mode, Mode::ButtonMotion | Mode::AnyMotion) =>
+        {
+            (button_code(button) + 32, false)
+        }
+        MouseEventKind::Moved if modes.mouse_mode == Mode::AnyMotion => (3 + 32, false),
+        MouseEventKind::ScrollUp => (64, false),
+        MouseEventKind::ScrollDown => (65, false),
+        MouseEventKind::ScrollLeft => (66, false),
+        MouseEventKind::ScrollRight => (67, false),
+        _ => return None,
+    };
+    if event.modifiers.contains(KeyModifiers::SHIFT) {
+        code += 4;
+    }
+    if event.modifiers.contains(KeyModifiers::ALT) {
+        code += 8;
+    }
+    if event.modifiers.contains(KeyModifiers::CONTROL) {
+        code += 16;
+    }
+    let (x, y) = (u32::from(column) + 1, u32::from(row) + 1);
+
+    if modes.mouse_encoding == MouseProtocolEncoding::Sgr {
+        let end = if release { 'm' } else { 'M' };
+        return Some(format!("\x1b[<{code};{x};{y}{end}").into_bytes());
+    }
+    // The older encodings can't say which button was released
+    if release {
+        code = 3 | (code & !3);
+    }
+    let mut bytes = b"\x1b[M".to_vec();
+    for value in [code + 32, x + 32, y + 32] {
+        match modes.mouse_encoding {
+            MouseProtocolEncoding::Utf8 => {
+                bytes.extend(char::from_u32(value)?.to_string().into_bytes())
+            }
+            _ => bytes.push(u8::try_from(value).ok()?),
+        }
+    }
+    Some(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(code: KeyCode, modifiers: KeyModifiers) -> Vec<u8> {
+        encode_key(KeyEvent::new(code, modifiers), &InputModes::default()).unwrap()
+    }
+
+    #[test]
+    fn test_encode_characters_and_control_keys() {
+        assert_eq!(key(KeyCode::Char('é'), KeyModifiers::NONE), "é".as_bytes());
+        assert_eq!(key(KeyCode::Char('A'), KeyModifiers::SHIFT), b"A");
+        assert_eq!(key(KeyCode::Char('c'), KeyModifiers::CONTROL), b"\x03");
+        assert_eq!(key(KeyCode::Char('W'), KeyModifiers::CONTROL), b"\x17");
+        assert_eq!(key(KeyCode::Char(' '), KeyModifiers::CONTROL), b"\x00");
+        assert_eq!(key(KeyCode::Char(']'), KeyModifiers::CONTROL), b"\x1d");
+        assert_eq!(key(KeyCode::Char('b'), KeyModifiers::ALT), b"\x1bb");
+        assert_eq!(
+            key(
+                KeyCode::Char('x'),
+                KeyModifiers::CONTROL | KeyModifiers::ALT
+            ),
+            b"\x1b\x18"
+        );
+        assert_eq!(key(KeyCode::Enter, KeyModifiers::NONE), b"\r");
+        assert_eq!(key(KeyCode::Backspace, KeyModifiers::NONE), b"\x7f");
+        assert_eq!(key(KeyCode::Backspace, KeyModifiers::ALT), b"\x1b\x7f");
+        assert_eq!(key(KeyCode::BackTab, KeyModifiers::SHIFT), b"\x1b[Z");
+    }
+
+    #[test]
+    fn test_encode_special_keys() {
+        assert_eq!(key(KeyCode::Up, KeyModifiers::NONE), b"\x1b[A");
+        assert_eq!(key(KeyCode::Left, KeyModifiers::CONTROL), b"\x1b[1;5D");
+        assert_eq!(
+            key(KeyCode::End, KeyModifiers::SHIFT | KeyModifiers::ALT),
+            b"\x1b[1;4F"
+        );
+        assert_eq!(key(KeyCode::PageUp, KeyModifiers::NONE), b"\x1b[5~");
+        assert_eq!(key(KeyCode::Delete, KeyModifiers::CONTROL), b"\x1b[3;5~");
+        assert_eq!(key(KeyCode::Insert, KeyModifiers::NONE), b"\x1b[2~");
+        assert_eq!(key(KeyCode::F(1), KeyModifiers::NONE), b"\x1bOP");
+        assert_eq!(key(KeyCode::F(4), KeyModifiers::SHIFT), b"\x1b[1;2S");
+        assert_eq!(key(KeyCode::F(5), KeyModifiers::NONE), b"\x1b[15~");
+        assert_eq!(key(KeyCode::F(10), KeyModifiers::NONE), b"\x1b[21~");
+        assert_eq!(key(KeyCode::F(12), KeyModifiers::CONTROL), b"\x1b[24;5~");
+        assert_eq!(
+            encode_key(
+                KeyEvent::new(KeyCode::F(20), KeyModifiers::NONE),
+                &InputModes::default()
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn test_encode_application_modes() {
+        let modes = InputModes {
+            application_cursor: true,
+            application_keypad: true,
+            ..InputModes::default()
+        };
+        let encode = |code, modifiers| encode_key(KeyEvent::new(code, modifiers), &modes).unwrap();
+        assert_eq!(encode(KeyCode::Up, KeyModifiers::NONE), b"\x1bOA");
+        assert_eq!(encode(KeyCode::Home, KeyModifiers::NONE), b"\x1bOH");
+        assert_eq!(encode(KeyCode::Up, KeyModifiers::CONTROL), b"\x1b[1;5A");
+
+        let mut keypad = KeyEvent::new(KeyCode::Char('5'), KeyModifiers::NONE);
+        keypad.state = KeyEventState::KEYPAD;
+        assert_eq!(encode_key(keypad, &modes).unwrap(), b"\x1bOu");
+        assert_eq!(
+            encode_key(keypad, &InputModes::default()).unwrap(),
+            b"5",
+            "numeric keypad mode sends the character"
+        );
+    }
+
+    #[test]
+    fn test_encode_paste() {
+        let modes = InputModes {
+            bracketed_paste: true,
+            ..InputModes::default()
+        };
+        assert_eq!(encode_paste("a\nb\r\n", &InputModes::default()), b"a\rb\r");
+        assert_eq!(
+            encode_paste("ls\x1b[201~\n", &modes),
+            b"\x1b[200~ls\r\x1b[201~"
+        );
+    }
+
+    #[test]
+    fn test_encode_mouse() {
+        let mouse = |kind, modifiers| MouseEvent {
+            kind,
+            column: 0,
+            row: 0,
+            modifiers,
+        };
+        let down = mouse(MouseEventKind::Down(MouseButton::Left), KeyModifiers::NONE);
+        let up = mouse(MouseEventKind::Up(MouseButton::Right), KeyModifiers::NONE);
+        let moved = mouse(MouseEventKind::Moved, KeyModifiers::NONE);
+
+        assert_eq!(encode_mouse(down, 4, 2, &InputModes::default()), None);
+
+        let sgr = InputModes {
+            mouse_mode: MouseProtocolMode::PressRelease,
+            mouse_encoding: MouseProtocolEncoding::Sgr,
+            ..InputModes::default()
+        };
+        assert_eq!(encode_mouse(down, 4, 2, &sgr).unwrap(), b"\x1b[<0;5;3M");
+        assert_eq!(encode_mouse(up, 4, 2, &sgr).unwrap(), b"\x1b[<2;5;3m");
+        assert_eq!(encode_mouse(moved, 4, 2, &sgr), None);
+        let wheel = mouse(MouseEventKind::ScrollDown, KeyModifiers::CONTROL);
+        assert_eq!(encode_mouse(wheel, 0, 0, &sgr).unwrap(), b"\x1b[<81;1;1M");
+
+        let x10 = InputModes {
+            mouse_mode: MouseProtocolMode::AnyMotion,
+            ..InputModes::default()
+        };
+        assert_eq!(encode_mouse(down, 4, 2, &x10).unwrap(), b"\x1b[M %#");
+        assert_eq!(encode_mouse(up, 4, 2, &x10).unwrap(), b"\x1b[M#%#");
+        assert_eq!(encode_mouse(moved, 0, 0, &x10).unwrap(), b"\x1b[MC!!");
+        assert_eq!(
+            encode_mouse(down, 300, 0, &x10),
+            None,
+            "columns past 223 can't be encoded"
+        );
+        let utf8 = InputModes {
+            mouse_encoding: MouseProtocolEncoding::Utf8,
+            ..x10
+        };
+        assert_eq!(
+            encode_mouse(down, 300, 0, &utf8).unwrap(),
+            "\x1b[M \u{14d}!".as_bytes()
+        );
+    }
+}
@@ -0,0 +1,481 @@
+//! Shell quoting for displaying and printing commands.
+//!
+//! The command preview and `--print` both turn the argv from
+//! `build_command_parts()` into a single line. Each dialect quotes words so
+//! that its shell splits the line back into exactly the same arguments.
+//! [`split_words`] goes the other way for free-form text typed by the user.
+
+use serde::Deserialize;
+
+/// Shell syntax used to quote command lines.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum Dialect {
+    /// POSIX `sh` and compatible shells (bash, zsh, dash, ...).
+    Posix,
+    /// The fish shell.
+    Fish,
+    /// PowerShell (Windows PowerShell and pwsh).
+    #[value(name = "powershell")]
+    PowerShell,
+    /// Windows `cmd.exe`, following the MSVC argv parsing rules.
+    Cmd,
+}
+
+impl Default for Dialect {
+    fn default() -> Self {
+        if cfg!(windows) {
+            Dialect::PowerShell
+        } else {
+            Dialect::Posix
+        }
+    }
+}
+
+impl Dialect {
+    /// Quote a single word, leaving it bare when no quoting is needed.
+    pub fn quote(self, word: &str) -> String {
+        // zsh expands a word starting with `=` to the path of a command
+        let leading_equals = self == Dialect::Posix && word.starts_with('=');
+        if !word.is_empty() && !leading_equals && word.chars().all(|c| self.is_safe(c)) {
+            return word.to_string();
+        }
+        match self {
+            Dialect::Posix => format!("'{}'", word.replace('\'', r"'\''")),
+            Dialect::Fish => format!("'{}'", word.replace('\\', r"\\").replace('\'', r"\'")),
+            Dialect::PowerShell => format!("'{}'", quote_powershell_single(word)),
+            Dialect::Cmd => escape_cmd(&quote_msvc(word)),
+        }
+    }
+
+    /// Quote each part for display as separate words of a command line.
+    ///
+    /// In PowerShell a quoted command name is a string expression, so the
+    /// call operator is prepended to it.
+    pub fn words(self, parts: &[String]) -> Vec<String> {
+        let mut words: Vec<String> = parts.iter().map(|part| self.quote(part)).collect();
+        if self == Dialect::PowerShell {
+            if let (Some(first), Some(part)) = (words.first_mut(), parts.first()) {
+                if *first != *part {
+                    *first = format!("& {first}");
+                }
+            }
+        }
+        words
+    }
+
+    /// Join parts into a command line that splits back into the same parts.
+    pub fn join(self, parts: &[String]) -> String {
+        self.words(parts).join(" ")
+    }
+
+    /// Prefix a command line with a change into `dir`, so it runs there
+    /// when pasted into a shell.
+    pub fn in_dir(self, dir: &str, command: &str) -> String {
+        let dir = self.quote(dir);
+        match self {
+            Dialect::Posix | Dialect::Fish => format!("cd {dir} && {command}"),
+            Dialect::PowerShell => format!("Set-Location {dir}; {command}"),
+            Dialect::Cmd => format!("cd /d {dir} && {command}"),
+        }
+    }
+
+    /// Characters that never need quoting in this dialect.
+    fn is_safe(self, c: char) -> bool {
+        if c.is_ascii_alphanumeric() {
+            return true;
+        }
+        match self {
+            Dialect::Posix => "_-./=:,+@%".contains(c),
+            // `%` starts a process expansion in older fish versions
+            Dialect::Fish => "_-./=:,+@".contains(c),
+            // `,` builds arrays and a leading `@` splats
+            Dialect::PowerShell => "_-./=:+".contains(c),
+            Dialect::Cmd => "_-./=:,+@\\".contains(c),
+        }
+    }
+}
+
+/// Split a line into words the way a POSIX shell does, without any
+/// expansion: words are separated by unquoted whitespace, single quotes
+/// keep their contents literally, and a backslash escapes the next character
+/// (inside double quotes, only `$`, `` ` ``, `"`, `\` and newline).
+pub fn split_words(line: &str) -> Result<Vec<String>, String> {
+    let mut words = Vec::new();
+    let mut word: Option<String> = None;
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => {
+                if let Some(word) = word.take() {
+                    words.push(word);
+                }
+            }
+            '\\' => {
+                let escaped = chars.next().ok_or("trailing backslash")?;
+                let word = word.get_or_insert_with(String::new);
+                if escaped != '\n' {
+                    word.push(escaped);
+                }
+            }
+            '\'' => {
+                let word = word.get_or_insert_with(String::new);
+                loop {
+                    match chars.next().ok_or("unterminated single quote")? {
+                        '\'' => break,
+                        c => word.push(c),
+                    }
+                }
+            }
+            '"' => {
+                let word = word.get_or_insert_with(String::new);
+                loop {
+                    match chars.next().ok_or("unterminated double quote")? {
+                        '"' => break,
+                        '\\' => match chars.next().ok_or("unterminated double quote")? {
+                            '\n' => {}
+                            c @ ('$' | '`' | '"' | '\\') => word.push(c),
+                            c => {
+                                word.push('\\');
+                                word.push(c);
+                            }
+                        },
+                        c => word.push(c),
+                    }
+                }
+            }
+            c => word.get_or_insert_with(String::new).push(c),
+        }
+    }
+    words.extend(word);
+    Ok(words)
+}
+
+/// Escape a word for a PowerShell single-quoted string, where `'` (and its
+/// typographic variants, which PowerShell treats the same) is doubled.
+pub(crate) fn quote_powershell_single(word: &str) -> String {
+    let mut out = String::with_capacity(word.len());
+    for c in word.chars() {
+        if matches!(c, '\'' | '\u{2018}' | '\u{2019}' | '\u{201A}' | '\u{201B}') {
+            out.push(c);
+        }
+        out.push(c);
+    }
+    out
+}
+
+/// Quote a word for the MSVC runtime's argv parser (`CommandLineToArgvW`):
+/// wrap in double quotes, escape embedded quotes with a backslash, and double
+/// any backslashes that precede a quote or the closing quote.
+///
+/// `cmd.exe` still expands `%VAR%` inside double quotes; there is no
+/// reliable way to escape it, so such values only round-trip when the
+/// variable is undefined.
+fn quote_msvc(word: &str) -> String {
+    let mut out = String::with_capacity(word.len() + 2);
+    out.push('"');
+    let mut backslashes = 0;
+    for c in word.chars() {
+        match c {
+            '\\' => backslashes += 1,
+            '"' => {
+                out.extend(std::iter::repeat_n('\\', backslashes * 2 + 1));
+                out.push('"');
+                backslashes = 0;
+            }
+            _ => {
+                out.extend(std::iter::repeat_n('\\', backslashes));
+                out.push(c);
+                backslashes = 0;
+            }
+        }
+    }
+    out.extend(std::iter::repeat_n('\\', backslashes * 2));
+    out.push('"');
+    out
+}
+
+/// Protect an MSVC-quoted word from `cmd.exe`, which turns its quoting on
+/// and off at every `"` (a `\"` included): `& | < > ^ ( ) %` outside its
+/// quotes get a `^`, and a closing quote that would turn its quoting on for
+/// the rest of the line is written as `^"`.
+fn escape_cmd(quoted: &str) -> String {
+    let mut out = String::with_capacity(quoted.len());
+    let mut in_quotes = false;
+    let last = quoted.chars().count().saturating_sub(1);
+    for (i, c) in quoted.chars().enumerate() {
+        match c {
+            '"' if i == last && i > 0 && !in_quotes => out.push('^'),
+            '"' => in_quotes = !in_quotes,
+            '&' | '|' | '<' | '>' | '^' | '(' | ')' | '%' if !in_quotes => out.push('^'),
+            _ => {}
+        }
+        out.push(c);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parts(words: &[&str]) -> Vec<String> {
+        words.iter().map(|w| w.to_string()).collect()
+    }
+
+    /// Words that need quoting in at least one dialect.
+    const TRICKY: &[&str] = &[
+        "",
+        "two words",
+        "it's",
+        "$HOME",
+        "`id`",
+        "\"quoted\"",
+        "*.rs",
+        "line\nbreak",
+        "tab\there",
+        "a\\b",
+        "trailing\\",
+        "semi;colon",
+        "pipe|amp&",
+        "~user",
+        "{a,b}",
+        "(paren)",
+        "#hash",
+        "=ls",
+        "a\"b & calc",
+        "x\"y\" | more",
+        "ünïcødé",
+    ];
+
+    #[test]
+    fn test_safe_words_stay_bare() {
+        for dialect in [
+            Dialect::Posix,
+            Dialect::Fish,
+            Dialect::PowerShell,
+            Dialect::Cmd,
+        ] {
+            for word in [
+                "mycli",
+                "--tag",
+                "-vvv",
+                "v1.2.3",
+                "./path/to-file_name",
+                "key=value",
+            ] {
+                assert_eq!(dialect.quote(word), word, "{dialect:?} {word}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_posix_quoting() {
+        assert_eq!(Dialect::Posix.quote(""), "''");
+        assert_eq!(Dialect::Posix.quote("a b"), "'a b'");
+        assert_eq!(Dialect::Posix.quote("it's"), r"'it'\''s'");
+        assert_eq!(Dialect::Posix.quote("$HOME"), "'$HOME'");
+        assert_eq!(Dialect::Posix.quote("=ls"), "'=ls'");
+        assert_eq!(
+            Dialect::Posix.join(&parts(&["mycli", "deploy", "a b", ""])),
+            "mycli deploy 'a b' ''"
+        );
+    }
+
+    #[test]
+    fn test_fish_quoting() {
+        assert_eq!(Dialect::Fish.quote("it's"), r"'it\'s'");
+        assert_eq!(Dialect::Fish.quote(r"a\b"), r"'a\\b'");
+        assert_eq!(Dialect::Fish.quote("50%"), "'50%'");
+    }
+
+    #[test]
+    fn test_powershell_quoting() {
+        assert_eq!(Dialect::PowerShell.quote("it's"), "'it''s'");
+        assert_eq!(Dialect::PowerShell.quote("$env:HOME"), "'$env:HOME'");
+        assert_eq!(Dialect::PowerShell.quote("a,b"), "'a,b'");
+        assert_eq!(
+            Dialect::PowerShell.join(&parts(&["my tool", "run"])),
+            "& 'my tool' run",
+            "quoted command names need the call operator"
+        );
+        assert_eq!(
+            Dialect::PowerShell.join(&parts(&["mycli", "a b"])),
+            "mycli 'a b'"
+        );
+    }
+
+    #[test]
+    fn test_cmd_quoting() {
+        assert_eq!(Dialect::Cmd.quote(""), "\"\"");
+        assert_eq!(Dialect::Cmd.quote("a b"), "\"a b\"");
+        assert_eq!(Dialect::Cmd.quote(r#"say "hi""#), r#""say \"hi\"""#);
+        assert_eq!(Dialect::Cmd.quote(r"C:\my dir\"), r#""C:\my dir\\""#);
+        assert_eq!(Dialect::Cmd.quote(r"C:\dir\"), r"C:\dir\");
+        // cmd's own quoting ends at the escaped quote, so `&` needs a caret
+        assert_eq!(Dialect::Cmd.quote(r#"a"b & calc"#), r#""a\"b ^& calc^""#);
+        assert_eq!(Dialect::Cmd.quote(r#"a"b"c"#), r#""a\"b\"c""#);
+    }
+
+    /// Undo cmd.exe's caret escapes the way it reads a command line,
+    /// failing on a metacharacter it would act on.
+    fn unescape_cmd(line: &str) -> String {
+        let mut out = String::new();
+        let mut in_quotes = false;
+        let mut chars = line.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '^' if !in_quotes => out.push(chars.next().expect("escaped character")),
+                '"' => {
+                    in_quotes = !in_quotes;
+                    out.push(c);
+                }
+                '&' | '|' | '<' | '>' | '(' | ')' | '%' if !in_quotes => {
+                    panic!("cmd acts on {c:?} in: {line}")
+                }
+                _ => out.push(c),
+            }
+        }
+        out
+    }
+
+    /// Split a command line the way the MSVC runtime does.
+    fn split_msvc(line: &str) -> Vec<String> {
+        let mut args = Vec::new();
+        let mut chars = line.chars().peekable();
+        loop {
+            while chars.peek() == Some(&' ') {
+                chars.next();
+            }
+            if chars.peek().is_none() {
+                return args;
+            }
+            let mut arg = String::new();
+            let mut in_quotes = false;
+            while let Some(&c) = chars.peek() {
+                match c {
+                    ' ' if !in_quotes => break,
+                    '\\' => {
+                        let mut n = 0;
+                        while chars.peek() == Some(&'\\') {
+                            chars.next();
+                            n += 1;
+                        }
+                        if chars.peek() == Some(&'"') {
+                            arg.extend(std::iter::repeat_n('\\', n / 2));
+                            if n % 2 == 1 {
+                                arg.push('"');
+                                chars.next();
+                            }
+                        } else {
+                            arg.extend(std::iter::repeat_n('\\', n));
+                        }
+                    }
+                    '"' => {
+                        in_quotes = !in_quotes;
+                        chars.next();
+                    }
+                    _ => {
+                        arg.push(c);
+                        chars.next();
+                    }
+                }
+            }
+            args.push(arg);
+        }
+    }
+
+    #[test]
+    fn test_in_dir() {
+        assert_eq!(
+            Dialect::Posix.in_dir("/src/my app", "make"),
+            "cd '/src/my app' && make"
+        );
+        assert_eq!(
+            Dialect::PowerShell.in_dir(r"C:\src", "make"),
+            r"Set-Location 'C:\src'; make"
+        );
+        assert_eq!(
+            Dialect::Cmd.in_dir(r"C:\src", "make"),
+            r"cd /d C:\src && make"
+        );
+    }
+
+    #[test]
+    fn test_split_words() {
+        assert_eq!(split_words("  a  b\tc "), Ok(parts(&["a", "b", "c"])));
+        assert_eq!(split_words(""), Ok(vec![]));
+        assert_eq!(
+            split_words(r#"--name 'two words' "it's \"quoted\"" a\ b ''"#),
+            Ok(parts(&["--name", "two words", r#"it's "quoted""#, "a b", ""]))
+        );
+        assert_eq!(split_words(r#""\$HOME \n" $HOME"#), Ok(parts(&["$HOME \\n", "$HOME"])));
+        assert_eq!(split_words("a'b'\"c\"d"), Ok(parts(&["abcd"])));
+        assert_eq!(split_words("'open"), Err("unterminated single quote".to_string()));
+        assert_eq!(split_words("\"open"), Err("unterminated double quote".to_string()));
+        assert_eq!(split_words("end\\"), Err("trailing backslash".to_string()));
+    }
+
+    #[test]
+    fn test_split_words_inverts_posix_join() {
+        let original = parts(TRICKY);
+        assert_eq!(split_words(&Dialect::Posix.join(&original)), Ok(original));
+    }
+
+    #[test]
+    fn test_cmd_roundtrip() {
+        let original = parts(TRICKY);
+        assert_eq!(
+            split_msvc(&unescape_cmd(&Dialect::Cmd.join(&original))),
+            original
+        );
+    }
+
+    /// Let a real shell split the line and print each argument NUL-terminated.
+    #[cfg(unix)]
+    fn split_with(shell: &str, dialect: Dialect, original: &[String]) -> Option<Vec<String>> {
+        let line = dialect.join(original);
+        let output = std::process::Command::new(shell)
+            .arg("-c")
+            .arg(format!("printf '%s\\0' {line}"))
+            .output()
+            .ok()?;
+        assert!(output.status.success(), "{shell} failed on: {line}");
+        let text = String::from_utf8(output.stdout).unwrap();
+        Some(
+            text.strip_suffix('\0')
+                .unwrap_or(&text)
+                .split('\0')
+                .map(String::from)
+                .collect(),
+        )
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_posix_roundtrip_through_sh() {
+        let original = parts(TRICKY);
+        let split = split_with("sh", Dialect::Posix, &original).expect("sh is available");
+        assert_eq!(split, original);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_posix_roundtrip_through_zsh() {
+        let original = parts(TRICKY);
+        // Only checked where zsh is installed
+        if let Some(split) = split_with("zsh", Dialect::Posix, &original) {
+            assert_eq!(split, original);
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_fish_roundtrip_through_fish() {
+        let original = parts(TRICKY);
+        // Only checked where fish is installed
+        if let Some(split) = split_with("fish", Dialect::Fish, &original) {
+            assert_eq!(split, original);
+        }
+    }
+}
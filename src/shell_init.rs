@@ -0,0 +1,134 @@
+//! Shell key bindings for editing the command line in TuiSage.
+//!
+//! `tuisage shell-init <shell>` prints a widget bound to Ctrl-G. It runs
+//! `tuisage --print --initial` with the command line being typed, so the
+//! builder starts from it, and puts the accepted command back into the
+//! line editor instead of running it, like fzf's history widget. The UI is
+//! drawn on the terminal while stdout, which the widget captures, only gets
+//! the printed command after the terminal is restored.
+
+/// Shells with a key binding script.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum Shell {
+    Bash,
+    Zsh,
+    Fish,
+}
+
+/// The script to `eval` (or `source`) in the shell's startup file.
+pub fn script(shell: Shell) -> &'static str {
+    match shell {
+        Shell::Bash => BASH,
+        Shell::Zsh => ZSH,
+        Shell::Fish => FISH,
+    }
+}
+
+const BASH: &str = r#"# TuiSage key binding for bash. Add to ~/.bashrc:
+#   eval "$(tuisage shell-init bash)"
+__tuisage_widget() {
+  [[ -n $READLINE_LINE ]] || return
+  local command
+  command=$(tuisage --print --shell posix --initial "$READLINE_LINE") || return
+  if [[ -n $command ]]; then
+    READLINE_LINE=$command
+    READLINE_POINT=${#command}
+  fi
+}
+bind -m emacs-standard -x '"\C-g": __tuisage_widget'
+bind -m vi-insert -x '"\C-g": __tuisage_widget'
+"#;
+
+const ZSH: &str = r#"# TuiSage key binding for zsh. Add to ~/.zshrc:
+#   eval "$(tuisage shell-init zsh)"
+tuisage-widget() {
+  [[ -n $BUFFER ]] || return
+  local command
+  command=$(tuisage --print --shell posix --initial "$BUFFER" </dev/tty)
+  if [[ $? -eq 0 && -n $command ]]; then
+    BUFFER=$command
+    CURSOR=${#BUFFER}
+  fi
+  zle reset-prompt
+}
+zle -N tuisage-widget
+bindkey -M emacs '^G' tuisage-widget
+bindkey -M viins '^G' tuisage-widget
+"#;
+
+const FISH: &str = r#"# TuiSage key binding for fish. Add to ~/.config/fish/config.fish:
+#   tuisage shell-init fish | source
+function __tuisage_widget
+    set -l line (commandline | string collect)
+    test -n "$line"; or return
+    set -l command (tuisage --print --shell fish --initial "$line" </dev/tty | string collect)
+    if test -n "$command"
+        commandline --replace -- $command
+    end
+    commandline -f repaint
+end
+bind \cg __tuisage_widget
+bind -M insert \cg __tuisage_widget
+"#;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(unix)]
+    #[test]
+    fn test_scripts_parse() {
+        // Only checked where the shell is installed
+        for (shell, program, check) in [
+            (Shell::Bash, "bash", "-n"),
+            (Shell::Zsh, "zsh", "-n"),
+            (Shell::Fish, "fish", "--no-execute"),
+        ] {
+            let Ok(output) = std::process::Command::new(program)
+                .args([check, "-c", script(shell)])
+                .output()
+            else {
+                continue;
+            };
+            assert!(
+                output.status.success(),
+                "{program}: {}",
+                String::from_utf8_lossy(&output.stderr)
+            );
+        }
+    }
+
+    /// Run the bash widget on `line` with `tuisage` replaced by `stub`, and
+    /// return the line and cursor position afterwards.
+    #[cfg(unix)]
+    fn bash_widget(stub: &str, line: &str) -> String {
+        let output = std::process::Command::new("bash")
+            .arg("-c")
+            .arg(format!(
+                "{BASH}\n{stub}\nREADLINE_LINE='{line}'\n__tuisage_widget\nprintf '%s|%s' \"$READLINE_LINE\" \"$READLINE_POINT\""
+            ))
+            .output()
+            .expect("bash is available");
+        assert!(output.status.success());
+        String::from_utf8(output.stdout).unwrap()
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_bash_widget_replaces_the_line() {
+        let stub = r#"tuisage() { [[ $* == "--print --shell posix --initial mycli dep" ]] && echo "mycli deploy 'prod env'"; }"#;
+        assert_eq!(bash_widget(stub, "mycli dep"), "mycli deploy 'prod env'|23");
+
+        // Quitting without printing, or failing, keeps the line
+        assert_eq!(bash_widget("tuisage() { :; }", "mycli dep"), "mycli dep|");
+        assert_eq!(
+            bash_widget("tuisage() { return 1; }", "mycli dep"),
+            "mycli dep|"
+        );
+        assert_eq!(
+            bash_widget("tuisage() { echo no; }", ""),
+            "|",
+            "nothing to edit"
+        );
+    }
+}
@@ -0,0 +1,256 @@
+//! Caching the output of spec commands.
+//!
+//! Commands like `mise tasks ls --usage` can take seconds, so their output is
+//! saved in the cache directory, keyed by the command and the directory it
+//! runs in. A cached spec is shown right away while the command runs again
+//! in the background ([`SpecRefresh`]), and the builder switches to the new
+//! spec if the output changed. An entry is only used while the files listed
+//! in `cache.watch` (e.g. `mise.toml`) keep the modification times they had
+//! when it was saved.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::time::SystemTime;
+
+use color_eyre::eyre::eyre;
+use serde::{Deserialize, Serialize};
+use usage::Spec;
+
+use crate::spec_source::{parse_spec_output, run_spec_command};
+
+/// The cache entry of one spec command run in one directory.
+#[derive(Debug, Clone)]
+pub struct SpecCache {
+    path: PathBuf,
+    command: String,
+    cwd: PathBuf,
+    /// Files whose changes make the cached output stale, relative to `cwd`.
+    watch: Vec<String>,
+}
+
+/// What a cache file holds.
+#[derive(Debug, Serialize, Deserialize)]
+struct CacheEntry {
+    command: String,
+    cwd: PathBuf,
+    /// Modification times of the watched files before the command ran,
+    /// `None` for files that didn't exist.
+    watched: BTreeMap<String, Option<SystemTime>>,
+    output: String,
+}
+
+impl SpecCache {
+    /// The entry for `command` run in the current directory, in the cache
+    /// directory.
+    pub fn open(command: &str, watch: &[String]) -> Option<Self> {
+        let dir = crate::paths::cache_dir()?.join("specs");
+        let cwd = std::env::current_dir().ok()?;
+        Some(Self::new(&dir, command, cwd, watch))
+    }
+
+    /// The entry for `command` run in `cwd`, stored in `dir`.
+    pub fn new(dir: &Path, command: &str, cwd: PathBuf, watch: &[String]) -> Self {
+        let key = fnv1a(format!("{}\0{}", cwd.display(), command).as_bytes());
+        Self {
+            path: dir.join(format!("{key:016x}.json")),
+            command: command.to_string(),
+            cwd,
+            watch: watch.to_vec(),
+        }
+    }
+
+    /// The cached output and its spec, unless there is none, a watched file
+    /// changed since it was saved, or it no longer parses.
+    pub fn load(&self) -> Option<(String, Spec)> {
+        let text = std::fs::read_to_string(&self.path).ok()?;
+        let entry: CacheEntry = serde_json::from_str(&text).ok()?;
+        // File names are hashes, so check that it is for this command
+        if entry.command != self.command
+            || entry.cwd != self.cwd
+            || entry.watched != self.watched_times()
+        {
+            return None;
+        }
+        let spec = parse_spec_output(&self.command, &entry.output).ok()?;
+        Some((entry.output, spec))
+    }
+
+    /// Run the spec command and parse its output, saving it when it parses.
+    pub fn fetch(&self) -> color_eyre::Result<(String, Spec)> {
+        // Taken first, so that changes made while the command runs aren't
+        // mistaken for the state its output reflects
+        let watched = self.watched_times();
+        let output = run_spec_command(&self.command)?;
+        let spec = parse_spec_output(&self.command, &output)?;
+        // Failing to save only makes the next start slower
+        let _ = self.store(watched, &output);
+        Ok((output, spec))
+    }
+
+    /// Run the spec command in the background to check `cached`, the
+    /// output the current spec was loaded from.
+    pub fn refresh(&self, cached: String) -> SpecRefresh {
+        let (sender, result) = mpsc::channel();
+        let cache = self.clone();
+        std::thread::spawn(move || {
+            let _ = sender.send(cache.fetch());
+        });
+        SpecRefresh {
+            command: self.command.clone(),
+            cached,
+            result,
+        }
+    }
+
+    fn store(
+        &self,
+        watched: BTreeMap<String, Option<SystemTime>>,
+        output: &str,
+    ) -> std::io::Result<()> {
+        let entry = CacheEntry {
+            command: self.command.clone(),
+            cwd: self.cwd.clone(),
+            watched,
+            output: output.to_string(),
+        };
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&self.path, serde_json::to_string(&entry)?)
+    }
+
+    fn watched_times(&self) -> BTreeMap<String, Option<SystemTime>> {
+        self.watch
+            .iter()
+            .map(|name| {
+                let modified = std::fs::metadata(self.cwd.join(name))
+                    .and_then(|m| m.modified())
+                    .ok();
+                (name.clone(), modified)
+            })
+            .collect()
+    }
+}
+
+/// A spec command running in the background to check a cached spec.
+pub struct SpecRefresh {
+    command: String,
+    cached: String,
+    result: mpsc::Receiver<color_eyre::Result<(String, Spec)>>,
+}
+
+impl SpecRefresh {
+    /// `None` while the command runs. Then its spec, or `Ok(None)` when the
+    /// output is the cached one.
+    pub fn poll(&self) -> Option<color_eyre::Result<Option<Spec>>> {
+        match self.result.try_recv() {
+            Ok(result) => {
+                Some(result.map(|(output, spec)| (output != self.cached).then_some(spec)))
+            }
+            Err(mpsc::TryRecvError::Empty) => None,
+            Err(mpsc::TryRecvError::Disconnected) => Some(Err(eyre!(
+                "Spec command '{}' stopped without a result",
+                self.command
+            ))),
+        }
+    }
+}
+
+/// 64-bit FNV-1a, for file names that stay the same across builds (unlike
+/// the standard library's hasher).
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, Instant};
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "tuisage-test-spec-cache-{}-{}",
+            name,
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_fetch_and_load() {
+        let dir = temp_dir("load");
+        let command = "printf 'bin \"mycli\"\\ncmd \"deploy\"'";
+        let watch = ["mise.toml".to_string()];
+        let cache = SpecCache::new(&dir.join("cache"), command, dir.clone(), &watch);
+        assert!(cache.load().is_none());
+
+        let (output, spec) = cache.fetch().unwrap();
+        assert_eq!(spec.bin, "mycli");
+        let (cached, spec) = cache.load().unwrap();
+        assert_eq!(cached, output);
+        assert!(spec.cmd.find_subcommand("deploy").is_some());
+
+        // Another command or directory has its own entry
+        let other = SpecCache::new(&dir.join("cache"), "mycli --usage", dir.clone(), &watch);
+        assert!(other.load().is_none());
+        let other = SpecCache::new(&dir.join("cache"), command, dir.join("sub"), &watch);
+        assert!(other.load().is_none());
+
+        // Creating a watched file makes it stale
+        std::fs::write(dir.join("mise.toml"), "[tasks]").unwrap();
+        assert!(cache.load().is_none());
+        cache.fetch().unwrap();
+        assert!(cache.load().is_some());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_failed_output_is_not_cached() {
+        let dir = temp_dir("failed");
+        let cache = SpecCache::new(&dir, "printf 'cmd \"broken\" {'", dir.clone(), &[]);
+        let err = cache.fetch().err().unwrap();
+        assert!(err.to_string().starts_with("Failed to parse usage spec"));
+        assert!(cache.load().is_none());
+
+        let cache = SpecCache::new(&dir, "exit 3", dir.clone(), &[]);
+        assert!(cache.fetch().is_err());
+        assert!(cache.load().is_none());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_refresh_reports_changes() {
+        let dir = temp_dir("refresh");
+        let cache = SpecCache::new(&dir, "printf 'bin \"mycli\"'", dir.clone(), &[]);
+        let wait = |refresh: &SpecRefresh| {
+            let deadline = Instant::now() + Duration::from_secs(5);
+            loop {
+                if let Some(result) = refresh.poll() {
+                    return result.unwrap();
+                }
+                assert!(Instant::now() < deadline, "refresh finished");
+                std::thread::sleep(Duration::from_millis(10));
+            }
+        };
+
+        let refresh = cache.refresh("bin \"mycli\"".to_string());
+        assert!(wait(&refresh).is_none(), "same output");
+        let refresh = cache.refresh("bin \"old\"".to_string());
+        assert_eq!(wait(&refresh).unwrap().bin, "mycli");
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_fnv1a() {
+        assert_eq!(fnv1a(b""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(fnv1a(b"a"), 0xaf63_dc4c_8601_ec8c);
+    }
+}
@@ -0,0 +1,210 @@
+//! Reading usage specs from `--spec-file` or a spec command.
+//!
+//! Besides `.usage.kdl` files, the spec can come from stdin (`-`) or from a
+//! script whose header comments hold the spec, as used by mise file tasks:
+//!
+//! ```sh
+//! #!/usr/bin/env bash
+//! #USAGE flag "--force" help="Overwrite existing files"
+//! #USAGE arg "<name>"
+//! ```
+//!
+//! Python and other scripts may write `# [USAGE]` instead, and `//` or `::`
+//! comments work too. usage-lib does the parsing; a script needs a shebang
+//! to be read as one.
+//!
+//! A [`SpecSource`] remembers where the spec came from, so it can be read
+//! again when it changes.
+
+use std::io::{IsTerminal, Read};
+use std::path::{Path, PathBuf};
+use std::process::Command as ProcessCommand;
+
+use color_eyre::eyre::eyre;
+use usage::Spec;
+
+/// The `--spec-file` value that reads the spec from stdin.
+pub const STDIN: &str = "-";
+
+/// Where the spec is read from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SpecSource {
+    /// A `--spec-file` path, or `-` for stdin.
+    File(PathBuf),
+    /// A shell command that prints the spec, e.g. `mycli --usage`.
+    Command(String),
+}
+
+impl SpecSource {
+    /// Read and parse the spec.
+    pub fn read(&self) -> color_eyre::Result<Spec> {
+        match self {
+            Self::File(path) => read_spec_file(path),
+            Self::Command(cmd) => read_spec_command(cmd),
+        }
+    }
+
+    /// Whether reading the spec again gets the current version. Stdin can
+    /// only be read once.
+    pub fn can_reload(&self) -> bool {
+        self.file() != Some(Path::new(STDIN))
+    }
+
+    /// The spec file, if the spec is read from one.
+    pub fn file(&self) -> Option<&Path> {
+        match self {
+            Self::File(path) => Some(path),
+            Self::Command(_) => None,
+        }
+    }
+
+    /// The name of the source in the config's `[specs]` table: the spec
+    /// file path or the spec command.
+    pub fn name(&self) -> String {
+        match self {
+            Self::File(path) => path.display().to_string(),
+            Self::Command(cmd) => cmd.clone(),
+        }
+    }
+}
+
+/// Read and parse the spec at `path`, or from stdin when it is `-`.
+/// usage-lib reads a script's `#USAGE` block when it starts with a shebang,
+/// defaulting `bin` to the file name; that is made a path that runs it.
+pub fn read_spec_file(path: &Path) -> color_eyre::Result<Spec> {
+    if path == Path::new(STDIN) {
+        return read_spec_stdin();
+    }
+    let mut spec = Spec::parse_file(path)
+        .map_err(|e| eyre!("Failed to parse usage spec '{}': {}", path.display(), e))?;
+    let is_script = std::fs::read_to_string(path).is_ok_and(|text| text.starts_with("#!"));
+    let file_name = path.file_name().map(|n| n.to_string_lossy());
+    if is_script && file_name.as_deref() == Some(spec.bin.as_str()) {
+        // Run the script itself rather than a program of that name
+        spec.bin = runnable_path(path).display().to_string();
+    }
+    Ok(spec)
+}
+
+fn read_spec_stdin() -> color_eyre::Result<Spec> {
+    let mut stdin = std::io::stdin();
+    if stdin.is_terminal() {
+        return Err(eyre!(
+            "Expected a usage spec on stdin, e.g. `mycli --usage | tuisage --cmd mycli -`"
+        ));
+    }
+    let mut text = String::new();
+    stdin
+        .read_to_string(&mut text)
+        .map_err(|e| eyre!("Failed to read usage spec from stdin: {}", e))?;
+    text.parse::<Spec>()
+        .map_err(|e| eyre!("Failed to parse usage spec from stdin: {}", e))
+}
+
+/// Run a spec command and parse its output.
+pub fn read_spec_command(cmd: &str) -> color_eyre::Result<Spec> {
+    let output = run_spec_command(cmd)?;
+    parse_spec_output(cmd, &output)
+}
+
+/// Parse the output of the spec command `cmd`.
+pub fn parse_spec_output(cmd: &str, output: &str) -> color_eyre::Result<Spec> {
+    output
+        .parse::<Spec>()
+        .map_err(|e| eyre!("Failed to parse usage spec from command '{}': {}", cmd, e))
+}
+
+/// Run a shell command and return its stdout as a string.
+pub fn run_spec_command(cmd: &str) -> color_eyre::Result<String> {
+    let output = if cfg!(target_os = "windows") {
+        ProcessCommand::new("cmd")
+            .args(["/C", cmd])
+            .output()
+            .map_err(|e| eyre!("Failed to run spec command '{}': {}", cmd, e))?
+    } else {
+        ProcessCommand::new("sh")
+            .args(["-c", cmd])
+            .output()
+            .map_err(|e| eyre!("Failed to run spec command '{}': {}", cmd, e))?
+    };
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(eyre!(
+            "Spec command '{}' failed with status {}{}",
+            cmd,
+            output.status,
+            if stderr.is_empty() {
+                String::new()
+            } else {
+                format!(": {}", stderr.trim())
+            }
+        ));
+    }
+
+    String::from_utf8(output.stdout)
+        .map_err(|e| eyre!("Spec command '{}' produced invalid UTF-8 output: {}", cmd, e))
+}
+
+/// A path that runs the script when used as a command: a bare file name
+/// would be looked up in `PATH`, so it is made relative to `.`.
+fn runnable_path(path: &Path) -> PathBuf {
+    if path.components().count() == 1 {
+        Path::new(".").join(path)
+    } else {
+        path.to_path_buf()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_script_spec() {
+        let dir = std::env::temp_dir().join(format!("tuisage-test-spec-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("build.sh");
+        std::fs::write(
+            &path,
+            "#!/bin/sh\n#USAGE arg \"<target>\"\n#USAGE flag \"--release\"\nmake \"$1\"\n",
+        )
+        .unwrap();
+
+        let spec = read_spec_file(&path).unwrap();
+        assert_eq!(spec.bin, path.display().to_string());
+        assert_eq!(spec.name, "build.sh");
+        assert_eq!(spec.cmd.args[0].name, "target");
+        assert_eq!(spec.cmd.flags[0].name, "release");
+
+        // A bin set in the script is kept, and Python-style blocks work too
+        let path = dir.join("deploy.py");
+        std::fs::write(
+            &path,
+            "#!/usr/bin/env python3\n# [USAGE] bin \"deploy\"\n# [USAGE] arg \"<env>\"\nimport sys\n",
+        )
+        .unwrap();
+        let spec = read_spec_file(&path).unwrap();
+        assert_eq!(spec.bin, "deploy");
+        assert_eq!(spec.cmd.args[0].name, "env");
+
+        // A KDL file's bin isn't turned into a path
+        let path = dir.join("mycli.usage.kdl");
+        std::fs::write(&path, "arg \"<name>\"\n").unwrap();
+        assert_eq!(read_spec_file(&path).unwrap().bin, "mycli.usage.kdl");
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_runnable_path() {
+        assert_eq!(
+            runnable_path(Path::new("build.sh")),
+            Path::new("./build.sh")
+        );
+        assert_eq!(
+            runnable_path(Path::new("scripts/build.sh")),
+            Path::new("scripts/build.sh")
+        );
+    }
+}
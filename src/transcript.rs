@@ -0,0 +1,375 @@
+//! Transcripts of executed commands.
+//!
+//! The execution view records the raw PTY output with the time each chunk
+//! arrived. A transcript can be saved as plain text (escape sequences
+//! stripped), raw ANSI (replayable with `cat`), and an
+//! [asciicast v2](https://docs.asciinema.org/manual/asciicast/v2/) recording
+//! (playable with `asciinema play`). Each format records the command, its
+//! argv, start and end times, and the exit status.
+
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use serde_json::json;
+
+/// A command's output as it was received from the PTY.
+#[derive(Debug, Clone)]
+pub struct Transcript {
+    /// Command string as displayed.
+    pub command: String,
+    /// Argument vector passed to the process.
+    pub argv: Vec<String>,
+    /// Terminal size as (columns, rows) when the command started.
+    pub size: (u16, u16),
+    pub started: SystemTime,
+    /// When the process exited, if it has.
+    pub ended: Option<SystemTime>,
+    /// Exit status description (e.g. "Success"), if the process exited.
+    pub exit_status: Option<String>,
+    /// Output chunks with their time since the start.
+    pub output: Vec<(Duration, Vec<u8>)>,
+    start_instant: Instant,
+}
+
+impl Transcript {
+    pub fn new(command: String, argv: Vec<String>, size: (u16, u16)) -> Self {
+        Self {
+            command,
+            argv,
+            size,
+            started: SystemTime::now(),
+            ended: None,
+            exit_status: None,
+            output: Vec::new(),
+            start_instant: Instant::now(),
+        }
+    }
+
+    /// Append a chunk of output received now.
+    pub fn record(&mut self, bytes: &[u8]) {
+        self.output
+            .push((self.start_instant.elapsed(), bytes.to_vec()));
+    }
+
+    /// Record that the process exited with the given status.
+    pub fn finish(&mut self, exit_status: Option<String>) {
+        self.ended = Some(SystemTime::now());
+        self.exit_status = exit_status;
+    }
+
+    /// The raw output stream.
+    pub fn raw(&self) -> Vec<u8> {
+        self.output
+            .iter()
+            .flat_map(|(_, b)| b.iter().copied())
+            .collect()
+    }
+
+    /// Header lines describing the run, shared by the text and ANSI formats.
+    fn header(&self) -> String {
+        let argv = serde_json::to_string(&self.argv).unwrap_or_default();
+        let mut header = format!(
+            "# command: {}\n# argv: {}\n# started: {}\n",
+            self.command,
+            argv,
+            format_utc(unix_secs(self.started))
+        );
+        if let Some(ended) = self.ended {
+            header.push_str(&format!("# ended: {}\n", format_utc(unix_secs(ended))));
+        }
+        header.push_str(&format!(
+            "# exit status: {}\n\n",
+            self.exit_status.as_deref().unwrap_or("unknown")
+        ));
+        header
+    }
+
+    /// Plain text with escape sequences and overwritten lines removed.
+    pub fn to_text(&self) -> String {
+        self.header() + &strip_escapes(&String::from_utf8_lossy(&self.raw()))
+    }
+
+    /// The raw output with escape sequences intact, after the header.
+    pub fn to_ansi(&self) -> Vec<u8> {
+        let mut bytes = self.header().into_bytes();
+        bytes.extend(self.raw());
+        bytes
+    }
+
+    /// An asciicast v2 recording: a JSON header line, then one
+    /// `[time, "o", data]` line per output chunk.
+    pub fn to_asciicast(&self) -> String {
+        let duration = self
+            .ended
+            .and_then(|e| e.duration_since(self.started).ok())
+            .or_else(|| self.output.last().map(|(t, _)| *t))
+            .unwrap_or_default();
+        let header = json!({
+            "version": 2,
+            "width": self.size.0,
+            "height": self.size.1,
+            "timestamp": unix_secs(self.started),
+            "duration": duration.as_secs_f64(),
+            "command": self.command,
+            "title": self.command,
+            "argv": self.argv,
+            "end_timestamp": self.ended.map(unix_secs),
+            "exit_status": self.exit_status,
+        });
+        let mut cast = header.to_string();
+        cast.push('\n');
+
+        // Chunks may split a UTF-8 character; carry it over to the next one
+        let mut pending = Vec::new();
+        for (time, bytes) in &self.output {
+            pending.extend_from_slice(bytes);
+            let valid = match std::str::from_utf8(&pending) {
+                Ok(_) => pending.len(),
+                Err(e) if e.error_len().is_none() => e.valid_up_to(),
+                Err(_) => pending.len(),
+            };
+            if valid == 0 {
+                continue;
+            }
+            let data = String::from_utf8_lossy(&pending[..valid]).into_owned();
+            pending.drain(..valid);
+            cast.push_str(&json!([time.as_secs_f64(), "o", data]).to_string());
+            cast.push('\n');
+        }
+        cast
+    }
+
+    /// Write the transcript in every format to `dir`, returning the text
+    /// file's path. Files are named after the start time and the program,
+    /// with a counter added when that name is taken, e.g. by another run
+    /// started in the same second.
+    pub fn save(&self, dir: &Path) -> std::io::Result<PathBuf> {
+        std::fs::create_dir_all(dir)?;
+        let program = self
+            .argv
+            .first()
+            .and_then(|p| Path::new(p).file_name())
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "command".to_string());
+        let stem = format!(
+            "{}-{}",
+            format_utc(unix_secs(self.started))
+                .replace(['-', ':'], "")
+                .replace('T', "-")
+                .trim_end_matches('Z'),
+            program
+        );
+        // Creating the text file claims the name for the other formats too
+        let mut name = stem.clone();
+        let mut count = 1;
+        let mut text_file = loop {
+            match std::fs::File::create_new(dir.join(format!("{name}.txt"))) {
+                Ok(file) => break file,
+                Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {
+                    count += 1;
+                    name = format!("{stem}-{count}");
+                }
+                Err(e) => return Err(e),
+            }
+        };
+        text_file.write_all(self.to_text().as_bytes())?;
+        std::fs::write(dir.join(format!("{name}.ansi")), self.to_ansi())?;
+        std::fs::write(dir.join(format!("{name}.cast")), self.to_asciicast())?;
+        Ok(dir.join(format!("{name}.txt")))
+    }
+}
+
+/// Default directory for saved transcripts, in the data directory.
+pub fn default_dir() -> Option<PathBuf> {
+    crate::paths::data_dir().map(|dir| dir.join("transcripts"))
+}
+
+fn unix_secs(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Format Unix seconds as an RFC 3339 UTC timestamp.
+fn format_utc(secs: u64) -> String {
+    // Civil date from days since the epoch (Howard Hinnant's algorithm)
+    let days = (secs / 86_400) as i64;
+    let rem = secs % 86_400;
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}Z",
+        rem / 3600,
+        rem % 3600 / 60,
+        rem % 60
+    )
+}
+
+/// Remove terminal escape sequences and control characters, applying
+/// carriage returns and backspaces the way a terminal would display them.
+pub fn strip_escapes(output: &str) -> String {
+    let mut text = String::new();
+    let mut line = String::new();
+    let mut chars = output.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\x1b' => match chars.next() {
+                // CSI: parameters, then a final byte in @..~
+                Some('[') => {
+                    for c in chars.by_ref() {
+                        if ('@'..='~').contains(&c) {
+                            break;
+                        }
+                    }
+                }
+                // OSC, DCS and friends: terminated by BEL or ST (ESC \)
+                Some(']' | 'P' | '_' | '^' | 'X') => {
+                    while let Some(c) = chars.next() {
+                        if c == '\x07' || (c == '\x1b' && chars.next_if_eq(&'\\').is_some()) {
+                            break;
+                        }
+                    }
+                }
+                // Character set selection takes one more character
+                Some('(' | ')' | '*' | '+') => {
+                    chars.next();
+                }
+                _ => {}
+            },
+            '\n' => {
+                text.push_str(line.trim_end());
+                text.push('\n');
+                line.clear();
+            }
+            '\r' => {
+                if chars.peek() != Some(&'\n') {
+                    line.clear();
+                }
+            }
+            '\x08' => {
+                line.pop();
+            }
+            '\t' => line.push(c),
+            c if c.is_control() => {}
+            c => line.push(c),
+        }
+    }
+    text.push_str(line.trim_end());
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn transcript() -> Transcript {
+        let mut transcript = Transcript::new(
+            "mycli deploy 'my app'".to_string(),
+            vec!["mycli".into(), "deploy".into(), "my app".into()],
+            (80, 24),
+        );
+        transcript.started = UNIX_EPOCH + Duration::from_secs(1_760_000_000);
+        transcript.output = vec![
+            (
+                Duration::from_millis(0),
+                b"\x1b[1;32mok\x1b[0m\r\n".to_vec(),
+            ),
+            (Duration::from_millis(500), vec![b'c', 0xC3]),
+            (Duration::from_millis(750), vec![0xA9, b'\r', b'\n']),
+        ];
+        transcript.ended = Some(transcript.started + Duration::from_secs(2));
+        transcript.exit_status = Some("Success".to_string());
+        transcript
+    }
+
+    #[test]
+    fn test_strip_escapes() {
+        assert_eq!(strip_escapes("\x1b[31mred\x1b[0m\r\n"), "red\n");
+        assert_eq!(strip_escapes("10%\r50%\r100%\n"), "100%\n");
+        assert_eq!(strip_escapes("\x1b]0;title\x07abx\x08c"), "abc");
+        assert_eq!(strip_escapes("\x1b]8;;http://x\x1b\\link\x1b(B"), "link");
+    }
+
+    #[test]
+    fn test_format_utc() {
+        assert_eq!(format_utc(0), "1970-01-01T00:00:00Z");
+        assert_eq!(format_utc(1_760_000_000), "2025-10-09T08:53:20Z");
+        assert_eq!(format_utc(951_782_400), "2000-02-29T00:00:00Z");
+    }
+
+    #[test]
+    fn test_text_and_ansi_formats() {
+        let transcript = transcript();
+        let header = "# command: mycli deploy 'my app'\n\
+                      # argv: [\"mycli\",\"deploy\",\"my app\"]\n\
+                      # started: 2025-10-09T08:53:20Z\n\
+                      # ended: 2025-10-09T08:53:22Z\n\
+                      # exit status: Success\n\n";
+        assert_eq!(transcript.to_text(), format!("{header}ok\ncé\n"));
+        let mut ansi = header.as_bytes().to_vec();
+        ansi.extend(transcript.raw());
+        assert_eq!(transcript.to_ansi(), ansi);
+    }
+
+    #[test]
+    fn test_asciicast_format() {
+        let cast = transcript().to_asciicast();
+        let lines: Vec<serde_json::Value> = cast
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+        assert_eq!(lines[0]["version"], 2);
+        assert_eq!(lines[0]["width"], 80);
+        assert_eq!(lines[0]["timestamp"], 1_760_000_000);
+        assert_eq!(lines[0]["duration"], 2.0);
+        assert_eq!(lines[0]["argv"], json!(["mycli", "deploy", "my app"]));
+        assert_eq!(lines[0]["exit_status"], "Success");
+        // The split "é" is carried over to the chunk that completes it
+        assert_eq!(
+            &lines[1..],
+            [
+                json!([0.0, "o", "\x1b[1;32mok\x1b[0m\r\n"]),
+                json!([0.5, "o", "c"]),
+                json!([0.75, "o", "é\r\n"]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_save_writes_all_formats() {
+        let dir =
+            std::env::temp_dir().join(format!("tuisage-test-transcript-{}", std::process::id()));
+        let path = transcript().save(&dir).unwrap();
+        assert_eq!(path, dir.join("20251009-085320-mycli.txt"));
+        assert!(dir.join("20251009-085320-mycli.ansi").exists());
+        assert!(dir.join("20251009-085320-mycli.cast").exists());
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_save_keeps_runs_started_in_the_same_second() {
+        let dir = std::env::temp_dir().join(format!(
+            "tuisage-test-transcript-same-second-{}",
+            std::process::id()
+        ));
+        let first = transcript().save(&dir).unwrap();
+        let mut other = transcript();
+        other.output = vec![(Duration::ZERO, b"second run\r\n".to_vec())];
+        let second = other.save(&dir).unwrap();
+        assert_eq!(first, dir.join("20251009-085320-mycli.txt"));
+        assert_eq!(second, dir.join("20251009-085320-mycli-2.txt"));
+        assert!(dir.join("20251009-085320-mycli-2.ansi").exists());
+        assert!(dir.join("20251009-085320-mycli-2.cast").exists());
+        assert!(std::fs::read_to_string(&first).unwrap().ends_with("ok\ncé\n"));
+        assert!(std::fs::read_to_string(&second).unwrap().ends_with("second run\n"));
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
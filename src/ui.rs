@@ -1,5 +1,7 @@
 use ratatui::{
     layout::{Constraint, Direction, Layout, Rect},
+    style::{Modifier, Style},
+    widgets::Paragraph,
     Frame,
 };
 
@@ -12,7 +14,9 @@ use crate::components::flag_panel::FlagRenderData;
 use crate::components::help_bar::{HelpBar, Keybind};
 use crate::components::preview::CommandPreview;
 use crate::components::{Component, RenderableComponent};
+use crate::config::{OutputLayout, PreviewEnter};
 use crate::theme::UiColors;
+use crate::validation::Diagnostic;
 
 /// Render the full UI: command panel, flag panel, arg panel, preview, help bar.
 pub fn render(frame: &mut Frame, app: &mut App) {
@@ -20,31 +24,45 @@ pub fn render(frame: &mut Frame, app: &mut App) {
     let colors = UiColors::from_palette(&palette);
 
     if app.mode == AppMode::Executing {
-        if let Some(ref mut exec) = app.execution {
+        if let Some(exec) = app.execution_mut() {
             exec.render(frame.area(), frame.buffer_mut(), &colors);
         }
         return;
     }
 
     let area = frame.area();
-
-    // Top-level vertical layout:
-    //   [command preview]
-    //   [main content area]
-    //   [help / status bar]
-    let outer = Layout::default()
-        .direction(Direction::Vertical)
-        .constraints([
-            Constraint::Length(3), // command preview
-            Constraint::Min(6),    // main content
-            Constraint::Length(1), // help text
-        ])
-        .split(area);
+    let outer = split_screen(area);
 
     let mut layout = UiLayout::new();
+    // Validated once per frame, for the panels and the preview
+    let diagnostics = app.diagnostics();
 
-    render_preview(frame, app, outer[0], &colors, &mut layout);
-    render_main_content(frame, app, outer[1], &colors, &mut layout);
+    let (builder_area, pane_area) = if app.has_output_pane() {
+        split_main_area(outer[1], app.config.behavior.layout)
+    } else {
+        (outer[1], None)
+    };
+    // A failed spec reload is reported above the builder panels
+    let builder_area = if app.spec_error.is_some() {
+        let split = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(1), Constraint::Min(0)])
+            .split(builder_area);
+        render_spec_error(frame, app, split[0], &colors);
+        split[1]
+    } else {
+        builder_area
+    };
+
+    render_preview(frame, app, outer[0], &colors, &diagnostics, &mut layout);
+    render_main_content(frame, app, builder_area, &colors, &diagnostics, &mut layout);
+    if let Some(pane_area) = pane_area {
+        let focused = app.focus() == Focus::Output;
+        if let Some(exec) = app.execution_mut() {
+            exec.render_pane(pane_area, frame.buffer_mut(), &colors, focused);
+        }
+        layout.click_regions.register(pane_area, Focus::Output);
+    }
     render_help_bar(frame, app, outer[2], &colors, &mut layout);
 
     // Register preview area for click hit-testing
@@ -92,15 +110,150 @@ pub fn render(frame: &mut Frame, app: &mut App) {
         }
     }
 
+    // Render history picker overlays
+    {
+        app.history_picker.set_viewport(area);
+        app.history_picker.set_mouse_position(app.mouse_position);
+        let overlays = app.history_picker.collect_overlays();
+        for req in overlays {
+            let overlay_area =
+                crate::components::clamp_overlay(req.anchor, req.size, area);
+            req.content.render(overlay_area, frame.buffer_mut(), &colors);
+            layout.history_overlay_rect = Some(overlay_area);
+        }
+    }
+
+    // Render favorites picker overlays
+    {
+        app.favorites_picker.set_viewport(area);
+        app.favorites_picker.set_mouse_position(app.mouse_position);
+        let overlays = app.favorites_picker.collect_overlays();
+        for req in overlays {
+            let overlay_area =
+                crate::components::clamp_overlay(req.anchor, req.size, area);
+            req.content.render(overlay_area, frame.buffer_mut(), &colors);
+            layout.favorites_overlay_rect = Some(overlay_area);
+        }
+    }
+
+    // Render jobs list overlays
+    {
+        app.jobs_picker.set_viewport(area);
+        app.jobs_picker.set_mouse_position(app.mouse_position);
+        let overlays = app.jobs_picker.collect_overlays();
+        for req in overlays {
+            let overlay_area =
+                crate::components::clamp_overlay(req.anchor, req.size, area);
+            req.content.render(overlay_area, frame.buffer_mut(), &colors);
+            layout.jobs_overlay_rect = Some(overlay_area);
+        }
+    }
+
+    // Render environment overlays
+    {
+        app.env_editor.set_viewport(area);
+        let overlays = app.env_editor.collect_overlays();
+        for req in overlays {
+            let overlay_area =
+                crate::components::clamp_overlay(req.anchor, req.size, area);
+            req.content.render(overlay_area, frame.buffer_mut(), &colors);
+            layout.env_overlay_rect = Some(overlay_area);
+        }
+    }
+
+    // Render working directory overlays
+    {
+        app.dir_picker.set_viewport(area);
+        let overlays = app.dir_picker.collect_overlays();
+        for req in overlays {
+            let overlay_area =
+                crate::components::clamp_overlay(req.anchor, req.size, area);
+            req.content.render(overlay_area, frame.buffer_mut(), &colors);
+            layout.dir_overlay_rect = Some(overlay_area);
+        }
+    }
+
+    // Render text prompt overlays
+    {
+        app.prompt.set_viewport(area);
+        let overlays = app.prompt.collect_overlays();
+        for req in overlays {
+            let overlay_area =
+                crate::components::clamp_overlay(req.anchor, req.size, area);
+            req.content.render(overlay_area, frame.buffer_mut(), &colors);
+            layout.prompt_overlay_rect = Some(overlay_area);
+        }
+    }
+
     app.layout = layout;
 }
 
+/// Top-level vertical layout:
+///   [command preview]
+///   [main content area]
+///   [help / status bar]
+fn split_screen(area: Rect) -> std::rc::Rc<[Rect]> {
+    Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(3), // command preview
+            Constraint::Min(6),    // main content
+            Constraint::Length(1), // help text
+        ])
+        .split(area)
+}
+
+/// Split the main content area between the builder panels and the output
+/// pane of a split layout.
+fn split_main_area(area: Rect, output: OutputLayout) -> (Rect, Option<Rect>) {
+    let (direction, builder) = match output {
+        OutputLayout::Full => return (area, None),
+        OutputLayout::Right => (Direction::Horizontal, 50),
+        OutputLayout::Below => (Direction::Vertical, 55),
+    };
+    let split = Layout::default()
+        .direction(direction)
+        .constraints([
+            Constraint::Percentage(builder),
+            Constraint::Percentage(100 - builder),
+        ])
+        .split(area);
+    (split[0], Some(split[1]))
+}
+
+/// Where the output pane of a split layout goes on a screen of this size.
+pub fn output_pane_area(area: Rect, output: OutputLayout) -> Option<Rect> {
+    split_main_area(split_screen(area)[1], output).1
+}
+
+/// Render the banner for a spec reload that failed. Parse errors can span
+/// several lines, so only the first is shown.
+fn render_spec_error(frame: &mut Frame, app: &App, area: Rect, colors: &UiColors) {
+    let error = app.spec_error.as_deref().unwrap_or_default();
+    let first_line = error
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .unwrap_or_default();
+    let text = format!(
+        " Spec not reloaded ({} to retry): {}",
+        app.config.keys.reload.label(),
+        first_line
+    );
+    let style = Style::default()
+        .fg(colors.bg)
+        .bg(colors.required)
+        .add_modifier(Modifier::BOLD);
+    frame.render_widget(Paragraph::new(text).style(style), area);
+}
+
 /// Render the main content area with panels for commands, flags, and args.
 fn render_main_content(
     frame: &mut Frame,
     app: &mut App,
     area: Rect,
     colors: &UiColors,
+    diagnostics: &[Diagnostic],
     layout: &mut UiLayout,
 ) {
     // Commands tree should always be visible (shows entire tree, not just subcommands)
@@ -121,16 +274,16 @@ fn render_main_content(
             .direction(Direction::Vertical)
             .constraints([Constraint::Percentage(60), Constraint::Percentage(40)])
             .split(h_split[1]);
-        render_flag_list(frame, app, v_split[0], colors, layout);
-        render_arg_list(frame, app, v_split[1], colors, layout);
+        render_flag_list(frame, app, v_split[0], colors, diagnostics, layout);
+        render_arg_list(frame, app, v_split[1], colors, diagnostics, layout);
     } else {
         // No commands - still show flags and args
         let v_split = Layout::default()
             .direction(Direction::Vertical)
             .constraints([Constraint::Percentage(60), Constraint::Percentage(40)])
             .split(area);
-        render_flag_list(frame, app, v_split[0], colors, layout);
-        render_arg_list(frame, app, v_split[1], colors, layout);
+        render_flag_list(frame, app, v_split[0], colors, diagnostics, layout);
+        render_arg_list(frame, app, v_split[1], colors, diagnostics, layout);
     }
 }
 
@@ -157,6 +310,7 @@ fn render_flag_list(
     app: &mut App,
     area: Rect,
     colors: &UiColors,
+    diagnostics: &[Diagnostic],
     layout: &mut UiLayout,
 ) {
     // Register area for click hit-testing
@@ -192,6 +346,7 @@ fn render_flag_list(
         flags: &flags,
         flag_values: &flag_values,
         flag_defaults: &flag_defaults,
+        diagnostics,
     };
 
     app.flag_panel
@@ -203,6 +358,7 @@ fn render_arg_list(
     app: &mut App,
     area: Rect,
     colors: &UiColors,
+    diagnostics: &[Diagnostic],
     layout: &mut UiLayout,
 ) {
     // Register area for click hit-testing
@@ -213,8 +369,11 @@ fn render_arg_list(
     app.arg_panel.set_focused(focused);
     app.arg_panel.set_mouse_position(app.mouse_position);
 
+    let extra_args = app.extra_args().to_string();
     let data = ArgRenderData {
         arg_values: &app.arg_values,
+        extra_args: &extra_args,
+        diagnostics,
     };
 
     app.arg_panel
@@ -228,12 +387,94 @@ fn render_help_bar(
     colors: &UiColors,
     layout: &mut UiLayout,
 ) {
-    let keybinds: &[Keybind] = if app.is_theme_picking() {
+    let keys = &app.config.keys;
+    let run = keys.execute.label();
+    let quit = keys.quit.label();
+    let history = keys.history.label();
+    let favorites = keys.favorites.label();
+    let save = keys.save_favorite.label();
+    let paste = keys.paste_command.label();
+    let jobs = keys.jobs.label();
+    let env = keys.env.label();
+    let cwd = keys.cwd.label();
+    let layout_key = keys.layout.label();
+    // The output pane's keys need the prefix while the command runs
+    let rerun = keys.prefixed(keys.rerun);
+    let stop = keys.prefixed(keys.terminate);
+    let background = keys.prefixed(keys.background);
+    let accept = keys.accept.label();
+    // Enter on the preview runs or prints; the other one gets its own key
+    let (preview_enter, other_key, other_desc) = match app.config.behavior.preview_enter {
+        PreviewEnter::Execute => ("run", &accept, "print"),
+        PreviewEnter::Print => ("print", &run, "run"),
+    };
+
+    let keybinds: &[Keybind] = if app.is_pasting_command() {
+        &[
+            Keybind { key: "⏎", desc: "load" },
+            Keybind { key: "Esc", desc: "cancel" },
+        ]
+    } else if app.is_prompting() || app.is_env_entry_editing() {
+        &[
+            Keybind { key: "⏎", desc: "save" },
+            Keybind { key: "Esc", desc: "cancel" },
+        ]
+    } else if app.is_dir_path_typing() {
+        &[
+            Keybind { key: "⏎", desc: "open" },
+            Keybind { key: "Esc", desc: "cancel" },
+        ]
+    } else if app.is_dir_picking() {
+        &[
+            Keybind { key: "⏎", desc: "open" },
+            Keybind { key: "Space", desc: "choose" },
+            Keybind { key: "←", desc: "up" },
+            Keybind { key: "/", desc: "type" },
+            Keybind { key: "⇥", desc: "recent" },
+            Keybind { key: "r", desc: "reset" },
+            Keybind { key: "Esc", desc: "cancel" },
+        ]
+    } else if app.is_env_editing() {
+        &[
+            Keybind { key: "⏎", desc: "edit" },
+            Keybind { key: "a", desc: "add" },
+            Keybind { key: "u", desc: "unset" },
+            Keybind { key: "d", desc: "remove" },
+            Keybind { key: "Esc", desc: "done" },
+        ]
+    } else if app.is_favorites_picking() {
+        &[
+            Keybind { key: "↑↓", desc: "navigate" },
+            Keybind { key: "⏎", desc: "load" },
+            Keybind { key: "d", desc: "delete" },
+            Keybind { key: "Esc", desc: "cancel" },
+        ]
+    } else if app.is_jobs_picking() {
+        &[
+            Keybind { key: "↑↓", desc: "navigate" },
+            Keybind { key: "⏎", desc: "show" },
+            Keybind { key: "d", desc: "dismiss" },
+            Keybind { key: "Esc", desc: "cancel" },
+        ]
+    } else if app.is_theme_picking() || app.is_history_picking() {
         &[
             Keybind { key: "↑↓", desc: "navigate" },
             Keybind { key: "⏎", desc: "confirm" },
             Keybind { key: "Esc", desc: "cancel" },
         ]
+    } else if app.is_list_entry_editing() {
+        &[
+            Keybind { key: "⏎", desc: "confirm" },
+            Keybind { key: "Esc", desc: "cancel" },
+        ]
+    } else if app.is_list_editing() {
+        &[
+            Keybind { key: "a", desc: "add" },
+            Keybind { key: "⏎", desc: "edit" },
+            Keybind { key: "d", desc: "remove" },
+            Keybind { key: "K/J", desc: "move" },
+            Keybind { key: "Esc", desc: "done" },
+        ]
     } else if app.is_choosing() {
         &[
             Keybind { key: "↑↓", desc: "select" },
@@ -263,35 +504,51 @@ fn render_help_bar(
                 Keybind { key: "↑↓", desc: "navigate" },
                 Keybind { key: "⇥", desc: "next" },
                 Keybind { key: "/", desc: "filter" },
-                Keybind { key: "^r", desc: "run" },
-                Keybind { key: "q", desc: "quit" },
+                Keybind { key: &run, desc: "run" },
+                Keybind { key: &quit, desc: "quit" },
             ],
             Focus::Flags => &[
                 Keybind { key: "⏎/Space", desc: "toggle" },
                 Keybind { key: "↑↓", desc: "navigate" },
                 Keybind { key: "⇥", desc: "next" },
                 Keybind { key: "/", desc: "filter" },
-                Keybind { key: "^r", desc: "run" },
-                Keybind { key: "q", desc: "quit" },
+                Keybind { key: &run, desc: "run" },
+                Keybind { key: &quit, desc: "quit" },
             ],
             Focus::Args => &[
                 Keybind { key: "⏎", desc: "edit" },
                 Keybind { key: "↑↓", desc: "navigate" },
                 Keybind { key: "⇥", desc: "next" },
                 Keybind { key: "/", desc: "filter" },
-                Keybind { key: "^r", desc: "run" },
-                Keybind { key: "q", desc: "quit" },
+                Keybind { key: &run, desc: "run" },
+                Keybind { key: &quit, desc: "quit" },
             ],
             Focus::Preview => &[
-                Keybind { key: "⏎", desc: "run" },
+                Keybind { key: "⏎", desc: preview_enter },
+                Keybind { key: other_key, desc: other_desc },
                 Keybind { key: "⇥", desc: "next" },
-                Keybind { key: "q", desc: "quit" },
+                Keybind { key: &history, desc: "history" },
+                Keybind { key: &favorites, desc: "favorites" },
+                Keybind { key: &save, desc: "save" },
+                Keybind { key: &paste, desc: "paste" },
+                Keybind { key: &env, desc: "env" },
+                Keybind { key: &cwd, desc: "dir" },
+                Keybind { key: &layout_key, desc: "layout" },
+                Keybind { key: &jobs, desc: "jobs" },
+                Keybind { key: &quit, desc: "quit" },
+            ],
+            Focus::Output => &[
+                Keybind { key: "⇥", desc: "builder" },
+                Keybind { key: &rerun, desc: "rerun" },
+                Keybind { key: &stop, desc: "stop" },
+                Keybind { key: &background, desc: "background" },
             ],
         }
     };
 
     let theme_display = app.theme_name.display_name();
-    let widget = HelpBar::new(keybinds, theme_display, colors);
+    let widget = HelpBar::new(keybinds, theme_display, colors)
+        .with_message(app.status_message.as_deref());
 
     // Store the theme indicator rect for mouse click detection
     layout.theme_indicator_rect = Some(widget.theme_indicator_rect(area));
@@ -305,17 +562,30 @@ fn render_preview(
     app: &App,
     area: Rect,
     colors: &UiColors,
+    diagnostics: &[Diagnostic],
     _layout: &mut UiLayout,
 ) {
     let is_focused = app.focus() == Focus::Preview;
-    let command = app.build_command();
+    let parts = app.build_preview_parts();
+    let cwd = app.cwd_label();
     let bin = if app.spec.bin.is_empty() {
         &app.spec.name
     } else {
         &app.spec.bin
     };
 
-    let widget = CommandPreview::new(&command, bin, &app.command_path, is_focused, colors);
+    let widget = CommandPreview::new(
+        &parts,
+        app.config.behavior.shell,
+        bin,
+        &app.command_path,
+        is_focused,
+        colors,
+    )
+    .with_env(app.env_vars())
+    .with_cwd(cwd.as_deref())
+    .with_problems(diagnostics.len())
+    .with_jobs(app.running_jobs(), app.jobs.len() - app.running_jobs());
     frame.render_widget(widget, area);
 }
 
@@ -490,6 +760,17 @@ mod tests {
         insta::assert_snapshot!(output);
     }
 
+    #[test]
+    fn snapshot_spec_error_banner() {
+        let mut app = App::new(sample_spec());
+        app.navigate_to_command(&["deploy"]);
+        app.spec_error = Some(
+            "Failed to parse usage spec 'mycli.usage.kdl': Failed to parse KDL document".to_string(),
+        );
+        let output = render_to_string(&mut app, 100, 24);
+        insta::assert_snapshot!(output);
+    }
+
     #[test]
     fn snapshot_deep_navigation() {
         let mut app = App::new(sample_spec());
@@ -1420,6 +1701,77 @@ flag "-q --quiet" help="Quiet mode"
         );
     }
 
+    #[test]
+    fn test_env_overlay_and_preview_prefix() {
+        let mut app = App::new(sample_spec());
+        app.open_env_editor();
+        for c in "LOG=debug".chars() {
+            app.handle_key(KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE));
+        }
+        app.handle_key(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+        let output = render_to_string(&mut app, 100, 24);
+        assert!(output.contains(" Environment "), "Overlay should be titled");
+        assert!(output.contains("▶ LOG=debug"), "Variable should be listed");
+        assert!(output.contains("u unset"), "Help bar should show editor keys");
+
+        app.handle_key(KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE));
+        let output = render_to_string(&mut app, 100, 24);
+        assert!(
+            output.contains("$ LOG=debug mycli"),
+            "Preview should show the variable before the command"
+        );
+    }
+
+    #[test]
+    fn test_dir_overlay_and_preview_cwd() {
+        let mut app = App::new(sample_spec());
+        app.open_dir_picker();
+        let output = render_to_string(&mut app, 100, 24);
+        assert!(output.contains(" Working directory "), "Overlay should be titled");
+        assert!(output.contains("▶ ./ (run here)"), "Current directory should be first");
+        assert!(output.contains("Space choose"), "Help bar should show picker keys");
+
+        app.handle_key(KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE));
+        app.set_cwd(Some(std::env::temp_dir()));
+        let label = app.cwd_label().expect("temp dir is not the launch dir");
+        let output = render_to_string(&mut app, 100, 24);
+        assert!(
+            output.contains(&format!(" in {label} ")),
+            "Preview should show the working directory"
+        );
+    }
+
+    #[test]
+    fn test_split_layout_shows_output_pane() {
+        use crate::components::execution::{ExecutionComponent, ExecutionState};
+        use std::sync::atomic::AtomicBool;
+        use std::sync::{Arc, Mutex, RwLock};
+
+        let parser = Arc::new(RwLock::new(vt100::Parser::new(5, 40, 0)));
+        parser.write().unwrap().process(b"hello from the pane\r\n");
+        let exec = ExecutionComponent::new(ExecutionState {
+            command_display: "mycli deploy".to_string(),
+            parser,
+            pty_writer: Arc::new(Mutex::new(None)),
+            pty_master: Arc::new(Mutex::new(None)),
+            exited: Arc::new(AtomicBool::new(true)),
+            exit_status: Arc::new(Mutex::new(Some("0".to_string()))),
+        });
+        let mut app = App::new(sample_spec());
+        app.config.behavior.layout = OutputLayout::Below;
+        app.start_execution(exec);
+        let output = render_to_string(&mut app, 100, 30);
+        assert!(output.contains("$ mycli deploy"), "Pane should be titled with the command");
+        assert!(output.contains("hello from the pane"), "Pane should show the output");
+        assert!(output.contains("Commands"), "Builder panels should stay visible");
+        assert!(output.contains("Tab to focus"), "Unfocused pane should say how to focus it");
+
+        app.set_focus(Focus::Output);
+        let output = render_to_string(&mut app, 100, 30);
+        assert!(output.contains("⏎/Esc close"), "Focused pane should show its keys");
+        assert!(output.contains("M-r rerun"), "Help bar should show the output pane keys");
+    }
+
     // ── Theme picker rendering tests ────────────────────────────────────
 
     #[test]
@@ -0,0 +1,355 @@
+//! Validation of the command being built.
+//!
+//! Checks the current flag and argument values against the spec before the
+//! command is run: required values, choices, the number of values of
+//! repeatable flags and variadic args, set flags that would collide on the
+//! command line, and quoting in the extra args. Each problem is reported as a [`Diagnostic`] attached to
+//! the flag or argument it concerns, so the panels can show it inline.
+
+use usage::SpecFlag;
+
+use crate::app::{ArgValue, FlagValue};
+use crate::components::flag_panel::flag_display_string;
+use crate::quoting::split_words;
+
+/// The flag or argument a diagnostic is about.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Target {
+    /// A flag, by its spec name.
+    Flag(String),
+    /// A positional argument, by its index in the current command.
+    Arg(usize),
+    /// The extra arguments passed after `--`.
+    ExtraArgs,
+}
+
+/// A problem with the current command that would likely make it fail.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub target: Target,
+    pub message: String,
+}
+
+impl Diagnostic {
+    fn flag(flag: &SpecFlag, message: impl Into<String>) -> Self {
+        Self {
+            target: Target::Flag(flag.name.clone()),
+            message: message.into(),
+        }
+    }
+
+    fn arg(index: usize, message: impl Into<String>) -> Self {
+        Self {
+            target: Target::Arg(index),
+            message: message.into(),
+        }
+    }
+
+    /// The diagnostic with the name of its flag or argument prepended, for
+    /// display away from the panels.
+    pub fn describe(&self, flags: &[&SpecFlag], args: &[ArgValue]) -> String {
+        let name = match &self.target {
+            Target::Flag(name) => flags
+                .iter()
+                .find(|f| &f.name == name)
+                .map(|f| flag_display_string(f))
+                .unwrap_or_else(|| name.clone()),
+            Target::Arg(index) => args
+                .get(*index)
+                .map(|a| format!("<{}>", a.name))
+                .unwrap_or_default(),
+            Target::ExtraArgs => "--".to_string(),
+        };
+        format!("{name}: {}", self.message)
+    }
+}
+
+/// Validate the values of the current command's flags and arguments.
+///
+/// `flags` are the flags visible for the command (including globals), and
+/// `flag_values` their values as kept by the app. `extra_args` is the text
+/// typed for after `--`.
+pub fn validate(
+    flags: &[&SpecFlag],
+    flag_values: &[(String, FlagValue)],
+    args: &[ArgValue],
+    extra_args: &str,
+) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    for flag in flags {
+        if let Some((_, value)) = flag_values.iter().find(|(n, _)| n == &flag.name) {
+            check_flag(flag, value, &mut diagnostics);
+        }
+    }
+    check_flag_conflicts(flags, flag_values, &mut diagnostics);
+    for (index, arg) in args.iter().enumerate() {
+        check_arg(index, arg, &mut diagnostics);
+    }
+    if let Err(message) = split_words(extra_args) {
+        diagnostics.push(Diagnostic {
+            target: Target::ExtraArgs,
+            message,
+        });
+    }
+    diagnostics
+}
+
+fn check_flag(flag: &SpecFlag, value: &FlagValue, diagnostics: &mut Vec<Diagnostic>) {
+    if flag.required && !value.is_set() {
+        diagnostics.push(Diagnostic::flag(flag, "required"));
+        return;
+    }
+    let choices = flag
+        .arg
+        .as_ref()
+        .and_then(|a| a.choices.as_ref())
+        .map(|c| c.choices.as_slice())
+        .unwrap_or_default();
+    match value {
+        FlagValue::String(s) if !s.is_empty() => {
+            if let Some(message) = check_choice(s, choices) {
+                diagnostics.push(Diagnostic::flag(flag, message));
+            }
+        }
+        FlagValue::Multi(values) => {
+            if let Some(message) = check_count(values.len(), flag.var_min, flag.var_max) {
+                diagnostics.push(Diagnostic::flag(flag, message));
+            }
+            for value in values {
+                if let Some(message) = check_choice(value, choices) {
+                    diagnostics.push(Diagnostic::flag(flag, message));
+                }
+            }
+        }
+        FlagValue::Count(n) if flag.var && *n > 0 => {
+            if let Some(message) = check_count(*n as usize, flag.var_min, flag.var_max) {
+                diagnostics.push(Diagnostic::flag(flag, message));
+            }
+        }
+        _ => {}
+    }
+}
+
+fn check_arg(index: usize, arg: &ArgValue, diagnostics: &mut Vec<Diagnostic>) {
+    if arg.required && !arg.is_set() {
+        diagnostics.push(Diagnostic::arg(index, "required"));
+        return;
+    }
+    if arg.var {
+        let min = arg.var_min.unwrap_or(usize::from(arg.required));
+        if let Some(message) = check_count(arg.values.len(), Some(min), arg.var_max) {
+            diagnostics.push(Diagnostic::arg(index, message));
+        }
+        for value in &arg.values {
+            if let Some(message) = check_choice(value, &arg.choices) {
+                diagnostics.push(Diagnostic::arg(index, message));
+            }
+        }
+    } else if !arg.value.is_empty() {
+        if let Some(message) = check_choice(&arg.value, &arg.choices) {
+            diagnostics.push(Diagnostic::arg(index, message));
+        }
+    }
+}
+
+fn check_choice(value: &str, choices: &[String]) -> Option<String> {
+    if choices.is_empty() || choices.iter().any(|c| c == value) {
+        None
+    } else {
+        Some(format!("'{value}' is not one of {}", choices.join("|")))
+    }
+}
+
+fn check_count(count: usize, min: Option<usize>, max: Option<usize>) -> Option<String> {
+    let noun = |n| if n == 1 { "value" } else { "values" };
+    match (min, max) {
+        (Some(min), _) if count < min => Some(format!("needs at least {min} {}", noun(min))),
+        (_, Some(max)) if count > max => Some(format!("accepts at most {max} {}", noun(max))),
+        _ => None,
+    }
+}
+
+/// Usage specs have no way to declare flags as mutually exclusive, but two
+/// set flags that share an option name (e.g. a subcommand's `-v` shadowing a
+/// global `-v`, or a negation that matches another flag) cannot both be
+/// passed unambiguously.
+fn check_flag_conflicts(
+    flags: &[&SpecFlag],
+    flag_values: &[(String, FlagValue)],
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    let set: Vec<(&SpecFlag, Vec<String>)> = flags
+        .iter()
+        .filter_map(|flag| {
+            let (_, value) = flag_values.iter().find(|(n, _)| n == &flag.name)?;
+            value.is_set().then(|| (*flag, option_names(flag, value)))
+        })
+        .collect();
+    for (i, (flag, names)) in set.iter().enumerate() {
+        for (j, (other, other_names)) in set.iter().enumerate() {
+            if i == j {
+                continue;
+            }
+            if let Some(shared) = names.iter().find(|n| other_names.contains(n)) {
+                let other = other
+                    .long
+                    .first()
+                    .map(|l| format!("--{l}"))
+                    .unwrap_or_else(|| flag_display_string(other));
+                diagnostics.push(Diagnostic::flag(
+                    flag,
+                    format!("conflicts with {other} (both use {shared})"),
+                ));
+            }
+        }
+    }
+}
+
+/// The option names a set flag is recognized by on the command line.
+fn option_names(flag: &SpecFlag, value: &FlagValue) -> Vec<String> {
+    if let FlagValue::NegBool(Some(false)) = value {
+        return flag.negate.iter().cloned().collect();
+    }
+    flag.short
+        .iter()
+        .map(|s| format!("-{s}"))
+        .chain(flag.long.iter().map(|l| format!("--{l}")))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spec(input: &str) -> usage::Spec {
+        input.parse::<usage::Spec>().expect("Failed to parse spec")
+    }
+
+    fn arg(name: &str) -> ArgValue {
+        ArgValue {
+            name: name.to_string(),
+            value: String::new(),
+            required: true,
+            choices: Vec::new(),
+            help: None,
+            var: false,
+            values: Vec::new(),
+            var_min: None,
+            var_max: None,
+            double_dash: Default::default(),
+        }
+    }
+
+    fn messages(diagnostics: &[Diagnostic]) -> Vec<(Target, &str)> {
+        diagnostics
+            .iter()
+            .map(|d| (d.target.clone(), d.message.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn test_required_args_and_flags() {
+        let spec = spec(r#"flag "--token <token>" required=#true"#);
+        let flags: Vec<&SpecFlag> = spec.cmd.flags.iter().collect();
+        let values = vec![("token".to_string(), FlagValue::String(String::new()))];
+        let mut args = vec![arg("env")];
+
+        let diagnostics = validate(&flags, &values, &args, "");
+        assert_eq!(
+            messages(&diagnostics),
+            vec![
+                (Target::Flag("token".into()), "required"),
+                (Target::Arg(0), "required"),
+            ]
+        );
+        assert_eq!(diagnostics[0].describe(&flags, &args), "--token: required");
+        assert_eq!(diagnostics[1].describe(&flags, &args), "<env>: required");
+
+        args[0].value = "prod".into();
+        let values = vec![("token".to_string(), FlagValue::String("abc".into()))];
+        assert!(validate(&flags, &values, &args, "").is_empty());
+    }
+
+    #[test]
+    fn test_choices() {
+        let spec =
+            spec(r#"flag "--format <format>" { arg "<format>" { choices "json" "yaml"; }; }"#);
+        let flags: Vec<&SpecFlag> = spec.cmd.flags.iter().collect();
+        let values = vec![("format".to_string(), FlagValue::String("xml".into()))];
+        let mut env = arg("env");
+        env.choices = vec!["dev".into(), "prod".into()];
+        env.value = "qa".into();
+
+        let diagnostics = validate(&flags, &values, &[env], "");
+        assert_eq!(
+            messages(&diagnostics),
+            vec![
+                (
+                    Target::Flag("format".into()),
+                    "'xml' is not one of json|yaml"
+                ),
+                (Target::Arg(0), "'qa' is not one of dev|prod"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_value_counts() {
+        let spec = spec(r#"flag "--tag <tag>" var=#true var_min=2 var_max=3"#);
+        let flags: Vec<&SpecFlag> = spec.cmd.flags.iter().collect();
+        let mut files = arg("files");
+        files.var = true;
+        files.var_max = Some(1);
+        files.values = vec!["a".into(), "b".into()];
+
+        let values = vec![("tag".to_string(), FlagValue::Multi(vec!["x".into()]))];
+        assert_eq!(
+            messages(&validate(&flags, &values, &[files.clone()], "")),
+            vec![
+                (Target::Flag("tag".into()), "needs at least 2 values"),
+                (Target::Arg(0), "accepts at most 1 value"),
+            ]
+        );
+
+        files.values.pop();
+        let values = vec![(
+            "tag".to_string(),
+            FlagValue::Multi(vec!["x".into(), "y".into()]),
+        )];
+        assert!(validate(&flags, &values, &[files], "").is_empty());
+    }
+
+    #[test]
+    fn test_flags_sharing_an_option_name_conflict() {
+        let spec = spec(
+            r#"
+            flag "-v --verbose" global=#true
+            cmd "show" {
+                flag "-v --version"
+            }
+            "#,
+        );
+        let show = spec.cmd.find_subcommand("show").unwrap();
+        let flags = crate::app::collect_visible_flags(show, &spec);
+        let mut values = vec![
+            ("version".to_string(), FlagValue::Bool(true)),
+            ("verbose".to_string(), FlagValue::Bool(false)),
+        ];
+        assert!(validate(&flags, &values, &[], "").is_empty());
+
+        values[1].1 = FlagValue::Bool(true);
+        assert_eq!(
+            messages(&validate(&flags, &values, &[], "")),
+            vec![
+                (
+                    Target::Flag("version".into()),
+                    "conflicts with --verbose (both use -v)"
+                ),
+                (
+                    Target::Flag("verbose".into()),
+                    "conflicts with --version (both use -v)"
+                ),
+            ]
+        );
+    }
+}
@@ -0,0 +1,300 @@
+//! Watch mode: running a command again when files change.
+//!
+//! A [`Watcher`] either listens for filesystem notifications under a
+//! directory, counting only changes to paths that match the include globs
+//! and none of the exclude globs, or fires at a fixed interval. The
+//! execution view asks it whether the command is due to run again, and the
+//! builder whether the spec file changed.
+
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use notify::{RecommendedWatcher, RecursiveMode, Watcher as _};
+
+use crate::config::WatchConfig;
+
+/// Decides when a watched command runs again.
+pub struct Watcher {
+    trigger: Trigger,
+    /// When the latest run started.
+    last_run: Instant,
+}
+
+enum Trigger {
+    /// Run after files changed and then stayed unchanged for `debounce`.
+    Files {
+        dir: PathBuf,
+        debounce: Duration,
+        /// When a matching change was last seen since the latest run started.
+        changed: Arc<Mutex<Option<Instant>>>,
+        /// Dropping the watcher stops the notifications.
+        _watcher: RecommendedWatcher,
+    },
+    /// Run this long after the latest run started.
+    Interval(Duration),
+}
+
+impl Watcher {
+    /// Watch for the configured changes under `dir`, or the configured
+    /// interval.
+    pub fn from_config(config: &WatchConfig, dir: &Path) -> color_eyre::Result<Self> {
+        match config.interval {
+            Some(interval) => Ok(Self::interval(interval.0)),
+            None => Self::files(dir, &config.include, &config.exclude, config.debounce.0),
+        }
+    }
+
+    /// Watch the files under `dir` that match `include` (all of them when
+    /// empty) and none of `exclude`.
+    pub fn files(
+        dir: &Path,
+        include: &[String],
+        exclude: &[String],
+        debounce: Duration,
+    ) -> color_eyre::Result<Self> {
+        Self::watch(dir, include, exclude, RecursiveMode::Recursive, debounce)
+    }
+
+    /// Watch a single file. Its directory is watched rather than the file,
+    /// so a file that editors replace on save is still followed.
+    pub fn file(path: &Path, debounce: Duration) -> color_eyre::Result<Self> {
+        let name = path
+            .file_name()
+            .ok_or_else(|| color_eyre::eyre::eyre!("Can't watch {}", path.display()))?;
+        let dir = match path.parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => dir,
+            _ => Path::new("."),
+        };
+        let include = [globset::escape(&name.to_string_lossy())];
+        Self::watch(dir, &include, &[], RecursiveMode::NonRecursive, debounce)
+    }
+
+    fn watch(
+        dir: &Path,
+        include: &[String],
+        exclude: &[String],
+        mode: RecursiveMode,
+        debounce: Duration,
+    ) -> color_eyre::Result<Self> {
+        let dir = dir
+            .canonicalize()
+            .map_err(|e| color_eyre::eyre::eyre!("Can't watch {}: {}", dir.display(), e))?;
+        let filter = PathFilter {
+            dir: dir.clone(),
+            include: (!include.is_empty())
+                .then(|| glob_set(include))
+                .transpose()?,
+            exclude: glob_set(exclude)?,
+        };
+        let changed: Arc<Mutex<Option<Instant>>> = Arc::new(Mutex::new(None));
+        let mut watcher = {
+            let changed = changed.clone();
+            notify::recommended_watcher(move |result: notify::Result<notify::Event>| {
+                let Ok(event) = result else {
+                    return;
+                };
+                // Reading files (as the command itself may) is not a change
+                if event.kind.is_access() || !event.paths.iter().any(|p| filter.matches(p)) {
+                    return;
+                }
+                if let Ok(mut changed) = changed.lock() {
+                    *changed = Some(Instant::now());
+                }
+            })
+        }
+        .map_err(|e| color_eyre::eyre::eyre!("Can't watch {}: {}", dir.display(), e))?;
+        watcher
+            .watch(&dir, mode)
+            .map_err(|e| color_eyre::eyre::eyre!("Can't watch {}: {}", dir.display(), e))?;
+        Ok(Self {
+            trigger: Trigger::Files {
+                dir,
+                debounce,
+                changed,
+                _watcher: watcher,
+            },
+            last_run: Instant::now(),
+        })
+    }
+
+    /// Run again every `interval`.
+    pub fn interval(interval: Duration) -> Self {
+        Self {
+            trigger: Trigger::Interval(interval),
+            last_run: Instant::now(),
+        }
+    }
+
+    /// Whether the command should run again at `now`.
+    pub fn due(&self, now: Instant) -> bool {
+        match self.trigger {
+            Trigger::Files {
+                debounce,
+                ref changed,
+                ..
+            } => changed
+                .lock()
+                .ok()
+                .and_then(|c| *c)
+                .is_some_and(|at| now.saturating_duration_since(at) >= debounce),
+            Trigger::Interval(interval) => now.saturating_duration_since(self.last_run) >= interval,
+        }
+    }
+
+    /// Note that a run started at `now`, forgetting earlier changes.
+    pub fn ran(&mut self, now: Instant) {
+        self.last_run = now;
+        if let Trigger::Files { ref changed, .. } = self.trigger {
+            if let Ok(mut changed) = changed.lock() {
+                *changed = None;
+            }
+        }
+    }
+
+    /// What the command waits for, e.g. `"changes in ~/src/app"` or
+    /// `"the next run in 25s"`.
+    pub fn describe(&self, now: Instant) -> String {
+        match self.trigger {
+            Trigger::Files { ref dir, .. } => {
+                format!("changes in {}", crate::paths::display_dir(dir))
+            }
+            Trigger::Interval(interval) => {
+                let left = interval.saturating_sub(now.saturating_duration_since(self.last_run));
+                format!("the next run in {}", crate::jobs::format_elapsed(left))
+            }
+        }
+    }
+}
+
+/// Which changed paths count.
+struct PathFilter {
+    dir: PathBuf,
+    include: Option<GlobSet>,
+    exclude: GlobSet,
+}
+
+impl PathFilter {
+    /// Whether a change to `path` counts. Globs match the path relative to
+    /// the watched directory, and `*` also matches `/`.
+    fn matches(&self, path: &Path) -> bool {
+        let relative = path.strip_prefix(&self.dir).unwrap_or(path);
+        if self.exclude.is_match(relative) {
+            return false;
+        }
+        self.include
+            .as_ref()
+            .is_none_or(|include| include.is_match(relative))
+    }
+}
+
+fn glob_set(patterns: &[String]) -> color_eyre::Result<GlobSet> {
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        let glob = Glob::new(pattern)
+            .map_err(|e| color_eyre::eyre::eyre!("Invalid watch pattern '{}': {}", pattern, e))?;
+        builder.add(glob);
+    }
+    builder
+        .build()
+        .map_err(|e| color_eyre::eyre::eyre!("Invalid watch patterns: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn filter(include: &[&str], exclude: &[&str]) -> PathFilter {
+        let strings = |p: &[&str]| p.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        PathFilter {
+            dir: PathBuf::from("/project"),
+            include: (!include.is_empty()).then(|| glob_set(&strings(include)).unwrap()),
+            exclude: glob_set(&strings(exclude)).unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_path_filter() {
+        let all = filter(&[], &["target/**", ".git/**"]);
+        assert!(all.matches(Path::new("/project/src/main.rs")));
+        assert!(all.matches(Path::new("/project/README.md")));
+        assert!(!all.matches(Path::new("/project/target/debug/app")));
+        assert!(!all.matches(Path::new("/project/.git/index")));
+
+        let rust = filter(&["*.rs", "Cargo.toml"], &["target/**"]);
+        assert!(rust.matches(Path::new("/project/src/deep/mod.rs")));
+        assert!(rust.matches(Path::new("/project/Cargo.toml")));
+        assert!(!rust.matches(Path::new("/project/README.md")));
+        assert!(!rust.matches(Path::new("/project/target/build.rs")));
+    }
+
+    #[test]
+    fn test_invalid_pattern() {
+        let err = glob_set(&["src/[".to_string()]).err().unwrap();
+        assert!(err.to_string().contains("Invalid watch pattern 'src/['"));
+    }
+
+    #[test]
+    fn test_interval_is_due_after_each_run() {
+        let mut watcher = Watcher::interval(Duration::from_secs(30));
+        let start = Instant::now();
+        watcher.ran(start);
+        assert!(!watcher.due(start + Duration::from_secs(29)));
+        assert_eq!(
+            watcher.describe(start + Duration::from_secs(5)),
+            "the next run in 25s"
+        );
+        assert!(watcher.due(start + Duration::from_secs(30)));
+        watcher.ran(start + Duration::from_secs(30));
+        assert!(!watcher.due(start + Duration::from_secs(31)));
+    }
+
+    #[test]
+    fn test_file_changes_are_debounced() {
+        let dir = std::env::temp_dir().join(format!("tuisage-test-watch-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let watcher = Watcher::files(
+            &dir,
+            &["*.txt".to_string()],
+            &[],
+            Duration::from_millis(100),
+        )
+        .unwrap();
+        std::fs::write(dir.join("ignored.log"), "log").unwrap();
+        std::thread::sleep(Duration::from_millis(300));
+        assert!(
+            !watcher.due(Instant::now()),
+            "non-matching files don't count"
+        );
+
+        std::fs::write(dir.join("notes.txt"), "hello").unwrap();
+        let due = || watcher.due(Instant::now());
+        assert!(crate::components::execution::wait_until(
+            due,
+            Duration::from_secs(5)
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_single_file_is_watched() {
+        let dir =
+            std::env::temp_dir().join(format!("tuisage-test-watch-file-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let spec = dir.join("my[cli].usage.kdl");
+        std::fs::write(&spec, "bin \"mycli\"").unwrap();
+        let watcher = Watcher::file(&spec, Duration::from_millis(100)).unwrap();
+        std::fs::write(dir.join("other.usage.kdl"), "bin \"other\"").unwrap();
+        std::thread::sleep(Duration::from_millis(300));
+        assert!(!watcher.due(Instant::now()), "other files don't count");
+
+        std::fs::write(&spec, "bin \"mycli2\"").unwrap();
+        let due = || watcher.due(Instant::now());
+        assert!(crate::components::execution::wait_until(
+            due,
+            Duration::from_secs(5)
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}